@@ -0,0 +1,822 @@
+use std::collections::HashMap;
+use std::env;
+use std::path::Path;
+use std::process::{Command, ExitCode};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::config::GgConfig;
+
+/// An `[aliases]` entry. Either a command line:
+///
+/// ```toml
+/// build = "gradle clean build && npm test"
+/// ```
+///
+/// or a table, for when that isn't enough:
+///
+/// ```toml
+/// [aliases.ci]
+/// steps = ["gradle build", "npm test || npm run test:retry"]
+/// env = { CI = "true" }
+/// cwd = "web"
+/// continue_on_error = true
+/// parallel = true
+/// ```
+///
+/// Args given to the alias are appended to the last command, unless it says
+/// where they go: `$1`, `$2`.. positional, `$@` all of them, `${name}` one of
+/// the table's `params`:
+///
+/// ```toml
+/// [aliases.deploy]
+/// description = "Publish to an environment"
+/// params = ["env", "region=eu-north-1"]
+/// steps = ["gradle publish -Penv=${env} -Pregion=${region}"]
+/// ```
+///
+/// `gg deploy prod`, `gg deploy --env=prod --region us-east-1`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Alias {
+    Command(String),
+    Table(AliasTable),
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AliasTable {
+    #[serde(default)]
+    pub steps: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Relative to the gg.toml
+    #[serde(default)]
+    pub cwd: Option<String>,
+    /// Run the next step even if one fails
+    #[serde(default)]
+    pub continue_on_error: bool,
+    /// Run all steps at once
+    #[serde(default)]
+    pub parallel: bool,
+    /// Shown in `gg aliases`, `gg help` and `gg tools`
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Named args for `${name}`, `name=default` for optional ones. Filled by
+    /// `--name=value`/`--name value`, or else by positional args in order.
+    #[serde(default)]
+    pub params: Vec<String>,
+}
+
+impl From<&str> for Alias {
+    fn from(command: &str) -> Self {
+        Alias::Command(command.to_string())
+    }
+}
+
+impl From<String> for Alias {
+    fn from(command: String) -> Self {
+        Alias::Command(command)
+    }
+}
+
+/// How a command hangs on to the one before it, like in sh.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    /// `;`, and the first command
+    Then,
+    /// `&&`
+    And,
+    /// `||`
+    Or,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chained {
+    pub op: Op,
+    pub args: Vec<String>,
+}
+
+/// Split a command line into commands on `&&`, `||` and `;`, sh-style: quotes
+/// and backslashes protect them (and whitespace), and are removed.
+pub fn tokenize(line: &str) -> Result<Vec<Chained>, String> {
+    let mut commands = vec![];
+    let mut op = Op::Then;
+    let mut args: Vec<String> = vec![];
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut chars = line.chars().peekable();
+
+    fn end_command(
+        commands: &mut Vec<Chained>,
+        args: &mut Vec<String>,
+        op: Op,
+        next: Op,
+    ) -> Result<Op, String> {
+        if args.is_empty() {
+            if op == Op::Then && next == Op::Then {
+                // `a ;; b` or a trailing `;` - nothing to chain, harmless
+                return Ok(Op::Then);
+            }
+            return Err("missing command around `&&`/`||`".to_string());
+        }
+        commands.push(Chained {
+            op,
+            args: std::mem::take(args),
+        });
+        Ok(next)
+    }
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err("unterminated ' quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') if matches!(chars.peek(), Some('"') | Some('\\')) => {
+                            word.push(chars.next().unwrap())
+                        }
+                        Some(c) => word.push(c),
+                        None => return Err("unterminated \" quote".to_string()),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                if let Some(c) = chars.next() {
+                    word.push(c);
+                }
+            }
+            '&' | '|' | ';' if c == ';' || chars.peek() == Some(&c) => {
+                if c != ';' {
+                    chars.next();
+                }
+                if in_word {
+                    args.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+                let next = match c {
+                    '&' => Op::And,
+                    '|' => Op::Or,
+                    _ => Op::Then,
+                };
+                op = end_command(&mut commands, &mut args, op, next)?;
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    args.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                word.push(c);
+            }
+        }
+    }
+    if in_word {
+        args.push(word);
+    }
+    if args.is_empty() && op != Op::Then {
+        return Err("missing command after `&&`/`||`".to_string());
+    }
+    if !args.is_empty() {
+        commands.push(Chained { op, args });
+    }
+    Ok(commands)
+}
+
+/// What an alias was called with, once its `params` are picked out.
+#[derive(Debug, Default, PartialEq)]
+pub struct AliasArgs {
+    pub positional: Vec<String>,
+    pub named: HashMap<String, String>,
+}
+
+fn placeholder_regex() -> Regex {
+    Regex::new(r"\$(@|[1-9][0-9]*|\{[A-Za-z_][A-Za-z0-9_-]*\})").unwrap()
+}
+
+/// Fill in `$1`, `$@` and `${name}` in one command's args. A bare `$@` becomes
+/// one arg per arg given, like `"$@"` in sh.
+pub fn substitute(args: &[String], bound: &AliasArgs) -> Result<Vec<String>, String> {
+    let placeholder = placeholder_regex();
+    let mut out = vec![];
+    for arg in args {
+        if arg == "$@" {
+            out.extend(bound.positional.iter().cloned());
+            continue;
+        }
+        let mut missing = None;
+        let replaced = placeholder.replace_all(arg, |caps: &regex::Captures| {
+            let key = &caps[1];
+            let value = if key == "@" {
+                Some(bound.positional.join(" "))
+            } else if let Some(name) = key.strip_prefix('{') {
+                bound.named.get(name.trim_end_matches('}')).cloned()
+            } else {
+                key.parse::<usize>()
+                    .ok()
+                    .and_then(|i| bound.positional.get(i - 1).cloned())
+            };
+            value.unwrap_or_else(|| {
+                missing.get_or_insert_with(|| caps[0].to_string());
+                String::new()
+            })
+        });
+        if let Some(missing) = missing {
+            return Err(format!("no value for {}", missing));
+        }
+        out.push(replaced.to_string());
+    }
+    Ok(out)
+}
+
+impl Alias {
+    pub fn steps(&self) -> Vec<String> {
+        match self {
+            Alias::Command(command) => vec![command.clone()],
+            Alias::Table(table) => table.steps.clone(),
+        }
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        match self {
+            Alias::Command(_) => None,
+            Alias::Table(table) => table.description.as_deref(),
+        }
+    }
+
+    /// `params` as (name, default)
+    pub fn params(&self) -> Vec<(String, Option<String>)> {
+        match self {
+            Alias::Command(_) => vec![],
+            Alias::Table(table) => table
+                .params
+                .iter()
+                .map(|p| match p.split_once('=') {
+                    Some((name, default)) => (name.to_string(), Some(default.to_string())),
+                    None => (p.to_string(), None),
+                })
+                .collect(),
+        }
+    }
+
+    /// Does it say where its args go, rather than having them appended?
+    pub fn takes_args(&self) -> bool {
+        let placeholder = placeholder_regex();
+        self.steps().iter().any(|step| placeholder.is_match(step))
+    }
+
+    /// Split args into `--param=value`/`--param value` and positional ones,
+    /// then fill params not given by name from the positional ones, in order.
+    pub fn bind(&self, args: &[String]) -> Result<AliasArgs, String> {
+        let params = self.params();
+        let is_param = |name: &str| params.iter().any(|(p, _)| p == name);
+        let mut bound = AliasArgs::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(flag) if flag.split_once('=').is_some_and(|(n, _)| is_param(n)) => {
+                    let (name, value) = flag.split_once('=').unwrap();
+                    bound.named.insert(name.to_string(), value.to_string());
+                }
+                Some(name) if is_param(name) => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("--{} needs a value", name))?;
+                    bound.named.insert(name.to_string(), value.clone());
+                }
+                _ => bound.positional.push(arg.clone()),
+            }
+        }
+
+        let mut positional = bound.positional.iter();
+        for (name, default) in &params {
+            if bound.named.contains_key(name) {
+                continue;
+            }
+            match positional.next().cloned().or_else(|| default.clone()) {
+                Some(value) => {
+                    bound.named.insert(name.clone(), value);
+                }
+                None => return Err(format!("missing <{}>", name)),
+            }
+        }
+        Ok(bound)
+    }
+
+    /// `<env> [region=eu-north-1]`, for listings and errors
+    pub fn usage(&self) -> String {
+        self.params()
+            .iter()
+            .map(|(name, default)| match default {
+                Some(default) => format!("[{}={}]", name, default),
+                None => format!("<{}>", name),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// A single plain command expands in place (`gg build x` is `gg gradle
+    /// build x`); anything else goes through `run`.
+    pub fn as_single_command(&self) -> Option<Vec<String>> {
+        if self.takes_args() {
+            return None;
+        }
+        match self {
+            Alias::Command(command) => match tokenize(command) {
+                Ok(commands) if commands.len() == 1 => Some(commands[0].args.clone()),
+                Ok(_) => None,
+                // Leave it to the tool to complain
+                Err(_) => Some(vec![command.clone()]),
+            },
+            Alias::Table(_) => None,
+        }
+    }
+
+    /// One line for listings
+    pub fn summary(&self) -> String {
+        match self {
+            Alias::Command(command) => command.clone(),
+            Alias::Table(table) => {
+                let mut summary = table.steps.join(if table.parallel { " & " } else { " ; " });
+                if let Some(cwd) = &table.cwd {
+                    summary = format!("(in {cwd}) {summary}");
+                }
+                summary
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Alias {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.summary())
+    }
+}
+
+/// Tokenize a step and put the alias' args in: into the placeholders when
+/// there are any, else after the last command.
+fn expand_step(
+    step: &str,
+    extra_args: &[String],
+    bound: Option<&AliasArgs>,
+) -> Result<Vec<Chained>, String> {
+    let mut commands =
+        tokenize(step).map_err(|e| format!("Invalid alias step '{}': {}", step, e))?;
+    match bound {
+        Some(bound) => {
+            for command in &mut commands {
+                command.args = substitute(&command.args, bound)
+                    .map_err(|e| format!("In alias step '{}': {}", step, e))?;
+            }
+        }
+        None => {
+            if let Some(last) = commands.last_mut() {
+                last.args.extend(extra_args.iter().cloned());
+            }
+        }
+    }
+    Ok(commands)
+}
+
+/// Run one step the way sh would, each command as a child gg. Returns its exit
+/// code.
+fn run_step(commands: &[Chained], env_vars: &HashMap<String, String>, cwd: Option<&Path>) -> i32 {
+    // Absolute, or a relative argv[0] breaks as soon as cwd changes
+    let gg = env::current_exe()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| env::args().next().unwrap_or_default());
+    let mut code = 0;
+    for command in commands {
+        let run = match command.op {
+            Op::Then => true,
+            Op::And => code == 0,
+            Op::Or => code != 0,
+        };
+        if !run {
+            continue;
+        }
+        println!("Executing: {}", command.args.join(" "));
+        let mut child = Command::new(&gg);
+        child.args(&command.args).envs(env_vars);
+        if let Some(cwd) = cwd {
+            child.current_dir(cwd);
+        }
+        code = match child.status() {
+            Ok(status) => status.code().unwrap_or(1),
+            Err(e) => {
+                println!("Failed to execute command: {}", e);
+                1
+            }
+        };
+        if code != 0 {
+            println!("Command failed: {}", command.args.join(" "));
+        }
+    }
+    code
+}
+
+/// Run an alias that isn't a single plain command: its steps in order (or all
+/// at once with `parallel`), stopping at the first failure unless
+/// `continue_on_error`.
+pub fn run(config: &GgConfig, name: &str, extra_args: &[String]) -> ExitCode {
+    let Some(alias) = config.aliases.get(name) else {
+        println!("Unknown alias '{}'", name);
+        return ExitCode::from(1);
+    };
+    if let Some(cycle) = alias_cycles(config)
+        .into_iter()
+        .find(|c| c.iter().any(|n| n == name))
+    {
+        println!(
+            "Alias '{}' calls itself: {} -> {}",
+            name,
+            cycle.join(" -> "),
+            cycle[0]
+        );
+        return ExitCode::from(1);
+    }
+    let table = match alias {
+        Alias::Table(table) => table.clone(),
+        Alias::Command(_) => AliasTable::default(),
+    };
+
+    let bound = if alias.takes_args() {
+        match alias.bind(extra_args) {
+            Ok(bound) => Some(bound),
+            Err(e) => {
+                println!("{}", e);
+                println!("Usage: gg {} {}", name, alias.usage());
+                return ExitCode::from(1);
+            }
+        }
+    } else {
+        None
+    };
+    let steps = alias.steps();
+    let last = steps.len().saturating_sub(1);
+    let steps = match steps
+        .iter()
+        .enumerate()
+        .map(|(i, step)| {
+            let args = if table.parallel || i == last {
+                extra_args
+            } else {
+                &[]
+            };
+            expand_step(step, args, bound.as_ref())
+        })
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(steps) => steps,
+        Err(e) => {
+            println!("{}", e);
+            return ExitCode::from(1);
+        }
+    };
+
+    let cwd = table.cwd.as_ref().map(|cwd| match &config.root {
+        Some(root) => root.join(cwd),
+        None => Path::new(cwd).to_path_buf(),
+    });
+    let env_vars = table
+        .env
+        .iter()
+        .map(|(k, v)| (k.clone(), config.interpolate(v, &HashMap::new())))
+        .collect::<HashMap<_, _>>();
+    let mut env_vars = env_vars;
+    if cwd.is_some() {
+        // A local (-l) cache is relative to where we are now
+        if let Ok(cache) = env::var("GG_CACHE_DIR") {
+            if let Ok(here) = env::current_dir() {
+                env_vars.insert(
+                    "GG_CACHE_DIR".to_string(),
+                    here.join(cache).to_string_lossy().to_string(),
+                );
+            }
+        }
+    }
+
+    let codes = if table.parallel {
+        std::thread::scope(|scope| {
+            let handles = steps
+                .iter()
+                .map(|step| {
+                    let (env_vars, cwd) = (&env_vars, cwd.as_deref());
+                    scope.spawn(move || run_step(step, env_vars, cwd))
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|h| h.join().unwrap_or(1))
+                .collect::<Vec<_>>()
+        })
+    } else {
+        let mut codes = vec![];
+        for step in &steps {
+            let code = run_step(step, &env_vars, cwd.as_deref());
+            codes.push(code);
+            if code != 0 && !table.continue_on_error {
+                break;
+            }
+        }
+        codes
+    };
+
+    match codes.into_iter().find(|code| *code != 0) {
+        Some(code) => ExitCode::from(code as u8),
+        None => ExitCode::from(0),
+    }
+}
+
+/// Aliases that end up running themselves, each loop once, e.g. `["a", "b"]`
+/// for `a = "b"` and `b = "a && c"`. Running one would never end.
+pub fn alias_cycles(config: &GgConfig) -> Vec<Vec<String>> {
+    let calls = |name: &str| -> Vec<String> {
+        let mut called = config.aliases[name]
+            .steps()
+            .iter()
+            .flat_map(|step| tokenize(step).unwrap_or_default())
+            .filter_map(|c| c.args.first().cloned())
+            .filter(|first| config.aliases.contains_key(first))
+            .collect::<Vec<_>>();
+        called.sort();
+        called.dedup();
+        called
+    };
+
+    fn visit(
+        name: &str,
+        stack: &mut Vec<String>,
+        done: &mut Vec<String>,
+        cycles: &mut Vec<Vec<String>>,
+        calls: &dyn Fn(&str) -> Vec<String>,
+    ) {
+        if let Some(start) = stack.iter().position(|n| n == name) {
+            let mut cycle = stack[start..].to_vec();
+            // Same loop found from another entry point: start at the smallest
+            let min = (0..cycle.len()).min_by_key(|i| &cycle[*i]).unwrap_or(0);
+            cycle.rotate_left(min);
+            if !cycles.contains(&cycle) {
+                cycles.push(cycle);
+            }
+            return;
+        }
+        if done.iter().any(|n| n == name) {
+            return;
+        }
+        stack.push(name.to_string());
+        for called in calls(name) {
+            visit(&called, stack, done, cycles, calls);
+        }
+        stack.pop();
+        done.push(name.to_string());
+    }
+
+    let mut names = config.aliases.keys().collect::<Vec<_>>();
+    names.sort();
+    let mut cycles = vec![];
+    let mut done = vec![];
+    for name in names {
+        visit(name, &mut vec![], &mut done, &mut cycles, &calls);
+    }
+    cycles
+}
+
+/// (name with usage, description or what it runs), sorted by name
+fn listing(config: &GgConfig) -> Vec<(String, String)> {
+    let mut names = config.aliases.keys().collect::<Vec<_>>();
+    names.sort();
+    names
+        .into_iter()
+        .map(|name| {
+            let alias = &config.aliases[name];
+            let usage = alias.usage();
+            let name = if usage.is_empty() {
+                name.to_string()
+            } else {
+                format!("{} {}", name, usage)
+            };
+            let about = alias
+                .description()
+                .map(|d| d.to_string())
+                .unwrap_or_else(|| alias.summary());
+            (name, about)
+        })
+        .collect()
+}
+
+fn print_listing(config: &GgConfig) {
+    for (name, about) in listing(config) {
+        if name.len() < 16 {
+            println!("    {:<15} {}", name, about);
+        } else {
+            println!("    {}\n                    {}", name, about);
+        }
+    }
+}
+
+/// `gg aliases`
+pub fn print_aliases(config: &GgConfig) {
+    if config.aliases.is_empty() {
+        println!("No aliases in gg.toml");
+        return;
+    }
+    println!("Aliases:");
+    print_listing(config);
+}
+
+/// The gg.toml aliases section of `gg help` and `gg tools`, if there are any.
+pub fn print_project_aliases(config: &GgConfig) {
+    if config.aliases.is_empty() {
+        return;
+    }
+    println!("Project aliases (gg.toml):");
+    print_listing(config);
+    println!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chained(op: Op, args: &[&str]) -> Chained {
+        Chained {
+            op,
+            args: args.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_tokenize_operators() {
+        assert_eq!(
+            tokenize("gradle build && npm test || echo failed; echo done").unwrap(),
+            vec![
+                chained(Op::Then, &["gradle", "build"]),
+                chained(Op::And, &["npm", "test"]),
+                chained(Op::Or, &["echo", "failed"]),
+                chained(Op::Then, &["echo", "done"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_respects_quotes() {
+        assert_eq!(
+            tokenize(r#"node -e "a && b" 'c || d' e\;f"#).unwrap(),
+            vec![chained(
+                Op::Then,
+                &["node", "-e", "a && b", "c || d", "e;f"]
+            )]
+        );
+        assert_eq!(
+            tokenize(r#"echo "say \"hi\"" '' x"#).unwrap(),
+            vec![chained(Op::Then, &["echo", "say \"hi\"", "", "x"])]
+        );
+        // A lone & or | is just text
+        assert_eq!(
+            tokenize("echo a&b c|d").unwrap(),
+            vec![chained(Op::Then, &["echo", "a&b", "c|d"])]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_errors() {
+        assert!(tokenize("echo 'oops").is_err());
+        assert!(tokenize("&& echo").is_err());
+        assert!(tokenize("echo &&").is_err());
+        assert_eq!(tokenize("echo;").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_alias_forms() {
+        let config: GgConfig = toml::from_str(
+            r#"
+[aliases]
+build = "gradle clean build"
+both = "gradle build && npm test"
+quoted = "node -e 'a && b'"
+
+[aliases.ci]
+steps = ["gradle build", "npm test"]
+env = { CI = "true" }
+cwd = "web"
+parallel = true
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.aliases["build"].as_single_command(),
+            Some(vec![
+                "gradle".to_string(),
+                "clean".to_string(),
+                "build".to_string()
+            ])
+        );
+        assert_eq!(config.aliases["both"].as_single_command(), None);
+        assert_eq!(
+            config.aliases["quoted"].as_single_command().unwrap().len(),
+            3
+        );
+
+        let Alias::Table(ci) = &config.aliases["ci"] else {
+            panic!("ci should be a table");
+        };
+        assert!(ci.parallel);
+        assert!(!ci.continue_on_error);
+        assert_eq!(ci.env["CI"], "true");
+        assert_eq!(ci.cwd.as_deref(), Some("web"));
+        assert_eq!(config.aliases["ci"].as_single_command(), None);
+        assert_eq!(
+            config.aliases["ci"].summary(),
+            "(in web) gradle build & npm test"
+        );
+    }
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_positional_placeholders() {
+        let alias: Alias = "gradle publish -Penv=$1 $@".into();
+        assert!(alias.takes_args());
+        assert_eq!(alias.as_single_command(), None);
+
+        let bound = alias.bind(&strings(&["prod", "--info"])).unwrap();
+        let step = expand_step(&alias.steps()[0], &[], Some(&bound)).unwrap();
+        assert_eq!(
+            step[0].args,
+            strings(&["gradle", "publish", "-Penv=prod", "prod", "--info"])
+        );
+
+        let bound = alias.bind(&[]).unwrap();
+        assert_eq!(
+            expand_step(&alias.steps()[0], &[], Some(&bound)).unwrap_err(),
+            "In alias step 'gradle publish -Penv=$1 $@': no value for $1"
+        );
+    }
+
+    #[test]
+    fn test_named_params() {
+        let config: GgConfig = toml::from_str(
+            r#"
+[aliases.deploy]
+description = "Publish to an environment"
+params = ["env", "region=eu-north-1"]
+steps = ["gradle publish -Penv=${env} -Pregion=${region}"]
+"#,
+        )
+        .unwrap();
+        let deploy = &config.aliases["deploy"];
+        assert_eq!(deploy.description(), Some("Publish to an environment"));
+        assert_eq!(deploy.usage(), "<env> [region=eu-north-1]");
+
+        let args = |given: &[&str]| {
+            let bound = deploy.bind(&strings(given))?;
+            Ok::<_, String>(
+                expand_step(&deploy.steps()[0], &[], Some(&bound))?[0].args[2..].to_vec(),
+            )
+        };
+        assert_eq!(
+            args(&["prod"]).unwrap(),
+            strings(&["-Penv=prod", "-Pregion=eu-north-1"])
+        );
+        assert_eq!(
+            args(&["--region", "us-east-1", "--env=test"]).unwrap(),
+            strings(&["-Penv=test", "-Pregion=us-east-1"])
+        );
+        assert_eq!(args(&[]).unwrap_err(), "missing <env>");
+        assert_eq!(args(&["--env"]).unwrap_err(), "--env needs a value");
+
+        assert_eq!(
+            listing(&config),
+            vec![(
+                "deploy <env> [region=eu-north-1]".to_string(),
+                "Publish to an environment".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_args_appended_without_placeholders() {
+        let step = expand_step("gradle build && npm test", &strings(&["-x"]), None).unwrap();
+        assert_eq!(step[0].args, strings(&["gradle", "build"]));
+        assert_eq!(step[1].args, strings(&["npm", "test", "-x"]));
+    }
+}
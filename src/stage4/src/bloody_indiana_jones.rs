@@ -5,7 +5,7 @@ use std::path::{Path, PathBuf};
 
 use futures_util::StreamExt;
 use indicatif::ProgressBar;
-use log::{debug, info};
+use log::{debug, info, warn};
 use tempfile::tempdir;
 use tokio::task;
 
@@ -91,12 +91,21 @@ impl BloodyIndianaJones {
         // a single hiccup doesn't fail the whole run.
         let max_attempts = 5;
         let mut last_error = String::new();
+        let mut url = crate::mirror::mirrored(&self.url);
         for attempt in 1..=max_attempts {
-            match self.try_download(&client).await {
+            match self.try_download(&client, &url).await {
                 Ok(()) => {
                     info!("Downloaded {} to {}", &self.url, &self.file_path);
                     return;
                 }
+                // A mirror that is down or can't get it shouldn't stop the download
+                Err((reason, _)) if url != self.url && attempt < max_attempts => {
+                    warn!(
+                        "Mirror failed for {} ({}), downloading it directly",
+                        &self.url, reason
+                    );
+                    url = self.url.clone();
+                }
                 Err((reason, retryable)) if retryable && attempt < max_attempts => {
                     let backoff = std::time::Duration::from_secs(attempt as u64);
                     info!(
@@ -116,8 +125,12 @@ impl BloodyIndianaJones {
     }
 
     /// One download attempt. Err carries (reason, retryable).
-    async fn try_download(&self, client: &reqwest::Client) -> Result<(), (String, bool)> {
-        let res = match client.get(&self.url).send().await {
+    async fn try_download(
+        &self,
+        client: &reqwest::Client,
+        url: &str,
+    ) -> Result<(), (String, bool)> {
+        let res = match client.get(url).send().await {
             Ok(res) => res,
             Err(e) => return Err((format!("request failed: {e}"), true)),
         };
@@ -0,0 +1,506 @@
+use std::fs::{self, File};
+use std::io::{BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+use std::process::ExitCode;
+
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use crate::cache;
+use crate::config::GgConfig;
+use crate::executor::{AppInput, GgMeta};
+use crate::prepare::{executors_for, prep_all, specs_or_config, with_deps};
+use crate::target::{Arch, Os, Target, Variant};
+
+/// First entry of every bundle, so import knows what it is getting before
+/// unpacking anything
+const BUNDLE_FILE: &str = "gg-bundle.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct BundleInfo {
+    target: String,
+    /// Install dirs, relative to the cache dir
+    dirs: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    fn from_file_name(name: &str) -> Option<Self> {
+        if name.ends_with(".tar.zst") || name.ends_with(".tzst") {
+            Some(Self::Zstd)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Self::Gzip)
+        } else if name.ends_with(".tar") {
+            Some(Self::None)
+        } else {
+            None
+        }
+    }
+
+    /// By magic bytes, so a renamed bundle still imports
+    fn sniff(path: &Path) -> std::io::Result<Self> {
+        let mut magic = [0u8; 4];
+        let n = File::open(path)?.read(&mut magic)?;
+        Ok(match &magic[..n] {
+            [0x28, 0xb5, 0x2f, 0xfd] => Self::Zstd,
+            [0x1f, 0x8b, ..] => Self::Gzip,
+            _ => Self::None,
+        })
+    }
+}
+
+/// `linux-x86_64`, `linux-x86_64-musl`, `mac-arm64`, `windows-x86_64`
+fn parse_target(s: &str) -> Result<Target, String> {
+    let bad = || {
+        format!(
+            "Unknown target '{}', expected <os>-<arch>[-musl] like linux-x86_64, mac-arm64 or windows-x86_64",
+            s
+        )
+    };
+    let mut parts = s.split('-');
+    let (Some(os), Some(arch)) = (parts.next(), parts.next()) else {
+        return Err(bad());
+    };
+    let variant = match parts.next().map(str::to_lowercase).as_deref() {
+        None | Some("gnu") => None,
+        Some("musl") => Some(Variant::Musl),
+        Some(_) => return Err(bad()),
+    };
+    if parts.next().is_some() {
+        return Err(bad());
+    }
+    let os = match os.to_lowercase().as_str() {
+        "windows" | "win" => Os::Windows,
+        "linux" => Os::Linux,
+        "mac" | "macos" | "darwin" => Os::Mac,
+        _ => return Err(bad()),
+    };
+    let arch = match arch.to_lowercase().as_str() {
+        "x86_64" | "x64" | "amd64" => Arch::X86_64,
+        "arm64" | "aarch64" => Arch::Arm64,
+        "armv7" | "arm" => Arch::Armv7,
+        _ => return Err(bad()),
+    };
+    Ok(Target { os, arch, variant })
+}
+
+/// Bundle dirs are `<tool>/<dir>` inside the cache, nothing else: import
+/// deletes them before unpacking, so one like `../..` must never get that far
+fn check_dir(dir: &str) -> Result<(), String> {
+    let path = Path::new(dir);
+    let normal = path.components().all(|c| matches!(c, Component::Normal(_)));
+    if normal && path.components().count() == 2 {
+        Ok(())
+    } else {
+        Err(format!("bad install dir '{}' in {}", dir, BUNDLE_FILE))
+    }
+}
+
+async fn compress(tar: &Path, out: &Path, compression: Compression) -> std::io::Result<()> {
+    let reader = tokio::io::BufReader::new(tokio::fs::File::open(tar).await?);
+    let mut writer = tokio::fs::File::create(out).await?;
+    match compression {
+        Compression::Zstd => {
+            let mut encoder = async_compression::tokio::bufread::ZstdEncoder::new(reader);
+            tokio::io::copy(&mut encoder, &mut writer).await?;
+        }
+        Compression::Gzip => {
+            let mut encoder = async_compression::tokio::bufread::GzipEncoder::new(reader);
+            tokio::io::copy(&mut encoder, &mut writer).await?;
+        }
+        Compression::None => {
+            let mut reader = reader;
+            tokio::io::copy(&mut reader, &mut writer).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn decompress(bundle: &Path, tar: &Path) -> std::io::Result<()> {
+    let reader = tokio::io::BufReader::new(tokio::fs::File::open(bundle).await?);
+    let mut writer = tokio::fs::File::create(tar).await?;
+    match Compression::sniff(bundle)? {
+        Compression::Zstd => {
+            let mut decoder = async_compression::tokio::bufread::ZstdDecoder::new(reader);
+            tokio::io::copy(&mut decoder, &mut writer).await?;
+        }
+        Compression::Gzip => {
+            let mut decoder = async_compression::tokio::bufread::GzipDecoder::new(reader);
+            tokio::io::copy(&mut decoder, &mut writer).await?;
+        }
+        Compression::None => {
+            let mut reader = reader;
+            tokio::io::copy(&mut reader, &mut writer).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Packs `dirs` (relative to `cache_dir`) into `out`
+async fn pack(cache_dir: &Path, info: &BundleInfo, out: &Path) -> Result<(), String> {
+    let compression = Compression::from_file_name(&out.to_string_lossy())
+        .ok_or("Bundle name must end in .tar.zst, .tar.gz or .tar")?;
+    let temp = tempfile::NamedTempFile::new().map_err(|e| e.to_string())?;
+    {
+        let mut builder = tar::Builder::new(temp.as_file());
+        builder.follow_symlinks(false);
+        let json = serde_json::to_vec_pretty(info).map_err(|e| e.to_string())?;
+        let mut header = tar::Header::new_gnu();
+        header.set_size(json.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, BUNDLE_FILE, json.as_slice())
+            .map_err(|e| e.to_string())?;
+        for dir in &info.dirs {
+            builder
+                .append_dir_all(dir, cache_dir.join(dir))
+                .map_err(|e| format!("{}: {}", dir, e))?;
+        }
+        builder.finish().map_err(|e| e.to_string())?;
+    }
+    compress(temp.path(), out, compression)
+        .await
+        .map_err(|e| format!("{}: {}", out.display(), e))
+}
+
+/// Unpacks a bundle into the cache for its target (see `cache::target_dir`),
+/// replacing installs already there. Returns where it went.
+async fn unpack(
+    bundle: &Path,
+    cache_base: &Path,
+    host: &str,
+) -> Result<(BundleInfo, PathBuf), String> {
+    let temp = tempfile::NamedTempFile::new().map_err(|e| e.to_string())?;
+    decompress(bundle, temp.path())
+        .await
+        .map_err(|e| format!("{}: {}", bundle.display(), e))?;
+
+    let not_a_bundle = || format!("{} is not a gg bundle", bundle.display());
+    let mut archive = tar::Archive::new(BufReader::new(
+        File::open(temp.path()).map_err(|e| e.to_string())?,
+    ));
+    archive.set_preserve_permissions(true);
+    let mut entries = archive.entries().map_err(|_| not_a_bundle())?;
+    let mut first = entries
+        .next()
+        .ok_or_else(not_a_bundle)?
+        .map_err(|_| not_a_bundle())?;
+    if first.path().map_err(|_| not_a_bundle())?.as_ref() != Path::new(BUNDLE_FILE) {
+        return Err(not_a_bundle());
+    }
+    let mut json = String::new();
+    first
+        .read_to_string(&mut json)
+        .map_err(|_| not_a_bundle())?;
+    let info: BundleInfo = serde_json::from_str(&json).map_err(|_| not_a_bundle())?;
+    for dir in &info.dirs {
+        check_dir(dir)?;
+    }
+    // The cache dir is named after it, so only a target gg knows, as gg names it
+    let target = parse_target(&info.target)
+        .map_err(|e| format!("{} in {}", e, BUNDLE_FILE))?
+        .name();
+
+    let cache_dir = cache::target_dir(cache_base, host, &target);
+    let cache_dir = cache_dir.as_path();
+    for dir in &info.dirs {
+        let path = cache_dir.join(dir);
+        if path.exists() {
+            info!("Replacing {}", path.display());
+            fs::remove_dir_all(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        }
+    }
+    fs::create_dir_all(cache_dir).map_err(|e| e.to_string())?;
+    for entry in entries {
+        let mut entry = entry.map_err(|e| e.to_string())?;
+        // Only into the installs the bundle says it has, never over gg itself
+        // or tools it didn't declare
+        let path = entry.path().map_err(|e| e.to_string())?.into_owned();
+        if !info.dirs.iter().any(|dir| path.starts_with(dir)) {
+            return Err(format!(
+                "{}: {} is not in a bundled install dir",
+                bundle.display(),
+                path.display()
+            ));
+        }
+        // unpack_in refuses paths that would land outside cache_dir
+        entry
+            .unpack_in(cache_dir)
+            .map_err(|e| format!("{}: {}", bundle.display(), e))?;
+    }
+    Ok((info, cache_dir.to_path_buf()))
+}
+
+fn describe(cache_dir: &Path, dir: &str) -> String {
+    let meta = fs::read_to_string(cache_dir.join(dir).join("gg-meta.json"))
+        .ok()
+        .and_then(|json| serde_json::from_str::<GgMeta>(&json).ok());
+    match meta {
+        Some(meta) => format!(
+            "{} {}",
+            meta.cmd.cmd,
+            meta.download
+                .version
+                .map(|v| v.to_string())
+                .unwrap_or_default()
+        ),
+        None => dir.to_string(),
+    }
+}
+
+/// `gg bundle create`: install gg.toml's tools (or `specs`) for a target and
+/// pack them up for a machine without internet
+pub async fn create(
+    input: &AppInput,
+    cache_base: &Path,
+    host: &Target,
+    config: &GgConfig,
+    specs: &[String],
+    target: Option<&str>,
+    out: &str,
+) -> ExitCode {
+    let out = PathBuf::from(out);
+    if Compression::from_file_name(&out.to_string_lossy()).is_none() {
+        println!("Bundle name must end in .tar.zst, .tar.gz or .tar");
+        return ExitCode::from(1);
+    }
+    let mut target_input = AppInput {
+        target: input.target,
+        app_args: vec![],
+    };
+    if let Some(target) = target {
+        match parse_target(target) {
+            Ok(target) => target_input.target = target,
+            Err(e) => {
+                println!("{}", e);
+                return ExitCode::from(1);
+            }
+        }
+    }
+    let cmds = match specs_or_config(config, specs) {
+        Ok(cmds) => cmds,
+        Err(e) => {
+            println!("{}", e);
+            return ExitCode::from(1);
+        }
+    };
+
+    // Another target's installs are prepared in that target's own cache
+    let cache_dir = cache::target_dir(cache_base, &host.name(), &target_input.target.name());
+    cache::set_target(&host.name(), &target_input.target.name());
+
+    let executors = with_deps(executors_for(&cmds), &target_input).await;
+    let prepared = match prep_all(&executors, &target_input).await {
+        Ok(prepared) => prepared,
+        Err(errors) => {
+            for e in errors {
+                println!("Prep failed: {}", e);
+            }
+            return ExitCode::from(1);
+        }
+    };
+
+    let Ok(cache_root) = cache_dir.canonicalize() else {
+        println!("Cache dir {} is missing", cache_dir.display());
+        return ExitCode::from(1);
+    };
+    let mut dirs = vec![];
+    for app_path in &prepared.app_paths {
+        // Tools gg doesn't download (custom_prep) live outside the cache
+        let Some(dir) = app_path
+            .install_dir
+            .canonicalize()
+            .ok()
+            .and_then(|d| d.strip_prefix(&cache_root).ok().map(Path::to_path_buf))
+        else {
+            info!("Not bundling {}", app_path.install_dir.display());
+            continue;
+        };
+        let dir = dir
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    let info = BundleInfo {
+        target: target_input.target.name(),
+        dirs,
+    };
+
+    if let Err(e) = pack(&cache_dir, &info, &out).await {
+        println!("Could not create bundle: {}", e);
+        return ExitCode::from(1);
+    }
+    for dir in &info.dirs {
+        println!("    {}", describe(&cache_dir, dir));
+    }
+    let size = fs::metadata(&out).map(|m| m.len()).unwrap_or(0);
+    println!(
+        "Bundled {} tools for {} in {} ({})",
+        info.dirs.len(),
+        info.target,
+        out.display(),
+        cache::human_size(size)
+    );
+    ExitCode::from(0)
+}
+
+/// `gg bundle import`: unpack a bundle into the cache, so gg finds everything
+/// in it without downloading
+pub async fn import(cache_base: &Path, host: &Target, bundle: &str) -> ExitCode {
+    let here = host.name();
+    let (info, cache_dir) = match unpack(Path::new(bundle), cache_base, &here).await {
+        Ok(unpacked) => unpacked,
+        Err(e) => {
+            println!("Could not import bundle: {}", e);
+            return ExitCode::from(1);
+        }
+    };
+    for dir in &info.dirs {
+        println!("    {}", describe(&cache_dir, dir));
+        cache::touch_last_used(&cache_dir.join(dir));
+    }
+    println!(
+        "Imported {} tools for {} into {}",
+        info.dirs.len(),
+        info.target,
+        cache_dir.display()
+    );
+    if info.target != here {
+        println!(
+            "Note: this machine is {}, run with --os/--arch to use them here",
+            here
+        );
+    }
+    ExitCode::from(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_target() {
+        let name = |s: &str| parse_target(s).map(|t| t.name());
+        assert_eq!(name("linux-x86_64"), Ok("linux-x86_64".to_string()));
+        assert_eq!(name("mac-aarch64"), Ok("mac-arm64".to_string()));
+        assert_eq!(name("Windows-x64"), Ok("windows-x86_64".to_string()));
+        assert_eq!(
+            name("linux-x86_64-musl"),
+            Ok("linux-x86_64-musl".to_string())
+        );
+        assert_eq!(name("linux-x86_64-gnu"), Ok("linux-x86_64".to_string()));
+        assert!(parse_target("linux").is_err());
+        assert!(parse_target("beos-x86_64").is_err());
+        assert!(parse_target("linux-x86_64-musl-x").is_err());
+        assert!(parse_target("x/../../..").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pack_and_unpack() {
+        let from = TempDir::new().unwrap();
+        let tool = from.path().join("node/node_hat_20");
+        fs::create_dir_all(tool.join("bin")).unwrap();
+        fs::write(tool.join("bin/node"), "node").unwrap();
+        fs::write(tool.join("gg-meta.json"), "{}").unwrap();
+
+        let info = BundleInfo {
+            target: "linux-x86_64".to_string(),
+            dirs: vec!["node/node_hat_20".to_string()],
+        };
+        for name in ["tools.tar.zst", "tools.tar.gz", "tools.tar"].iter() {
+            let out = from.path().join(name);
+            pack(from.path(), &info, &out).await.unwrap();
+
+            let to = TempDir::new().unwrap();
+            let stale = to.path().join("node/node_hat_20/stale");
+            fs::create_dir_all(&stale).unwrap();
+            assert_eq!(
+                unpack(&out, to.path(), "linux-x86_64").await.unwrap(),
+                (info.clone(), to.path().to_path_buf())
+            );
+            assert_eq!(
+                fs::read_to_string(to.path().join("node/node_hat_20/bin/node")).unwrap(),
+                "node"
+            );
+            assert!(!stale.exists());
+        }
+
+        let not_bundle = from.path().join("other.tar");
+        fs::write(&not_bundle, "nope").unwrap();
+        assert!(unpack(&not_bundle, from.path(), "linux-x86_64")
+            .await
+            .is_err());
+
+        // Another platform's tools go next to this one's, not over them
+        let to = TempDir::new().unwrap();
+        let out = from.path().join("tools.tar");
+        pack(from.path(), &info, &out).await.unwrap();
+        let (_, dir) = unpack(&out, to.path(), "linux-arm64").await.unwrap();
+        assert_eq!(dir, to.path().join("gg-target-linux-x86_64"));
+        assert!(dir.join("node/node_hat_20/bin/node").is_file());
+    }
+
+    #[tokio::test]
+    async fn test_unpack_refuses_dirs_outside_the_cache() {
+        assert!(check_dir("node/node_hat_20").is_ok());
+        for dir in [
+            "../../x",
+            "/etc",
+            "node/../..",
+            "node",
+            "node/a/b",
+            "./node/a",
+            "",
+        ]
+        .iter()
+        {
+            assert!(check_dir(dir).is_err(), "{}", dir);
+        }
+
+        let dir = TempDir::new().unwrap();
+        let victim = dir.path().join("victim");
+        fs::create_dir_all(victim.join("a/b")).unwrap();
+        let cache = dir.path().join("cache");
+        let evil = |json: &str, files: &[&str]| {
+            let out = dir.path().join("evil.tar");
+            let mut builder = tar::Builder::new(File::create(&out).unwrap());
+            let mut entries = vec![(BUNDLE_FILE, json.as_bytes())];
+            entries.extend(files.iter().map(|f| (*f, &b"x"[..])));
+            for (path, data) in entries {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(data.len() as u64);
+                header.set_mode(0o644);
+                header.set_cksum();
+                builder.append_data(&mut header, path, data).unwrap();
+            }
+            builder.finish().unwrap();
+            out
+        };
+
+        let out = evil(r#"{"target": "linux-x86_64", "dirs": ["../victim"]}"#, &[]);
+        assert!(unpack(&out, &cache, "linux-x86_64").await.is_err());
+        let out = evil(r#"{"target": "x/../../..", "dirs": ["a/b"]}"#, &[]);
+        assert!(unpack(&out, &cache, "linux-arm64").await.is_err());
+        assert!(victim.join("a/b").exists());
+
+        // Entries outside the declared dirs, over gg itself here
+        let out = evil(
+            r#"{"target": "linux-x86_64", "dirs": ["node/n"]}"#,
+            &["node/n/bin", "gg-1.0.0/gg"],
+        );
+        assert!(unpack(&out, &cache, "linux-x86_64").await.is_err());
+        assert!(!cache.join("gg-1.0.0/gg").exists());
+    }
+}
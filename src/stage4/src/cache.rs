@@ -0,0 +1,249 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::executor::GgMeta;
+
+/// The `gg-target-*` dir this run installs into, None for this machine
+static TARGET_DIR: RwLock<Option<String>> = RwLock::new(None);
+
+const TARGET_PREFIX: &str = "gg-target-";
+
+pub fn cache_dir() -> PathBuf {
+    PathBuf::from(env::var("GG_CACHE_DIR").unwrap_or_else(|_| ".cache/gg".to_string()))
+}
+
+/// Installs for another OS/arch (`--os`, `--arch`) get a cache of their own
+/// inside this one. Cache dir names don't say what they were built for, so
+/// sharing would pass host binaries off as the target's, or replace them.
+pub fn target_dir(cache_dir: &Path, host: &str, target: &str) -> PathBuf {
+    if host == target {
+        cache_dir.to_path_buf()
+    } else {
+        cache_dir.join(format!("{}{}", TARGET_PREFIX, target))
+    }
+}
+
+/// Makes `install_dir` the target's cache. Only for this process: GG_CACHE_DIR
+/// stays the whole cache, so gg run from here (aliases) works out its own.
+pub fn set_target(host: &str, target: &str) {
+    *TARGET_DIR.write().unwrap() = (host != target).then(|| format!("{}{}", TARGET_PREFIX, target));
+}
+
+/// Where this run installs and finds tools: the cache, or the target's
+/// cache inside it
+pub fn install_dir() -> PathBuf {
+    let cache_dir = cache_dir();
+    match TARGET_DIR.read().unwrap().as_ref() {
+        Some(target) => cache_dir.join(target),
+        None => cache_dir,
+    }
+}
+
+/// One installed tool version: `<cache>/<tool>/<dir>`.
+pub struct CacheEntry {
+    pub tool: String,
+    pub path: PathBuf,
+    /// None when gg-meta.json is missing or unreadable
+    pub meta: Option<GgMeta>,
+}
+
+/// Every tool dir in the cache, then in the `gg-target-*` caches for other
+/// platforms. gg's own `gg-<version>` dirs aren't tools.
+pub fn entries(cache_dir: &Path) -> Vec<CacheEntry> {
+    let mut entries = vec![];
+    let Ok(tools) = fs::read_dir(cache_dir) else {
+        return entries;
+    };
+    let (mut targets, mut tools): (Vec<_>, Vec<_>) = tools
+        .flatten()
+        .filter(|t| t.path().is_dir())
+        .map(|t| t.file_name().to_string_lossy().to_string())
+        .filter(|t| !t.starts_with("gg-") || t.starts_with(TARGET_PREFIX))
+        .partition(|t| t.starts_with(TARGET_PREFIX));
+    tools.sort();
+    targets.sort();
+    for tool in tools {
+        let Ok(dirs) = fs::read_dir(cache_dir.join(&tool)) else {
+            continue;
+        };
+        let mut dirs = dirs
+            .flatten()
+            .map(|d| d.path())
+            .filter(|d| d.is_dir())
+            .collect::<Vec<_>>();
+        dirs.sort();
+        for path in dirs {
+            let meta = fs::read_to_string(path.join("gg-meta.json"))
+                .ok()
+                .and_then(|json| serde_json::from_str(&json).ok());
+            entries.push(CacheEntry {
+                tool: tool.clone(),
+                path,
+                meta,
+            });
+        }
+    }
+    for target in targets {
+        entries.extend(self::entries(&cache_dir.join(target)));
+    }
+    entries
+}
+
+/// Bytes on disk under `path`, not following symlinks
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    fs::read_dir(path)
+        .map(|dir| dir.flatten().map(|e| dir_size(&e.path())).sum())
+        .unwrap_or(0)
+}
+
+/// Written into a tool dir each time gg runs it, so `gg ls` can tell
+const LAST_USED_FILE: &str = "gg-last-used";
+
+fn unix_secs(time: SystemTime) -> Option<u64> {
+    time.duration_since(UNIX_EPOCH).ok().map(|d| d.as_secs())
+}
+
+pub fn now() -> u64 {
+    unix_secs(SystemTime::now()).unwrap_or(0)
+}
+
+pub fn touch_last_used(dir: &Path) {
+    let _ = fs::write(dir.join(LAST_USED_FILE), now().to_string());
+}
+
+/// When gg last ran something from `dir`. None for installs from before gg
+/// kept track.
+pub fn last_used(dir: &Path) -> Option<u64> {
+    fs::read_to_string(dir.join(LAST_USED_FILE))
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+}
+
+/// gg-meta.json is written last thing after a download, so its age is the
+/// install date
+pub fn installed_at(dir: &Path) -> Option<u64> {
+    fs::metadata(dir.join("gg-meta.json"))
+        .or_else(|_| fs::metadata(dir))
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(unix_secs)
+}
+
+/// `2026-10-19 14:03`, in UTC
+pub fn format_date(secs: u64) -> String {
+    // Days to civil date, from Howard Hinnant's date algorithms
+    let days = (secs / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let doe = days - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    let time = secs % 86400;
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60
+    )
+}
+
+/// `5 min ago`, `3 days ago`
+pub fn ago(secs: u64, now: u64) -> String {
+    let elapsed = now.saturating_sub(secs);
+    match elapsed {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{} min ago", elapsed / 60),
+        3600..=86399 => format!("{} h ago", elapsed / 3600),
+        _ if elapsed < 2 * 86400 => "1 day ago".to_string(),
+        _ => format!("{} days ago", elapsed / 86400),
+    }
+}
+
+pub fn human_size(bytes: u64) -> String {
+    let units = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, units[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_entries_and_sizes() {
+        let dir = TempDir::new().unwrap();
+        let cache = dir.path();
+        fs::create_dir_all(cache.join("gg-1.0.0")).unwrap();
+        fs::create_dir_all(target_dir(cache, "linux-x86_64", "windows-x86_64").join("node/x"))
+            .unwrap();
+        fs::create_dir_all(cache.join("node/node_hat_20/bin")).unwrap();
+        fs::write(cache.join("node/node_hat_20/bin/node"), "12345").unwrap();
+        fs::write(cache.join("node/node_hat_20/gg-meta.json"), "not json").unwrap();
+
+        let entries = entries(cache);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].tool, "node");
+        assert!(entries[0].meta.is_none());
+        assert_eq!(dir_size(&entries[0].path), 5 + 8);
+        // Other platforms' installs come after this one's
+        assert_eq!(entries[1].tool, "node");
+        assert_eq!(
+            entries[1].path,
+            cache.join("gg-target-windows-x86_64/node/x")
+        );
+
+        assert_eq!(
+            target_dir(cache, "linux-x86_64", "linux-x86_64"),
+            cache.to_path_buf()
+        );
+        assert_eq!(
+            target_dir(cache, "linux-x86_64", "linux-arm64"),
+            cache.join("gg-target-linux-arm64")
+        );
+
+        assert_eq!(human_size(512), "512 B");
+        assert_eq!(human_size(1536), "1.5 KB");
+        assert_eq!(human_size(3 * 1024 * 1024 * 1024), "3.0 GB");
+    }
+
+    #[test]
+    fn test_dates() {
+        assert_eq!(format_date(0), "1970-01-01 00:00");
+        assert_eq!(format_date(1_709_210_096), "2024-02-29 12:34");
+        assert_eq!(ago(1000, 1030), "just now");
+        assert_eq!(ago(1000, 1000 + 3 * 3600), "3 h ago");
+        assert_eq!(ago(1000, 1000 + 86400 + 5), "1 day ago");
+        assert_eq!(ago(1000, 1000 + 10 * 86400), "10 days ago");
+        assert_eq!(ago(2000, 1000), "just now");
+
+        let dir = TempDir::new().unwrap();
+        assert_eq!(last_used(dir.path()), None);
+        touch_last_used(dir.path());
+        assert!(last_used(dir.path()).unwrap() + 5 >= now());
+        assert!(installed_at(dir.path()).is_some());
+    }
+}
@@ -792,6 +792,7 @@ mod tests {
         let config = crate::config::GgConfig {
             dependencies: HashMap::new(),
             aliases: HashMap::new(),
+            ..Default::default()
         };
         let run = |name: &'static str| {
             let input = &input;
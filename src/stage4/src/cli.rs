@@ -1,5 +1,7 @@
+use crate::alias::Alias;
 use crate::config::GgConfig;
 use crate::executor::find_version;
+use crate::prune::PruneArgs;
 use crate::tools::get_tool_info;
 use clap::{ArgAction, Parser, Subcommand};
 use regex::{Match, Regex};
@@ -23,6 +25,7 @@ pub struct ClapCmd {
     version,
     disable_help_flag = true,
     disable_version_flag = true,
+    disable_help_subcommand = true,
     allow_external_subcommands = true,
     trailing_var_arg = true
 )]
@@ -45,11 +48,16 @@ pub struct Cli {
     #[arg(short = 'V', long = "version", help = "Print version")]
     pub version: bool,
 
-    #[arg(long = "os", help = "Override target OS (windows, linux, mac)")]
+    #[arg(
+        long = "os",
+        global = true,
+        help = "Override target OS (windows, linux, mac)"
+    )]
     pub override_os: Option<String>,
 
     #[arg(
         long = "arch",
+        global = true,
         help = "Override target architecture (x86_64, arm64, armv7)"
     )]
     pub override_arch: Option<String>,
@@ -70,6 +78,37 @@ pub struct Cli {
     pub args: Vec<String>,
 }
 
+/// Built-in commands that came after gg.toml aliases did. A project alias
+/// with one of these names was there first, so it keeps winning.
+pub const ALIASABLE_COMMANDS: &[&str] = &[
+    "install", "verify", "ls", "shell", "activate", "doctor", "prune", "bundle", "serve",
+    "aliases", "shims",
+];
+
+/// Lets a gg.toml alias named like one of `ALIASABLE_COMMANDS` run instead of
+/// the built-in, by putting `--` in front of the name so clap doesn't take it
+/// for a subcommand. Returns whether it did.
+pub fn prefer_aliases(raw_args: &mut Vec<String>, config: &GgConfig) -> bool {
+    let mut i = 1;
+    while let Some(arg) = raw_args.get(i) {
+        match arg.as_str() {
+            "--" => return false,
+            "--os" | "--arch" => i += 2,
+            _ if arg.starts_with('-') => i += 1,
+            _ => break,
+        }
+    }
+    match raw_args.get(i) {
+        Some(name)
+            if ALIASABLE_COMMANDS.contains(&name.as_str()) && config.aliases.contains_key(name) =>
+        {
+            raw_args.insert(i, "--".to_string());
+            true
+        }
+        _ => false,
+    }
+}
+
 /// Applet dispatch (busybox-style) with a jump-out, applied to raw argv:
 /// - A literal `gg` first argument bypasses applet dispatch and is stripped,
 ///   so a renamed gg.cmd still works as plain gg: `node.cmd gg update` behaves
@@ -115,6 +154,8 @@ pub fn applet_from_cmd_path(path: &str) -> Option<String> {
 
 #[derive(Subcommand, Debug, Clone)]
 pub enum Commands {
+    #[command(about = "Print help")]
+    Help,
     #[command(about = "Check for updates for all tools (including gg)")]
     Update {
         #[arg(help = "Tool name to update (e.g., flutter, gg)")]
@@ -135,6 +176,49 @@ pub enum Commands {
         #[arg(help = "Tool name to get info about")]
         tool: Option<String>,
     },
+    #[command(about = "List installed tools with versions, sizes and when they were last used")]
+    Ls {
+        #[arg(help = "Only this tool")]
+        tool: Option<String>,
+    },
+    #[command(about = "Remove some of the cache, by age, count or total size")]
+    Prune {
+        #[arg(
+            long = "older-than",
+            help = "Remove versions not used for this long (e.g. 30d, 12h, 2w)"
+        )]
+        older_than: Option<String>,
+        #[arg(
+            long = "keep-latest",
+            help = "Keep only the N newest versions of each tool"
+        )]
+        keep_latest: Option<usize>,
+        #[arg(
+            long = "max-size",
+            help = "Remove least recently used versions until the cache fits (e.g. 10G)"
+        )]
+        max_size: Option<String>,
+        #[arg(long = "tool", help = "Only prune this tool")]
+        tool: Option<String>,
+        #[arg(long = "dry-run", help = "Only show what would be removed")]
+        dry_run: bool,
+        #[arg(short = 'y', long = "yes", help = "Don't ask before removing")]
+        yes: bool,
+    },
+    #[command(about = "Check installed tools against what was downloaded")]
+    Verify {
+        #[arg(help = "Tools to verify (default: gg.toml dependencies, or everything)")]
+        tools: Vec<String>,
+        #[arg(long = "all", help = "Verify everything in the cache")]
+        all: bool,
+        #[arg(long = "repair", help = "Reinstall broken tools without asking")]
+        repair: bool,
+    },
+    #[command(about = "Move tools to machines without internet access")]
+    Bundle {
+        #[command(subcommand)]
+        action: BundleAction,
+    },
     #[command(name = "clean-cache", about = "Clean cache (prompts for confirmation)")]
     CleanCache,
     #[command(about = "Manage gg configuration")]
@@ -142,14 +226,118 @@ pub enum Commands {
         #[command(subcommand)]
         action: ConfigAction,
     },
+    #[command(about = "Download tools without running them (default: everything in gg.toml)")]
+    Install {
+        #[arg(help = "Tools to install, e.g. node@20 java@21-azul")]
+        specs: Vec<String>,
+    },
+    #[command(about = "List aliases from gg.toml")]
+    Aliases,
+    #[command(about = "Create launcher scripts for gg.toml tools and aliases in .gg/bin")]
+    Shims {
+        #[arg(long = "global", help = "Put them in ~/.local/bin instead")]
+        global: bool,
+    },
+    #[command(about = "Show what gg sees: target, cache, GitHub access, network, version files")]
+    Doctor,
+    #[command(about = "Serve downloads as a pull-through mirror for other gg instances")]
+    Serve {
+        #[arg(long = "port", default_value_t = 8080, help = "Port to listen on")]
+        port: u16,
+        #[arg(
+            long = "bind",
+            default_value = "127.0.0.1",
+            help = "Address to listen on, 0.0.0.0 for other machines"
+        )]
+        bind: String,
+    },
+    #[command(about = "Start a shell with all gg.toml dependencies on PATH")]
+    Shell,
+    #[command(about = "Print a shell hook that loads gg.toml dependencies on cd")]
+    Activate {
+        #[arg(help = "Shell to hook into (bash, zsh, fish, pwsh)")]
+        shell: String,
+    },
+    #[command(name = "hook-env", hide = true)]
+    HookEnv { shell: String },
+}
+
+/// A built-in command, as opposed to a tool: just the name, main dispatches on it.
+fn builtin_cmd(cmd: &str) -> ClapCmd {
+    ClapCmd {
+        cmd: cmd.to_string(),
+        version: None,
+        distribution: None,
+        include_tags: HashSet::new(),
+        exclude_tags: HashSet::new(),
+        gems: None,
+    }
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum BundleAction {
+    #[command(about = "Install tools for a target and pack them into one archive")]
+    Create {
+        #[arg(help = "Tools to bundle (default: gg.toml dependencies)")]
+        specs: Vec<String>,
+        #[arg(
+            long = "for",
+            help = "Target to bundle for, e.g. linux-x86_64 (default: this machine or --os/--arch)"
+        )]
+        target: Option<String>,
+        #[arg(
+            short = 'o',
+            long = "output",
+            help = "Archive to write (.tar.zst, .tar.gz or .tar)"
+        )]
+        output: String,
+    },
+    #[command(about = "Unpack a bundle into the cache")]
+    Import {
+        #[arg(help = "Archive made by 'gg bundle create'")]
+        file: String,
+    },
 }
 
 #[derive(Subcommand, Debug, Clone)]
 pub enum ConfigAction {
     #[command(about = "Initialize a new gg.toml config file")]
     Init,
+    #[command(about = "Check gg.toml for mistakes")]
+    Check,
+    #[command(about = "Add dependencies to gg.toml, e.g. node@^20")]
+    Add {
+        #[arg(
+            required = true,
+            help = "Tools with version requirements, e.g. node@^20 java@17"
+        )]
+        specs: Vec<String>,
+    },
+    #[command(about = "Remove dependencies or aliases from gg.toml")]
+    Remove {
+        #[arg(required = true)]
+        names: Vec<String>,
+    },
+    #[command(about = "Add or change an alias in gg.toml")]
+    Alias {
+        name: String,
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        command: Vec<String>,
+    },
+    #[command(about = "Set any gg.toml value, e.g. env.GRADLE_OPTS -Xmx1g")]
+    Set {
+        key: String,
+        #[arg(allow_hyphen_values = true)]
+        value: String,
+    },
     #[command(about = "Show current configuration")]
-    Show,
+    Show {
+        #[arg(
+            long = "resolved",
+            help = "Show the merged result of all config files and where each value came from"
+        )]
+        resolved: bool,
+    },
 }
 
 impl Cli {
@@ -158,73 +346,74 @@ impl Cli {
             match command {
                 Commands::Update { tool, .. } => {
                     let app_args = tool.as_ref().map(|t| vec![t.clone()]).unwrap_or_default();
-                    (
-                        vec![ClapCmd {
-                            cmd: "update".to_string(),
-                            version: None,
-                            distribution: None,
-                            include_tags: HashSet::new(),
-                            exclude_tags: HashSet::new(),
-                            gems: None,
-                        }],
-                        app_args,
-                    )
+                    (vec![builtin_cmd("update")], app_args)
                 }
                 Commands::Tools { tool } => {
                     let app_args = tool.as_ref().map(|t| vec![t.clone()]).unwrap_or_default();
-                    (
-                        vec![ClapCmd {
-                            cmd: "tools".to_string(),
-                            version: None,
-                            distribution: None,
-                            include_tags: HashSet::new(),
-                            exclude_tags: HashSet::new(),
-                            gems: None,
-                        }],
-                        app_args,
-                    )
+                    (vec![builtin_cmd("tools")], app_args)
+                }
+                Commands::Help => (vec![builtin_cmd("help")], vec![]),
+                Commands::CleanCache => (vec![builtin_cmd("clean-cache")], vec![]),
+                Commands::Prune { .. } => (vec![builtin_cmd("prune")], vec![]),
+                Commands::Bundle { action } => match action {
+                    BundleAction::Create { specs, .. } => {
+                        (vec![builtin_cmd("bundle-create")], specs.clone())
+                    }
+                    BundleAction::Import { file } => {
+                        (vec![builtin_cmd("bundle-import")], vec![file.clone()])
+                    }
+                },
+                Commands::Verify { tools, .. } => (vec![builtin_cmd("verify")], tools.clone()),
+                Commands::Ls { tool } => {
+                    let app_args = tool.as_ref().map(|t| vec![t.clone()]).unwrap_or_default();
+                    (vec![builtin_cmd("ls")], app_args)
                 }
-                Commands::CleanCache => (
-                    vec![ClapCmd {
-                        cmd: "clean-cache".to_string(),
-                        version: None,
-                        distribution: None,
-                        include_tags: HashSet::new(),
-                        exclude_tags: HashSet::new(),
-                        gems: None,
-                    }],
-                    vec![],
-                ),
                 Commands::Config { action } => {
-                    let cmd = match action {
-                        ConfigAction::Init => "config-init",
-                        ConfigAction::Show => "config-show",
+                    let (cmd, args) = match action {
+                        ConfigAction::Init => ("config-init", vec![]),
+                        ConfigAction::Check => ("config-check", vec![]),
+                        ConfigAction::Add { specs } => ("config-add", specs.clone()),
+                        ConfigAction::Remove { names } => ("config-remove", names.clone()),
+                        ConfigAction::Alias { name, command } => (
+                            "config-alias",
+                            std::iter::once(name.clone())
+                                .chain(command.iter().cloned())
+                                .collect(),
+                        ),
+                        ConfigAction::Set { key, value } => {
+                            ("config-set", vec![key.clone(), value.clone()])
+                        }
+                        ConfigAction::Show { resolved } => (
+                            "config-show",
+                            if *resolved {
+                                vec!["--resolved".to_string()]
+                            } else {
+                                vec![]
+                            },
+                        ),
                     };
-                    (
-                        vec![ClapCmd {
-                            cmd: cmd.to_string(),
-                            version: None,
-                            distribution: None,
-                            include_tags: HashSet::new(),
-                            exclude_tags: HashSet::new(),
-                            gems: None,
-                        }],
-                        vec![],
-                    )
+                    (vec![builtin_cmd(cmd)], args)
                 }
+                Commands::Install { specs } => (vec![builtin_cmd("install")], specs.clone()),
+                Commands::Aliases => (vec![builtin_cmd("aliases")], vec![]),
+                Commands::Shims { .. } => (vec![builtin_cmd("shims")], vec![]),
+                Commands::Doctor => (vec![builtin_cmd("doctor")], vec![]),
+                Commands::Serve { .. } => (vec![builtin_cmd("serve")], vec![]),
+                Commands::Shell => (vec![builtin_cmd("shell")], vec![]),
+                Commands::Activate { shell } => {
+                    (vec![builtin_cmd("activate")], vec![shell.clone()])
+                }
+                Commands::HookEnv { shell } => (vec![builtin_cmd("hook-env")], vec![shell.clone()]),
             }
         } else if let Some(first_arg) = self.args.first() {
             if let Some(alias_commands) = config.resolve_alias_with_and(first_arg) {
-                if alias_commands.len() > 1 {
+                let alias = &config.aliases[first_arg];
+                if alias_commands.len() > 1
+                    || matches!(alias, Alias::Table(_))
+                    || alias.takes_args()
+                {
                     return (
-                        vec![ClapCmd {
-                            cmd: format!("__multi_alias__{}", first_arg),
-                            version: None,
-                            distribution: None,
-                            include_tags: HashSet::new(),
-                            exclude_tags: HashSet::new(),
-                            gems: None,
-                        }],
+                        vec![builtin_cmd(&format!("__multi_alias__{}", first_arg))],
                         self.args[1..].to_vec(),
                     );
                 }
@@ -245,9 +434,23 @@ impl Cli {
                             depth += 1;
                             continue;
                         }
+                        if config.aliases.contains_key(first) {
+                            // Steps or placeholders, that's for the runner
+                            return (
+                                vec![builtin_cmd(&format!("__multi_alias__{}", first))],
+                                expanded_args[1..].to_vec(),
+                            );
+                        }
                     }
                     break;
                 }
+                if depth == MAX_DEPTH {
+                    // Most likely a loop; the runner says which
+                    return (
+                        vec![builtin_cmd(&format!("__multi_alias__{}", first_arg))],
+                        self.args[1..].to_vec(),
+                    );
+                }
 
                 let cmds = if let Some(cmd_part) = expanded_args.first() {
                     parse_command_string(cmd_part, config)
@@ -263,7 +466,9 @@ impl Cli {
                 if cmds.len() == 1
                     && cmds[0].cmd == "run"
                     && app_args.first().is_some_and(|a| {
-                        a.starts_with("gh/") || get_tool_info(a).is_some()
+                        a.starts_with("gh/")
+                            || package_source(a).is_some()
+                            || get_tool_info(a).is_some()
                     })
                 {
                     let tool = &app_args[0];
@@ -304,6 +509,57 @@ impl Cli {
         }
     }
 
+    pub fn get_global_flag(&self) -> bool {
+        matches!(&self.command, Some(Commands::Shims { global: true }))
+    }
+
+    pub fn get_prune_args(&self) -> PruneArgs {
+        match &self.command {
+            Some(Commands::Prune {
+                older_than,
+                keep_latest,
+                max_size,
+                tool,
+                dry_run,
+                yes,
+            }) => PruneArgs {
+                older_than: older_than.clone(),
+                keep_latest: *keep_latest,
+                max_size: max_size.clone(),
+                tool: tool.clone(),
+                dry_run: *dry_run,
+                yes: *yes,
+            },
+            _ => PruneArgs::default(),
+        }
+    }
+
+    /// `gg verify` --all and --repair
+    pub fn get_verify_flags(&self) -> (bool, bool) {
+        match &self.command {
+            Some(Commands::Verify { all, repair, .. }) => (*all, *repair),
+            _ => (false, false),
+        }
+    }
+
+    /// `gg bundle create` --for and -o
+    pub fn get_bundle_create_args(&self) -> (Option<String>, String) {
+        match &self.command {
+            Some(Commands::Bundle {
+                action: BundleAction::Create { target, output, .. },
+            }) => (target.clone(), output.clone()),
+            _ => (None, String::new()),
+        }
+    }
+
+    /// `gg serve` --bind and --port
+    pub fn get_serve_args(&self) -> (String, u16) {
+        match &self.command {
+            Some(Commands::Serve { bind, port }) => (bind.clone(), *port),
+            _ => ("127.0.0.1".to_string(), 8080),
+        }
+    }
+
     pub fn get_force_flag(&self) -> bool {
         if let Some(Commands::Update { force, .. }) = &self.command {
             *force
@@ -336,13 +592,55 @@ pub(crate) fn starts_like_version(s: &str) -> bool {
     s.starts_with(|c: char| c.is_ascii_digit())
 }
 
-fn parse_command_string(cmd_string: &str, config: &GgConfig) -> Vec<ClapCmd> {
-    cmd_string
-        .split(":")
-        .filter(|s| !s.is_empty())
+/// Prefixes naming a package to run rather than a gg tool: `npm:prettier`,
+/// `pip:black`
+pub(crate) const PACKAGE_SOURCES: &[&str] = &["npm", "pip"];
+
+/// The package of a `npm:` style command
+pub(crate) fn package_source(cmd: &str) -> Option<&str> {
+    PACKAGE_SOURCES
+        .iter()
+        .find_map(|source| cmd.strip_prefix(source)?.strip_prefix(':'))
+}
+
+/// `node:java` is two tools, but `npm:prettier:java` is the prettier package
+/// and java. A bare `npm` is still node's npm.
+fn split_cmds(cmd_string: &str) -> Vec<String> {
+    let mut cmds = vec![];
+    let mut source = None;
+    for part in cmd_string.split(':').filter(|s| !s.is_empty()) {
+        match source.take() {
+            Some(source) => cmds.push(format!("{source}:{part}")),
+            None if PACKAGE_SOURCES.contains(&part) => source = Some(part),
+            None => cmds.push(part.to_string()),
+        }
+    }
+    cmds.extend(source.map(String::from));
+    cmds
+}
+
+/// Split off the `@version`. The @ of an npm scope (`npm:@scope/pkg@1`) is
+/// part of the name.
+pub(crate) fn split_name_version(cmd: &str) -> Vec<String> {
+    if let Some(package) = package_source(cmd) {
+        let start = cmd.len() - package.len() + usize::from(package.starts_with('@'));
+        return match cmd[start..].find('@') {
+            Some(at) => vec![
+                cmd[..start + at].to_string(),
+                cmd[start + at + 1..].to_string(),
+            ],
+            None => vec![cmd.to_string()],
+        };
+    }
+    cmd.split("@").map(String::from).collect()
+}
+
+pub(crate) fn parse_command_string(cmd_string: &str, config: &GgConfig) -> Vec<ClapCmd> {
+    split_cmds(cmd_string)
+        .into_iter()
         .map(|cmd| {
-            let mut cmd = cmd.to_string();
-            let parts: Vec<String> = cmd.split("@").map(String::from).collect();
+            let mut cmd = cmd;
+            let parts = split_name_version(&cmd);
             let mut include_tags = HashSet::new();
             let mut exclude_tags = HashSet::new();
             let mut version = None;
@@ -437,6 +735,8 @@ fn parse_command_string(cmd_string: &str, config: &GgConfig) -> Vec<ClapCmd> {
                     // Piggybacking!
                     let underlying_tool = match base_cmd.as_str() {
                         "npm" | "npx" => "node",
+                        "pnpx" => "pnpm",
+                        "yarnpkg" => "yarn",
                         "dart" => "flutter",
                         _ => &base_cmd,
                     };
@@ -446,6 +746,15 @@ fn parse_command_string(cmd_string: &str, config: &GgConfig) -> Vec<ClapCmd> {
                 }
             }
 
+            // A `[tools.<name>]` with a source is a tool of its own
+            let source = config.tools.get(&base_cmd).and_then(|t| t.source.as_ref());
+            if let (Some(source), None) = (source, get_tool_info(&base_cmd)) {
+                if let Some(source_cmd) = parse_command_string(source, &GgConfig::default()).pop() {
+                    cmd = source_cmd.cmd;
+                    version = version.or(source_cmd.version);
+                }
+            }
+
             ClapCmd {
                 cmd,
                 version,
@@ -464,7 +773,7 @@ mod tests {
 
     fn parse_test_args(args: Vec<&str>) -> Cli {
         let args = std::iter::once("gg")
-            .chain(args.into_iter())
+            .chain(args)
             .map(String::from)
             .collect::<Vec<_>>();
         Cli::try_parse_from(args).unwrap()
@@ -659,6 +968,30 @@ mod tests {
         assert_eq!(app_args, vec!["--version"]);
     }
 
+    #[test]
+    fn test_install_command() {
+        let cli = parse_test_args(vec![
+            "install",
+            "node@20",
+            "java@21-azul",
+            "--os",
+            "windows",
+        ]);
+        let config = GgConfig::default();
+        let (cmds, app_args) = cli.parse_args(&config);
+        assert_eq!(cmds[0].cmd, "install");
+        assert_eq!(app_args, vec!["node@20", "java@21-azul"]);
+        assert_eq!(cli.override_os.as_deref(), Some("windows"));
+    }
+
+    #[test]
+    fn test_os_flag_after_tool_goes_to_the_tool() {
+        let cli = parse_test_args(vec!["deno", "--os", "windows"]);
+        assert_eq!(cli.override_os, None);
+        let (_, app_args) = cli.parse_args(&GgConfig::default());
+        assert_eq!(app_args, vec!["--os", "windows"]);
+    }
+
     #[test]
     fn test_distribution_parsing() {
         let cli = parse_test_args(vec!["java@17-temurin", "hello"]);
@@ -780,20 +1113,100 @@ mod tests {
         let mut config = GgConfig::default();
         config
             .aliases
-            .insert("build".to_string(), "gradle clean build".to_string());
+            .insert("build".to_string(), "gradle clean build".into());
 
         let (cmds, app_args) = cli.parse_args(&config);
         assert_eq!(cmds[0].cmd, "gradle");
         assert_eq!(app_args, vec!["clean", "build", "extra", "args"]);
     }
 
+    #[test]
+    fn test_chained_and_table_aliases_run_as_steps() {
+        let mut config: GgConfig = toml::from_str(
+            r#"
+[aliases.ci]
+steps = ["gradle build"]
+env = { CI = "true" }
+"#,
+        )
+        .unwrap();
+        config
+            .aliases
+            .insert("check".to_string(), "gradle check || npm test".into());
+        config
+            .aliases
+            .insert("inline".to_string(), "node -e 'a && b'".into());
+
+        let (cmds, app_args) = parse_test_args(vec!["ci", "x"]).parse_args(&config);
+        assert_eq!(cmds[0].cmd, "__multi_alias__ci");
+        assert_eq!(app_args, vec!["x"]);
+        let (cmds, _) = parse_test_args(vec!["check"]).parse_args(&config);
+        assert_eq!(cmds[0].cmd, "__multi_alias__check");
+
+        // && inside quotes is an argument, not a second step
+        let (cmds, app_args) = parse_test_args(vec!["inline"]).parse_args(&config);
+        assert_eq!(cmds[0].cmd, "node");
+        assert_eq!(app_args, vec!["-e", "a && b"]);
+    }
+
+    #[test]
+    fn test_placeholder_aliases_run_as_steps() {
+        let mut config = GgConfig::default();
+        config
+            .aliases
+            .insert("deploy".to_string(), "gradle publish -Penv=$1".into());
+        config
+            .aliases
+            .insert("prod".to_string(), "deploy prod".into());
+
+        let (cmds, app_args) = parse_test_args(vec!["deploy", "dev"]).parse_args(&config);
+        assert_eq!(cmds[0].cmd, "__multi_alias__deploy");
+        assert_eq!(app_args, vec!["dev"]);
+
+        // Reached through another alias, too
+        let (cmds, app_args) = parse_test_args(vec!["prod", "--info"]).parse_args(&config);
+        assert_eq!(cmds[0].cmd, "__multi_alias__deploy");
+        assert_eq!(app_args, vec!["prod", "--info"]);
+    }
+
+    #[test]
+    fn test_alias_wins_over_newer_builtin() {
+        let mut config = GgConfig::default();
+        config
+            .aliases
+            .insert("install".to_string(), "npm ci".into());
+        let mut raw_args = vec!["gg", "-v", "--os", "linux", "install", "--force"]
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        assert!(prefer_aliases(&mut raw_args, &config));
+        let (cmds, app_args) = Cli::try_parse_from(&raw_args).unwrap().parse_args(&config);
+        assert_eq!(cmds[0].cmd, "npm");
+        assert_eq!(app_args, vec!["ci", "--force"]);
+
+        // Without the alias it's the built-in, and the old built-ins always are
+        let mut raw_args = vec!["gg".to_string(), "ls".to_string()];
+        assert!(!prefer_aliases(&mut raw_args, &config));
+        config
+            .aliases
+            .insert("update".to_string(), "npm update".into());
+        let mut raw_args = vec!["gg".to_string(), "update".to_string()];
+        assert!(!prefer_aliases(&mut raw_args, &config));
+    }
+
     #[test]
     fn test_alias_with_version() {
-        let cli = parse_test_args(vec!["serve", "--port", "8080"]);
         let mut config = GgConfig::default();
         config
             .aliases
-            .insert("serve".to_string(), "node@18 server.js".to_string());
+            .insert("serve".to_string(), "node@18 server.js".into());
+        // Named like `gg serve`, which the alias wins over
+        let mut raw_args = vec!["gg", "serve", "--port", "8080"]
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        assert!(prefer_aliases(&mut raw_args, &config));
+        let cli = Cli::try_parse_from(&raw_args).unwrap();
 
         let (cmds, app_args) = cli.parse_args(&config);
         assert_eq!(cmds[0].cmd, "node");
@@ -882,6 +1295,66 @@ mod tests {
         assert_eq!(app_args, vec!["--help"]);
     }
 
+    #[test]
+    fn test_npm_package_commands() {
+        let config = GgConfig::default();
+        let (cmds, app_args) =
+            parse_test_args(vec!["npm:@scope/pkg@1.2.3:java@17", "x"]).parse_args(&config);
+        assert_eq!(cmds.len(), 2);
+        assert_eq!(cmds[0].cmd, "npm:@scope/pkg");
+        assert_eq!(cmds[0].version.as_deref(), Some("1.2.3"));
+        assert_eq!(cmds[1].cmd, "java");
+        assert_eq!(app_args, vec!["x"]);
+
+        let cmds = parse_command_string("npm:@scope/pkg", &config);
+        assert_eq!(cmds[0].cmd, "npm:@scope/pkg");
+        assert!(cmds[0].version.is_none());
+
+        // Bare npm is still node's
+        let cmds = parse_command_string("npm", &config);
+        assert_eq!(cmds[0].cmd, "npm");
+
+        let (cmds, _) = parse_test_args(vec!["run", "npm:prettier", "."]).parse_args(&config);
+        assert_eq!(cmds[0].cmd, "npm:prettier");
+
+        let cmds = parse_command_string("pip:black@24:node", &config);
+        assert_eq!(cmds[0].cmd, "pip:black");
+        assert_eq!(cmds[0].version.as_deref(), Some("24"));
+        assert_eq!(cmds[1].cmd, "node");
+    }
+
+    #[test]
+    fn test_tool_source() {
+        let mut config: GgConfig = toml::from_str(
+            r#"
+[tools.fmt]
+source = "npm:prettier@3"
+
+[tools.node]
+source = "npm:not-node"
+"#,
+        )
+        .unwrap();
+        let cmds = parse_command_string("fmt", &config);
+        assert_eq!(cmds[0].cmd, "npm:prettier");
+        assert_eq!(cmds[0].version.as_deref(), Some("3"));
+
+        config
+            .dependencies
+            .insert("fmt".to_string(), "3.1".to_string());
+        assert_eq!(
+            parse_command_string("fmt", &config)[0].version.as_deref(),
+            Some("3.1")
+        );
+        assert_eq!(
+            parse_command_string("fmt@2", &config)[0].version.as_deref(),
+            Some("2")
+        );
+
+        // A real tool can't be redefined
+        assert_eq!(parse_command_string("node", &config)[0].cmd, "node");
+    }
+
     #[test]
     fn test_run_with_regular_command_unchanged() {
         let cli = parse_test_args(vec!["run", "somecommand"]);
@@ -899,12 +1372,11 @@ mod tests {
         let mut config = GgConfig::default();
         config.aliases.insert(
             "run-hello".to_string(),
-            "flutter run -t lib/main.dart --flavor dev".to_string(),
-        );
-        config.aliases.insert(
-            "run-hello-chrome".to_string(),
-            "run-hello -d chrome".to_string(),
+            "flutter run -t lib/main.dart --flavor dev".into(),
         );
+        config
+            .aliases
+            .insert("run-hello-chrome".to_string(), "run-hello -d chrome".into());
 
         let (cmds, app_args) = cli.parse_args(&config);
         assert_eq!(cmds[0].cmd, "flutter");
@@ -1,75 +1,308 @@
+use crate::alias::{tokenize, Alias};
+use crate::config_edit::{add_dependency, detect_project};
+use crate::tools::canonical_name;
 use log::{debug, info, warn};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[derive(Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct GgConfig {
     #[serde(default)]
     pub dependencies: HashMap<String, String>,
     #[serde(default)]
-    pub aliases: HashMap<String, String>,
+    pub aliases: HashMap<String, Alias>,
+    /// Extra env vars for whatever gg runs, `${java.home}` style placeholders
+    /// allowed (see `interpolate`)
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Extra PATH entries, after the tools' own bin dirs
+    #[serde(default)]
+    pub path: Vec<String>,
+    /// `[tools.<name>]`: env and PATH that only apply when that tool is in use
+    #[serde(default)]
+    pub tools: HashMap<String, ToolConfig>,
+    /// A `gg serve` to download through, unless GG_MIRROR says otherwise
+    #[serde(default)]
+    pub mirror: Option<String>,
+    /// `[java]`: which vendor `java` comes from unless a command says
+    #[serde(default)]
+    pub java: JavaConfig,
+    /// `[maven]`: JDKs for Maven toolchains
+    #[serde(default)]
+    pub maven: MavenConfig,
+    /// `[github]`: only read from the user config, see `github_token`
+    #[serde(default)]
+    pub github: GithubConfig,
+    /// Dir holding the nearest gg.toml, for `${project.root}`
+    #[serde(skip)]
+    pub root: Option<PathBuf>,
+    /// The files this was merged from, least specific first
+    #[serde(skip)]
+    pub files: Vec<PathBuf>,
+    /// Each resolved (dotted) key and where it came from, for
+    /// `config show --resolved`
+    #[serde(skip)]
+    pub sources: BTreeMap<String, ConfigSource>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigSource {
+    pub file: PathBuf,
+    pub value: toml::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ToolConfig {
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default)]
+    pub path: Vec<String>,
+    /// Makes `<name>` a tool of its own, e.g. `npm:prettier@3`
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct JavaConfig {
+    #[serde(default)]
+    pub distribution: Option<String>,
+    /// JDKs to prepare for Gradle toolchains, instead of reading the build
+    #[serde(default)]
+    pub toolchains: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MavenConfig {
+    /// JDKs for toolchains.xml, instead of reading pom.xml
+    #[serde(default)]
+    pub toolchains: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GithubConfig {
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+/// Lay `layer` over `base`: tables merge key by key, anything else (arrays
+/// included) is replaced. `sources` tracks the file behind each leaf.
+fn merge_table(
+    base: &mut toml::Table,
+    layer: toml::Table,
+    file: &Path,
+    prefix: &str,
+    sources: &mut BTreeMap<String, ConfigSource>,
+) {
+    for (key, value) in layer {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(layer)) => {
+                merge_table(base, layer, file, &path, sources)
+            }
+            (_, value) => {
+                let nested = format!("{}.", path);
+                sources.retain(|k, _| k != &path && !k.starts_with(&nested));
+                record_sources(&value, &path, file, sources);
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn record_sources(
+    value: &toml::Value,
+    path: &str,
+    file: &Path,
+    sources: &mut BTreeMap<String, ConfigSource>,
+) {
+    match value {
+        toml::Value::Table(table) if !table.is_empty() => {
+            for (key, value) in table {
+                record_sources(value, &format!("{}.{}", path, key), file, sources);
+            }
+        }
+        _ => {
+            sources.insert(
+                path.to_string(),
+                ConfigSource {
+                    file: file.to_path_buf(),
+                    value: value.clone(),
+                },
+            );
+        }
+    }
+}
+
+/// The config main loaded, see `GgConfig::loaded`
+static LOADED: OnceLock<GgConfig> = OnceLock::new();
 
 impl GgConfig {
+    /// Makes this the config `GgConfig::loaded` hands out. Main does it once,
+    /// right after loading.
+    pub fn set_loaded(&self) {
+        let _ = LOADED.set(self.clone());
+    }
+
+    /// The project config, for settings needed far from main (`mirror`,
+    /// `[java]`, `[maven]`). In-process on purpose: a gg started by a tool
+    /// reads its own project's gg.toml instead of inheriting this one.
+    pub fn loaded() -> &'static GgConfig {
+        LOADED.get_or_init(GgConfig::default)
+    }
+
+    /// Every config file that applies here, merged with later ones winning:
+    /// the user's `~/.config/gg/config.toml`, then each gg.toml from the
+    /// outermost directory in to the nearest one.
     pub fn load() -> Self {
-        match Self::find_config_file() {
-            Some(path) => {
-                info!("Loading config from: {}", path.display());
-                match Self::load_from_file(&path) {
-                    Ok(config) => config,
-                    Err(e) => {
-                        warn!("Failed to load config from {}: {}", path.display(), e);
-                        Self::default()
-                    }
+        let files = match env::current_dir() {
+            Ok(dir) => Self::config_files(&dir, Self::user_config_file()),
+            Err(_) => vec![],
+        };
+        if files.is_empty() {
+            debug!("No config file found, using defaults");
+            return Self::default();
+        }
+        match Self::load_layered(&files) {
+            Ok(config) => config,
+            Err(e) => {
+                // Reported properly by config_check, which knows the line
+                debug!("{}", e);
+                Self {
+                    files,
+                    ..Self::default()
                 }
             }
-            None => {
-                debug!("No config file found, using defaults");
-                Self::default()
-            }
         }
     }
 
-    fn find_config_file() -> Option<PathBuf> {
-        let mut current_dir = env::current_dir().ok()?;
+    /// `$XDG_CONFIG_HOME/gg/config.toml`, or `~/.config/gg/config.toml`
+    pub fn user_config_file() -> Option<PathBuf> {
+        let config_home = env::var("XDG_CONFIG_HOME")
+            .ok()
+            .filter(|d| !d.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| {
+                env::var("HOME")
+                    .or_else(|_| env::var("USERPROFILE"))
+                    .ok()
+                    .map(|home| Path::new(&home).join(".config"))
+            })?;
+        Some(config_home.join("gg").join("config.toml"))
+    }
 
-        loop {
-            let config_path = current_dir.join("gg.toml");
-            if config_path.exists() {
-                return Some(config_path);
+    /// The files to layer, least specific first. Only those that exist. The
+    /// walk up stops at the repository root (the first dir with a `.git`), so
+    /// a gg.toml somewhere above a checkout doesn't leak into it.
+    fn config_files(start: &Path, user_config: Option<PathBuf>) -> Vec<PathBuf> {
+        let mut files = vec![];
+        for dir in start.ancestors() {
+            let file = dir.join("gg.toml");
+            if file.is_file() {
+                files.push(file);
             }
-
-            if !current_dir.pop() {
+            if dir.join(".git").exists() {
                 break;
             }
         }
-
-        None
+        files.extend(user_config.filter(|p| p.is_file()));
+        files.reverse();
+        files
     }
 
-    fn load_from_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
-        let content = fs::read_to_string(path)?;
-        let config: GgConfig = toml::from_str(&content)?;
+    pub(crate) fn load_layered(files: &[PathBuf]) -> Result<Self, String> {
+        let mut merged = toml::Table::new();
+        let mut sources = BTreeMap::new();
+        for file in files {
+            info!("Loading config from: {}", file.display());
+            let layer = fs::read_to_string(file)
+                .map_err(|e| e.to_string())
+                .and_then(|content| {
+                    toml::from_str::<toml::Table>(&content).map_err(|e| e.to_string())
+                })
+                .map_err(|e| format!("Failed to load config from {}: {}", file.display(), e))?;
+            merge_table(&mut merged, layer, file, "", &mut sources);
+        }
+
+        let mut config: GgConfig = toml::Value::Table(merged)
+            .try_into()
+            .map_err(|e| format!("Failed to load config: {}", e))?;
+        config.root = files
+            .iter()
+            .rev()
+            .find(|f| f.file_name().is_some_and(|n| n == "gg.toml"))
+            .and_then(|f| f.parent().map(|p| p.to_path_buf()));
+        config.files = files.to_vec();
+        config.sources = sources;
         Ok(config)
     }
 
+    /// `[github] token` and the file it's in, when that's the user config. A
+    /// gg.toml is usually committed, so a token there is ignored.
+    pub fn github_token(&self) -> Option<(&str, &Path)> {
+        let source = self.sources.get("github.token")?;
+        if source.file.file_name().is_some_and(|n| n == "gg.toml") {
+            return None;
+        }
+        let token = self.github.token.as_deref()?.trim();
+        (!token.is_empty()).then_some((token, source.file.as_path()))
+    }
+
+    /// The nearest gg.toml, the project one. Not looked for above the
+    /// repository root, same as `load`.
+    pub fn find_config_file() -> Option<PathBuf> {
+        Self::config_files(&env::current_dir().ok()?, None).pop()
+    }
+
+    /// `${project.root}`, `${<tool>.home}` (install dir of a tool in use, by
+    /// any name it answers to) and `${env.NAME}`. Anything else is left as is.
+    pub fn interpolate(&self, value: &str, homes: &HashMap<String, PathBuf>) -> String {
+        let placeholder = Regex::new(r"\$\{([^}]+)\}").unwrap();
+        placeholder
+            .replace_all(value, |caps: &regex::Captures| {
+                let key = &caps[1];
+                let resolved = match key.split_once('.') {
+                    Some(("project", "root")) => self
+                        .root
+                        .clone()
+                        .or_else(|| env::current_dir().ok())
+                        .map(|p| p.to_string_lossy().to_string()),
+                    Some(("env", name)) => env::var(name).ok(),
+                    Some((tool, "home")) => homes
+                        .get(&canonical_name(tool))
+                        .map(|p| p.to_string_lossy().to_string()),
+                    _ => None,
+                };
+                resolved.unwrap_or_else(|| {
+                    warn!("Unknown placeholder ${{{}}} in gg.toml", key);
+                    caps[0].to_string()
+                })
+            })
+            .to_string()
+    }
+
     pub fn resolve_alias(&self, command: &str) -> Option<Vec<String>> {
-        self.aliases
-            .get(command)
-            .map(|alias_cmd| shlex::split(alias_cmd).unwrap_or_else(|| vec![alias_cmd.clone()]))
+        self.aliases.get(command).and_then(Alias::as_single_command)
     }
 
+    /// Every command an alias runs, across its steps and `&&`/`||`/`;`.
     pub fn resolve_alias_with_and(&self, command: &str) -> Option<Vec<Vec<String>>> {
-        self.aliases.get(command).map(|alias_cmd| {
-            alias_cmd
-                .split("&&")
-                .map(|cmd| cmd.trim())
-                .map(|cmd| shlex::split(cmd).unwrap_or_else(|| vec![cmd.to_string()]))
+        self.aliases.get(command).map(|alias| {
+            alias
+                .steps()
+                .iter()
+                .flat_map(|step| match tokenize(step) {
+                    Ok(commands) => commands.into_iter().map(|c| c.args).collect(),
+                    Err(_) => vec![vec![step.clone()]],
+                })
                 .collect()
         })
     }
@@ -97,47 +330,90 @@ impl GgConfig {
 # build = "gradle clean build"
 # serve = "node@18 server.js"
 # test = "npm test"
+
+[env]
+# Environment variables for every tool gg runs
+# Examples:
+# GRADLE_USER_HOME = "${project.root}/.gradle-home"
+
+# [tools.maven.env]
+# Environment variables only when a tool is in use
+# MAVEN_OPTS = "-Xmx2g"
 "#;
 
-        fs::write(config_path, default_config)
+        let detected = detect_project(Path::new("."));
+        let mut doc = default_config
+            .parse::<toml_edit::DocumentMut>()
+            .map_err(|e| e.to_string())?;
+        for found in &detected {
+            add_dependency(&mut doc, found.tool, &found.version)?;
+        }
+
+        fs::write(config_path, doc.to_string())
             .map_err(|e| format!("Failed to create gg.toml: {}", e))?;
 
         println!("Created gg.toml configuration file");
+        for found in &detected {
+            println!(
+                "  {} = \"{}\" (from {})",
+                found.tool, found.version, found.from
+            );
+        }
         Ok(())
     }
 
-    pub fn show_config(&self) -> Result<(), String> {
-        match Self::find_config_file() {
-            Some(path) => {
-                println!("Configuration loaded from: {}", path.display());
-                match fs::read_to_string(&path) {
-                    Ok(content) => {
-                        println!("\nCurrent configuration:");
-                        println!("{}", content);
-                    }
-                    Err(e) => return Err(format!("Failed to read config file: {}", e)),
-                }
+    pub fn show_config(&self, resolved: bool) -> Result<(), String> {
+        if self.files.is_empty() {
+            println!("No gg.toml configuration file found");
+            println!("Run 'gg config init' to create one");
+            return Ok(());
+        }
+
+        if resolved {
+            println!("Configuration loaded from (later files win):");
+            for file in &self.files {
+                println!("  {}", file.display());
+            }
+            println!("\nResolved configuration:");
+            for (key, source) in &self.sources {
+                println!("  {} = {}  # {}", key, source.value, source.file.display());
+            }
+            return Ok(());
+        }
 
-                if !self.dependencies.is_empty() || !self.aliases.is_empty() {
-                    println!("\nParsed configuration:");
-                    if !self.dependencies.is_empty() {
-                        println!("\nDependencies:");
-                        for (tool, version) in &self.dependencies {
-                            println!("  {} = \"{}\"", tool, version);
-                        }
-                    }
-
-                    if !self.aliases.is_empty() {
-                        println!("\nAliases:");
-                        for (alias, command) in &self.aliases {
-                            println!("  {} = \"{}\"", alias, command);
-                        }
-                    }
+        for path in &self.files {
+            println!("Configuration loaded from: {}", path.display());
+            match fs::read_to_string(path) {
+                Ok(content) => {
+                    println!("\nCurrent configuration:");
+                    println!("{}", content);
                 }
+                Err(e) => return Err(format!("Failed to read config file: {}", e)),
+            }
+        }
+
+        if !self.dependencies.is_empty() || !self.aliases.is_empty() || !self.env.is_empty() {
+            println!("\nParsed configuration:");
+        }
+
+        if !self.dependencies.is_empty() {
+            println!("\nDependencies:");
+            for (tool, version) in &self.dependencies {
+                println!("  {} = \"{}\"", tool, version);
             }
-            None => {
-                println!("No gg.toml configuration file found");
-                println!("Run 'gg config init' to create one");
+        }
+
+        if !self.aliases.is_empty() {
+            println!("\nAliases:");
+            for (alias, command) in &self.aliases {
+                println!("  {} = \"{}\"", alias, command);
+            }
+        }
+
+        if !self.env.is_empty() {
+            println!("\nEnvironment:");
+            for (key, value) in &self.env {
+                println!("  {} = \"{}\"", key, value);
             }
         }
         Ok(())
@@ -167,7 +443,7 @@ test = "npm test"
 
         fs::write(&config_path, config_content).unwrap();
 
-        let config = GgConfig::load_from_file(&config_path).unwrap();
+        let config = GgConfig::load_layered(&[config_path]).unwrap();
 
         assert_eq!(
             config.dependencies.get("node"),
@@ -176,9 +452,151 @@ test = "npm test"
         assert_eq!(config.dependencies.get("java"), Some(&"17".to_string()));
         assert_eq!(
             config.aliases.get("build"),
-            Some(&"gradle clean build".to_string())
+            Some(&"gradle clean build".into())
+        );
+        assert_eq!(config.aliases.get("test"), Some(&"npm test".into()));
+    }
+
+    #[test]
+    fn test_env_and_tool_tables() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("gg.toml");
+        fs::write(
+            &config_path,
+            r#"
+path = ["${project.root}/scripts"]
+
+[env]
+GRADLE_USER_HOME = "${project.root}/.gradle-home"
+
+[tools.maven.env]
+MAVEN_OPTS = "-Xmx2g"
+
+[tools.node]
+env = { NODE_OPTIONS = "--max-old-space-size=4096" }
+path = ["node_modules/.bin"]
+"#,
+        )
+        .unwrap();
+
+        let config = GgConfig::load_layered(&[config_path]).unwrap();
+        assert_eq!(config.root.as_deref(), Some(temp_dir.path()));
+        assert_eq!(config.path, vec!["${project.root}/scripts"]);
+        assert_eq!(config.tools["maven"].env["MAVEN_OPTS"], "-Xmx2g");
+        assert_eq!(config.tools["node"].path, vec!["node_modules/.bin"]);
+        assert_eq!(
+            config.interpolate(&config.env["GRADLE_USER_HOME"], &HashMap::new()),
+            format!("{}/.gradle-home", temp_dir.path().display())
+        );
+    }
+
+    #[test]
+    fn test_layered_configs() {
+        let temp_dir = TempDir::new().unwrap();
+        let user = temp_dir.path().join("config.toml");
+        let repo = temp_dir.path().join("repo");
+        let sub = repo.join("sub");
+        fs::create_dir_all(&sub).unwrap();
+        fs::write(
+            &user,
+            r#"
+[dependencies]
+java = "17"
+[env]
+GRADLE_OPTS = "-Xmx1g"
+"#,
+        )
+        .unwrap();
+        fs::write(
+            repo.join("gg.toml"),
+            r#"
+path = ["bin"]
+[dependencies]
+node = "18"
+[aliases]
+build = "gradle build"
+"#,
+        )
+        .unwrap();
+        fs::write(
+            sub.join("gg.toml"),
+            r#"
+[dependencies]
+java = "21"
+[aliases.build]
+steps = ["npm run build"]
+"#,
+        )
+        .unwrap();
+
+        let files = GgConfig::config_files(&sub, Some(user.clone()));
+        assert_eq!(
+            files,
+            vec![user.clone(), repo.join("gg.toml"), sub.join("gg.toml")]
+        );
+        let config = GgConfig::load_layered(&files).unwrap();
+        assert_eq!(config.root.as_deref(), Some(sub.as_path()));
+        assert_eq!(config.dependencies["java"], "21");
+        assert_eq!(config.dependencies["node"], "18");
+        assert_eq!(config.env["GRADLE_OPTS"], "-Xmx1g");
+        assert_eq!(config.path, vec!["bin"]);
+        assert_eq!(config.aliases["build"].steps(), vec!["npm run build"]);
+
+        let from = |key: &str| config.sources[key].file.clone();
+        assert_eq!(from("dependencies.java"), sub.join("gg.toml"));
+        assert_eq!(from("dependencies.node"), repo.join("gg.toml"));
+        assert_eq!(from("env.GRADLE_OPTS"), user);
+        assert_eq!(from("aliases.build.steps"), sub.join("gg.toml"));
+        assert!(!config.sources.contains_key("aliases.build"));
+
+        // No user config, nothing above
+        assert_eq!(
+            GgConfig::config_files(&sub, Some(temp_dir.path().join("nope.toml"))).len(),
+            2
+        );
+
+        // Nothing above the repository root either
+        fs::write(temp_dir.path().join("gg.toml"), "[dependencies]\ngo = \"1\"\n").unwrap();
+        assert_eq!(GgConfig::config_files(&sub, None).len(), 3);
+        fs::create_dir(repo.join(".git")).unwrap();
+        assert_eq!(
+            GgConfig::config_files(&sub, None),
+            vec![repo.join("gg.toml"), sub.join("gg.toml")]
+        );
+    }
+
+    #[test]
+    fn test_github_token_only_from_user_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let user = temp_dir.path().join("config.toml");
+        let project = temp_dir.path().join("gg.toml");
+        fs::write(&user, "[github]\ntoken = \" ghp_user \"\n").unwrap();
+        fs::write(&project, "[dependencies]\nnode = \"20\"\n").unwrap();
+
+        let config = GgConfig::load_layered(&[user.clone(), project.clone()]).unwrap();
+        assert_eq!(config.github_token(), Some(("ghp_user", user.as_path())));
+
+        fs::write(&project, "[github]\ntoken = \"ghp_committed\"\n").unwrap();
+        let config = GgConfig::load_layered(&[user, project]).unwrap();
+        assert_eq!(config.github_token(), None);
+    }
+
+    #[test]
+    fn test_interpolate_tool_homes() {
+        let config = GgConfig::default();
+        let mut homes = HashMap::new();
+        homes.insert("java".to_string(), PathBuf::from("/cache/java/17"));
+        homes.insert("node".to_string(), PathBuf::from("/cache/node/20"));
+
+        assert_eq!(
+            config.interpolate("${java.home}/bin:${npm.home}", &homes),
+            "/cache/java/17/bin:/cache/node/20"
+        );
+        // Not in use, or not a thing: left alone
+        assert_eq!(
+            config.interpolate("${gradle.home} ${nope}", &homes),
+            "${gradle.home} ${nope}"
         );
-        assert_eq!(config.aliases.get("test"), Some(&"npm test".to_string()));
     }
 
     #[test]
@@ -186,10 +604,10 @@ test = "npm test"
         let mut config = GgConfig::default();
         config
             .aliases
-            .insert("build".to_string(), "gradle clean build".to_string());
+            .insert("build".to_string(), "gradle clean build".into());
         config
             .aliases
-            .insert("quoted".to_string(), r#"echo "hello world""#.to_string());
+            .insert("quoted".to_string(), r#"echo "hello world""#.into());
 
         let resolved = config.resolve_alias("build").unwrap();
         assert_eq!(resolved, vec!["gradle", "clean", "build"]);
@@ -205,7 +623,7 @@ test = "npm test"
         let mut config = GgConfig::default();
         config.aliases.insert(
             "build-and-test".to_string(),
-            "gradle clean build && npm test".to_string(),
+            "gradle clean build && npm test".into(),
         );
 
         let resolved = config.resolve_alias_with_and("build-and-test").unwrap();
@@ -0,0 +1,715 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use log::warn;
+
+use crate::alias::alias_cycles;
+use crate::cli::{package_source, parse_command_string, ALIASABLE_COMMANDS, PACKAGE_SOURCES};
+use crate::config::GgConfig;
+use crate::executor::{Executor, GgVersionReq};
+use crate::executors::java_distributions::JavaDistributions;
+use crate::prepare::executor_cmd;
+use crate::tools::get_tool_info;
+
+const TOP_LEVEL_KEYS: &[&str] = &[
+    "dependencies",
+    "aliases",
+    "env",
+    "path",
+    "tools",
+    "mirror",
+    "java",
+    "maven",
+    "github",
+];
+const TOOL_KEYS: &[&str] = &["env", "path", "source"];
+const JAVA_KEYS: &[&str] = &["distribution", "toolchains"];
+const MAVEN_KEYS: &[&str] = &["toolchains"];
+const GITHUB_KEYS: &[&str] = &["token"];
+const ALIAS_KEYS: &[&str] = &[
+    "steps",
+    "env",
+    "cwd",
+    "continue_on_error",
+    "parallel",
+    "description",
+    "params",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// Something off in a config file, pointing at the line when we know it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub file: Option<PathBuf>,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.file, self.line) {
+            (Some(file), Some(line)) => write!(f, "{}:{}: ", file.display(), line)?,
+            (Some(file), None) => write!(f, "{}: ", file.display())?,
+            _ => {}
+        }
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}: {}", severity, self.message)
+    }
+}
+
+/// `[a."b c"]` -> `a.b c`, for comparing headers and keys
+fn normalize_key(key: &str) -> String {
+    key.split('.')
+        .map(|part| part.trim().trim_matches('"').trim_matches('\''))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// 1-based line of `key` in table `section` ("" for the top), or of the
+/// `[section.key]` header. Good enough for pointing at things, not a parser.
+fn key_line(content: &str, section: &str, key: &str) -> Option<usize> {
+    let mut current = String::new();
+    let nested = if section.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", section, key)
+    };
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.starts_with("[[") {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('[') {
+            current = normalize_key(header.split(']').next().unwrap_or(""));
+            if current == nested {
+                return Some(i + 1);
+            }
+            continue;
+        }
+        if current == section {
+            if let Some((k, _)) = line.split_once('=') {
+                if normalize_key(k) == key {
+                    return Some(i + 1);
+                }
+            }
+        }
+    }
+    None
+}
+
+fn line_of(span_start: usize, content: &str) -> usize {
+    content[..span_start.min(content.len())]
+        .matches('\n')
+        .count()
+        + 1
+}
+
+struct Checker<'a> {
+    config: &'a GgConfig,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker<'_> {
+    fn push(
+        &mut self,
+        severity: Severity,
+        file: Option<&Path>,
+        line: Option<usize>,
+        message: String,
+    ) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            file: file.map(|f| f.to_path_buf()),
+            line,
+            message,
+        });
+    }
+
+    /// A merged key (`dependencies.node`, `aliases.ci`): which file it came
+    /// from, per the config's sources, and where in there.
+    fn locate(&self, section: &str, key: &str) -> (Option<PathBuf>, Option<usize>) {
+        let path = format!("{}.{}", section, key);
+        let nested = format!("{}.", path);
+        let file = self
+            .config
+            .sources
+            .iter()
+            .find(|(k, _)| **k == path || k.starts_with(&nested))
+            .map(|(_, source)| source.file.clone());
+        let line = file
+            .as_ref()
+            .and_then(|f| fs::read_to_string(f).ok())
+            .and_then(|content| key_line(&content, section, key));
+        (file, line)
+    }
+
+    fn error_at(&mut self, section: &str, key: &str, message: String) {
+        let (file, line) = self.locate(section, key);
+        self.push(Severity::Error, file.as_deref(), line, message);
+    }
+
+    fn warning_at(&mut self, section: &str, key: &str, message: String) {
+        let (file, line) = self.locate(section, key);
+        self.push(Severity::Warning, file.as_deref(), line, message);
+    }
+
+    /// Unknown keys need the file as written, the merged config has lost them.
+    fn check_keys(&mut self, file: &Path, content: &str, table: &toml::Table) {
+        let mut unknown = vec![];
+        for (key, value) in table {
+            if !TOP_LEVEL_KEYS.contains(&key.as_str()) {
+                unknown.push(("".to_string(), key.clone()));
+                continue;
+            }
+            let fields = match (key.as_str(), value) {
+                ("java", toml::Value::Table(java)) => Some((java, JAVA_KEYS)),
+                ("maven", toml::Value::Table(maven)) => Some((maven, MAVEN_KEYS)),
+                ("github", toml::Value::Table(github)) => Some((github, GITHUB_KEYS)),
+                _ => None,
+            };
+            if let Some((fields, known)) = fields {
+                for field in fields.keys() {
+                    if !known.contains(&field.as_str()) {
+                        unknown.push((key.clone(), field.clone()));
+                    }
+                }
+            }
+            let nested = match (key.as_str(), value) {
+                ("tools", toml::Value::Table(tools)) => Some((tools, TOOL_KEYS)),
+                ("aliases", toml::Value::Table(aliases)) => Some((aliases, ALIAS_KEYS)),
+                _ => None,
+            };
+            if let Some((entries, known)) = nested {
+                for (name, entry) in entries {
+                    if let toml::Value::Table(entry) = entry {
+                        for field in entry.keys() {
+                            if !known.contains(&field.as_str()) {
+                                unknown.push((format!("{}.{}", key, name), field.clone()));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        unknown.sort_by_key(|(section, key)| key_line(content, section, key));
+        for (section, key) in unknown {
+            let shown = if section.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", section, key)
+            };
+            self.push(
+                Severity::Error,
+                Some(file),
+                key_line(content, &section, &key),
+                format!("unknown key '{}'", shown),
+            );
+        }
+    }
+
+    fn check_files(&mut self) -> bool {
+        let mut all_parsed = true;
+        for file in &self.config.files {
+            let content = match fs::read_to_string(file) {
+                Ok(content) => content,
+                Err(e) => {
+                    self.push(
+                        Severity::Error,
+                        Some(file),
+                        None,
+                        format!("can't read: {}", e),
+                    );
+                    all_parsed = false;
+                    continue;
+                }
+            };
+            match toml::from_str::<toml::Table>(&content) {
+                Ok(table) => self.check_keys(file, &content, &table),
+                Err(e) => {
+                    let line = e.span().map(|span| line_of(span.start, &content));
+                    self.push(Severity::Error, Some(file), line, e.message().to_string());
+                    all_parsed = false;
+                }
+            }
+        }
+        all_parsed
+    }
+
+    fn check_values(&mut self) {
+        let config = self.config;
+        if let Some(mirror) = &config.mirror {
+            if !mirror.starts_with("http://") && !mirror.starts_with("https://") {
+                self.error_at(
+                    "",
+                    "mirror",
+                    format!("mirror '{}' should be an http:// or https:// URL", mirror),
+                );
+            }
+        }
+        let token_in_project = config
+            .sources
+            .get("github.token")
+            .is_some_and(|s| s.file.file_name().is_some_and(|n| n == "gg.toml"));
+        if token_in_project {
+            let user_config = GgConfig::user_config_file()
+                .map(|f| f.display().to_string())
+                .unwrap_or_else(|| "~/.config/gg/config.toml".to_string());
+            self.warning_at(
+                "github",
+                "token",
+                format!(
+                    "github.token is ignored in gg.toml, which is usually committed; put it in {}",
+                    user_config
+                ),
+            );
+        }
+        if let Some(distribution) = &config.java.distribution {
+            if JavaDistributions::get_by_name(distribution).is_none() {
+                let known = JavaDistributions::get_all()
+                    .iter()
+                    .map(|d| d.name)
+                    .collect::<Vec<_>>();
+                self.error_at(
+                    "java",
+                    "distribution",
+                    format!(
+                        "unknown Java distribution '{}', one of: {}",
+                        distribution,
+                        known.join(", ")
+                    ),
+                );
+            }
+        }
+        let toolchains = [
+            ("java", &config.java.toolchains),
+            ("maven", &config.maven.toolchains),
+        ];
+        for (section, toolchain) in toolchains
+            .iter()
+            .flat_map(|(section, t)| t.iter().flatten().map(move |t| (*section, t)))
+        {
+            let version = toolchain.split('-').next().unwrap_or_default();
+            if GgVersionReq::new(version).is_none() {
+                self.error_at(
+                    section,
+                    "toolchains",
+                    format!("invalid Java toolchain version '{}'", toolchain),
+                );
+            }
+        }
+        let mut deps = config.dependencies.iter().collect::<Vec<_>>();
+        deps.sort();
+        for (name, version) in deps {
+            let custom = config.tools.get(name).is_some_and(|t| t.source.is_some());
+            if get_tool_info(name).is_none()
+                && !name.starts_with("gh/")
+                && package_source(name).is_none()
+                && !custom
+            {
+                self.error_at(
+                    "dependencies",
+                    name,
+                    format!(
+                        "unknown tool '{}', run 'gg tools' to see available tools",
+                        name
+                    ),
+                );
+            }
+            if !version.is_empty() && GgVersionReq::new(version).is_none() {
+                self.error_at(
+                    "dependencies",
+                    name,
+                    format!("invalid version requirement '{}' for {}", version, name),
+                );
+            }
+        }
+
+        let mut tools = config.tools.keys().collect::<Vec<_>>();
+        tools.sort();
+        for name in tools {
+            match (&config.tools[name].source, get_tool_info(name)) {
+                (Some(_), Some(tool)) => self.warning_at(
+                    &format!("tools.{}", name),
+                    "source",
+                    format!("source is ignored, {} is the {} tool", name, tool.name),
+                ),
+                (Some(source), None) => {
+                    if let Err(message) = check_source(source) {
+                        self.error_at(&format!("tools.{}", name), "source", message);
+                    }
+                }
+                (None, None) => self.warning_at(
+                    "tools",
+                    name,
+                    format!("[tools.{}] is not a known tool", name),
+                ),
+                (None, Some(_)) => {}
+            }
+        }
+
+        let mut aliases = config.aliases.keys().collect::<Vec<_>>();
+        aliases.sort();
+        for name in aliases {
+            if let Some(tool) = get_tool_info(name) {
+                self.warning_at(
+                    "aliases",
+                    name,
+                    format!("alias '{}' shadows the {} tool", name, tool.name),
+                );
+            } else if ALIASABLE_COMMANDS.contains(&name.as_str()) {
+                self.warning_at(
+                    "aliases",
+                    name,
+                    format!(
+                        "alias '{}' shadows the built-in 'gg {}' command",
+                        name, name
+                    ),
+                );
+            } else if BUILTIN_COMMANDS.contains(&name.as_str()) {
+                self.warning_at(
+                    "aliases",
+                    name,
+                    format!(
+                        "alias '{}' never runs, 'gg {}' is a built-in command",
+                        name, name
+                    ),
+                );
+            }
+        }
+
+        for cycle in alias_cycles(config) {
+            self.error_at(
+                "aliases",
+                &cycle[0],
+                format!("alias loop: {} -> {}", cycle.join(" -> "), cycle[0]),
+            );
+        }
+    }
+}
+
+/// Built-in commands that always win over an alias of the same name
+const BUILTIN_COMMANDS: &[&str] = &["help", "update", "tools", "clean-cache", "config"];
+
+/// A `[tools.<name>]` source has to be one package, `npm:prettier@3` style
+fn check_source(source: &str) -> Result<(), String> {
+    let sources = PACKAGE_SOURCES
+        .iter()
+        .map(|s| format!("{}:", s))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let cmds = parse_command_string(source, &GgConfig::default());
+    let cmd = match cmds.as_slice() {
+        [cmd] if package_source(&cmd.cmd).is_some() => cmd,
+        _ => {
+            return Err(format!(
+                "source '{}' should be one package, starting with {}",
+                source, sources
+            ))
+        }
+    };
+    if let Some(version) = &cmd.version {
+        if GgVersionReq::new(version).is_none() {
+            return Err(format!(
+                "invalid version requirement '{}' in source",
+                version
+            ));
+        }
+    }
+    if <dyn Executor>::new(executor_cmd(cmd)).is_none() {
+        return Err(format!("'{}' is not a package name", cmd.cmd));
+    }
+    Ok(())
+}
+
+/// Everything wrong with the loaded config and the files behind it.
+pub fn check(config: &GgConfig) -> Vec<Diagnostic> {
+    let mut checker = Checker {
+        config,
+        diagnostics: vec![],
+    };
+    if checker.check_files() {
+        // Parses, but might still not fit (a number where a string goes..).
+        // An unknown key in an alias table already explains that.
+        if let Err(e) = GgConfig::load_layered(&config.files) {
+            if checker.diagnostics.is_empty() {
+                checker.push(Severity::Error, None, None, e);
+            }
+        }
+        checker.check_values();
+    }
+    checker.diagnostics
+}
+
+/// At startup: problems go to stderr rather than being silently ignored.
+/// Warnings only with -v.
+pub fn report(config: &GgConfig) {
+    for diagnostic in check(config) {
+        match diagnostic.severity {
+            Severity::Error => eprintln!("{}", diagnostic),
+            Severity::Warning => warn!("{}", diagnostic),
+        }
+    }
+}
+
+/// `gg config check`
+pub fn run(config: &GgConfig) -> ExitCode {
+    if config.files.is_empty() {
+        println!("No gg.toml configuration file found");
+        println!("Run 'gg config init' to create one");
+        return ExitCode::from(1);
+    }
+    let diagnostics = check(config);
+    for diagnostic in &diagnostics {
+        println!("{}", diagnostic);
+    }
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    let warnings = diagnostics.len() - errors;
+    if diagnostics.is_empty() {
+        let files = config
+            .files
+            .iter()
+            .map(|f| f.display().to_string())
+            .collect::<Vec<_>>();
+        println!("No problems found in {}", files.join(", "));
+    } else {
+        println!("{} error(s), {} warning(s)", errors, warnings);
+    }
+    if errors > 0 {
+        ExitCode::from(1)
+    } else {
+        ExitCode::from(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn check_str(content: &str) -> (PathBuf, Vec<Diagnostic>, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("gg.toml");
+        fs::write(&file, content).unwrap();
+        let config =
+            GgConfig::load_layered(std::slice::from_ref(&file)).unwrap_or_else(|_| GgConfig {
+                files: vec![file.clone()],
+                ..Default::default()
+            });
+        (file, check(&config), temp_dir)
+    }
+
+    fn at(file: &Path, line: usize, severity: Severity, message: &str) -> Diagnostic {
+        Diagnostic {
+            severity,
+            file: Some(file.to_path_buf()),
+            line: Some(line),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_clean_config() {
+        let (_, diagnostics, _dir) = check_str(
+            r#"
+mirror = "http://ci-cache:8080"
+
+[java]
+distribution = "corretto"
+toolchains = ["17", "21-graalvm"]
+
+[maven]
+toolchains = ["11", "17"]
+
+[dependencies]
+node = "^18"
+java = "17"
+"gh/cli/cli" = ""
+
+[aliases]
+build = "gradle build"
+"#,
+        );
+        assert_eq!(diagnostics, vec![]);
+    }
+
+    #[test]
+    fn test_tool_sources() {
+        let (file, diagnostics, _dir) = check_str(
+            r#"[dependencies]
+"npm:@biomejs/biome" = "1"
+prettier = "3"
+
+[tools.prettier]
+source = "npm:prettier"
+
+[tools.ts]
+source = "npm:typescript@^5.4"
+
+[tools.lint]
+source = "pip:ruff@0.4"
+
+[tools.bad]
+source = "prettier"
+
+[tools.node]
+source = "npm:node"
+"#,
+        );
+        assert_eq!(
+            diagnostics,
+            vec![
+                at(
+                    &file,
+                    15,
+                    Severity::Error,
+                    "source 'prettier' should be one package, starting with npm:, pip:"
+                ),
+                at(
+                    &file,
+                    18,
+                    Severity::Warning,
+                    "source is ignored, node is the node tool"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reports_with_lines() {
+        let (file, diagnostics, _dir) = check_str(
+            r#"depndencies = {}
+
+[dependencies]
+nod = "18"
+java = "not a version"
+
+[aliases]
+node = "node@18"
+a = "b x"
+b = "a && c"
+
+[aliases.ci]
+step = ["gradle build"]
+"#,
+        );
+        assert_eq!(
+            diagnostics,
+            vec![
+                at(&file, 1, Severity::Error, "unknown key 'depndencies'"),
+                at(&file, 13, Severity::Error, "unknown key 'aliases.ci.step'"),
+            ]
+        );
+
+        // Fix the unknown keys and the rest shows up
+        let (file, diagnostics, _dir) = check_str(
+            r#"
+[dependencies]
+nod = "18"
+java = "not a version"
+
+[aliases]
+node = "node@18"
+a = "b x"
+b = "a && c"
+install = "npm ci"
+update = "npm update"
+"#,
+        );
+        assert_eq!(
+            diagnostics,
+            vec![
+                at(
+                    &file,
+                    4,
+                    Severity::Error,
+                    "invalid version requirement 'not a version' for java"
+                ),
+                at(
+                    &file,
+                    3,
+                    Severity::Error,
+                    "unknown tool 'nod', run 'gg tools' to see available tools"
+                ),
+                at(
+                    &file,
+                    10,
+                    Severity::Warning,
+                    "alias 'install' shadows the built-in 'gg install' command"
+                ),
+                at(
+                    &file,
+                    7,
+                    Severity::Warning,
+                    "alias 'node' shadows the node tool"
+                ),
+                at(
+                    &file,
+                    11,
+                    Severity::Warning,
+                    "alias 'update' never runs, 'gg update' is a built-in command"
+                ),
+                at(&file, 8, Severity::Error, "alias loop: a -> b -> a"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_java_distribution() {
+        let (_, diagnostics, _dir) = check_str("[java]\ndistribution = \"oracle\"\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, Some(2));
+        assert!(diagnostics[0]
+            .message
+            .starts_with("unknown Java distribution 'oracle', one of: temurin, azul"));
+
+        let (file, diagnostics, _dir) = check_str("[java]\nvendor = \"azul\"\n");
+        assert_eq!(
+            diagnostics,
+            vec![at(&file, 2, Severity::Error, "unknown key 'java.vendor'")]
+        );
+    }
+
+    #[test]
+    fn test_github_token_in_project() {
+        let (file, diagnostics, _dir) = check_str("[github]\ntoken = \"ghp_x\"\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].file.as_deref(), Some(file.as_path()));
+        assert_eq!(diagnostics[0].line, Some(2));
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert!(diagnostics[0]
+            .message
+            .starts_with("github.token is ignored in gg.toml"));
+    }
+
+    #[test]
+    fn test_parse_error_line() {
+        let (file, diagnostics, _dir) = check_str("[dependencies]\nnode = \"18\"\njava = \n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].file.as_deref(), Some(file.as_path()));
+        assert_eq!(diagnostics[0].line, Some(3));
+    }
+
+    #[test]
+    fn test_key_line() {
+        let content = "a = 1\n[aliases]\nbuild = \"x\"\n[aliases.\"ci\"]\nsteps = []\n";
+        assert_eq!(key_line(content, "", "a"), Some(1));
+        assert_eq!(key_line(content, "aliases", "build"), Some(3));
+        assert_eq!(key_line(content, "aliases", "ci"), Some(4));
+        assert_eq!(key_line(content, "aliases.ci", "steps"), Some(5));
+        assert_eq!(key_line(content, "aliases", "nope"), None);
+    }
+}
@@ -0,0 +1,372 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use regex::Regex;
+use toml_edit::{DocumentMut, Item, TableLike, Value};
+
+use crate::config::GgConfig;
+use crate::executor::GgVersionReq;
+use crate::executors::gradle_properties::parse_distribution_url;
+use crate::tools::get_tool_info;
+
+/// `node@^20` -> ("node", "^20"). No version means any.
+fn parse_spec(spec: &str) -> Result<(String, String), String> {
+    let (name, version) = spec.split_once('@').unwrap_or((spec, "*"));
+    if get_tool_info(name).is_none() && !name.starts_with("gh/") {
+        return Err(format!(
+            "Unknown tool '{}'. Run 'gg tools' to see available tools",
+            name
+        ));
+    }
+    if GgVersionReq::new(version).is_none() {
+        return Err(format!(
+            "Invalid version requirement '{}' for {}",
+            version, name
+        ));
+    }
+    Ok((name.to_string(), version.to_string()))
+}
+
+/// A table in the document, made if it isn't there
+fn table<'a>(parent: &'a mut dyn TableLike, name: &str) -> Result<&'a mut dyn TableLike, String> {
+    parent
+        .entry(name)
+        .or_insert(toml_edit::table())
+        .as_table_like_mut()
+        .ok_or_else(|| format!("'{}' in gg.toml is not a table", name))
+}
+
+/// Set `key`, keeping the comment after the old value if there was one
+fn put(table: &mut dyn TableLike, key: &str, value: Value) {
+    let mut value = value;
+    if let Some(old) = table.get(key).and_then(Item::as_value) {
+        *value.decor_mut() = old.decor().clone();
+    }
+    table.insert(key, Item::Value(value));
+}
+
+pub fn add_dependency(doc: &mut DocumentMut, name: &str, version: &str) -> Result<(), String> {
+    put(
+        table(doc.as_table_mut(), "dependencies")?,
+        name,
+        version.into(),
+    );
+    Ok(())
+}
+
+pub fn set_alias(doc: &mut DocumentMut, name: &str, command: &str) -> Result<(), String> {
+    put(table(doc.as_table_mut(), "aliases")?, name, command.into());
+    Ok(())
+}
+
+/// Drop a dependency, or else an alias, by that name. Says which it was.
+pub fn remove(doc: &mut DocumentMut, name: &str) -> Result<&'static str, String> {
+    for section in ["dependencies", "aliases"] {
+        let removed = doc
+            .get_mut(section)
+            .and_then(Item::as_table_like_mut)
+            .and_then(|t| t.remove(name));
+        if removed.is_some() {
+            return Ok(section);
+        }
+    }
+    Err(format!(
+        "'{}' is not a dependency or alias in gg.toml",
+        name
+    ))
+}
+
+/// `env.GRADLE_OPTS -Xmx1g`: any dotted key. Values are strings (versions
+/// look like numbers but aren't), unless they're a TOML bool, array or table.
+pub fn set(doc: &mut DocumentMut, key: &str, value: &str) -> Result<(), String> {
+    let parts = key.split('.').collect::<Vec<_>>();
+    if parts.iter().any(|p| p.is_empty()) {
+        return Err(format!("Invalid key '{}'", key));
+    }
+    let value = match value.parse::<Value>() {
+        Ok(
+            v @ (Value::Boolean(_) | Value::Array(_) | Value::InlineTable(_) | Value::String(_)),
+        ) => v.decorated("", ""),
+        _ => Value::from(value),
+    };
+
+    let mut current: &mut dyn TableLike = doc.as_table_mut();
+    for part in &parts[..parts.len() - 1] {
+        current = table(current, part)?;
+    }
+    put(current, parts[parts.len() - 1], value);
+    Ok(())
+}
+
+/// A tool `config init` found a reason to add
+#[derive(Debug, PartialEq)]
+pub struct Detected {
+    pub tool: &'static str,
+    pub version: String,
+    pub from: &'static str,
+}
+
+fn read(dir: &Path, file: &str) -> Option<String> {
+    fs::read_to_string(dir.join(file)).ok()
+}
+
+fn capture(content: &str, pattern: &str) -> Option<String> {
+    Regex::new(pattern)
+        .unwrap()
+        .captures(content)
+        .map(|c| c[1].to_string())
+}
+
+/// npm style `>=18 <21` -> `>=18, <21`, or any version if it's beyond us
+fn version_or_any(version: Option<String>) -> String {
+    version
+        .map(|v| v.split_whitespace().collect::<Vec<_>>().join(", "))
+        .filter(|v| !v.is_empty() && GgVersionReq::new(v).is_some())
+        .unwrap_or_else(|| "*".to_string())
+}
+
+/// `1.8` is Java 8
+fn java_version(version: String) -> String {
+    version.strip_prefix("1.").unwrap_or(&version).to_string()
+}
+
+/// What the project in `dir` looks like it needs, from its build files.
+pub fn detect_project(dir: &Path) -> Vec<Detected> {
+    let mut found = vec![];
+    let mut add = |tool, version, from| {
+        found.push(Detected {
+            tool,
+            version,
+            from,
+        })
+    };
+
+    if let Some(package) = read(dir, "package.json") {
+        let engines = serde_json::from_str::<serde_json::Value>(&package)
+            .ok()
+            .and_then(|p| p["engines"]["node"].as_str().map(|v| v.to_string()));
+        add("node", version_or_any(engines), "package.json");
+    }
+
+    if let Some(pom) = read(dir, "pom.xml") {
+        let wrapper = read(dir, ".mvn/wrapper/maven-wrapper.properties")
+            .and_then(|p| capture(&p, r"apache-maven/(\d+\.\d+\.\d+)/"));
+        add(
+            "maven",
+            wrapper.unwrap_or_else(|| "3".to_string()),
+            "pom.xml",
+        );
+        let java = capture(
+            &pom,
+            r"<(?:maven\.compiler\.release|maven\.compiler\.source|java\.version)>\s*([\d.]+)\s*<",
+        );
+        if let Some(java) = java {
+            add("java", java_version(java), "pom.xml");
+        }
+    }
+
+    let gradle_file = [
+        "build.gradle.kts",
+        "build.gradle",
+        "settings.gradle.kts",
+        "settings.gradle",
+    ]
+    .iter()
+    .copied()
+    .find(|f| dir.join(f).is_file());
+    if let Some(gradle_file) = gradle_file {
+        let wrapper = read(dir, "gradle/wrapper/gradle-wrapper.properties")
+            .and_then(|p| capture(&p, r"distributionUrl\s*=\s*(\S+)"))
+            .and_then(|url| parse_distribution_url(&url))
+            .map(|d| d.version);
+        add("gradle", version_or_any(wrapper), gradle_file);
+        let java = read(dir, gradle_file).and_then(|build| {
+            capture(&build, r"JavaLanguageVersion\.of\(\s*(\d+)\s*\)")
+                .or_else(|| capture(&build, r"JavaVersion\.VERSION_(\d+(?:_\d+)?)"))
+                .or_else(|| capture(&build, r#"sourceCompatibility\s*=\s*['"]?([\d.]+)"#))
+        });
+        if let Some(java) = java {
+            add("java", java_version(java.replace('_', ".")), gradle_file);
+        }
+    }
+
+    if let Some(go_mod) = read(dir, "go.mod") {
+        let version = capture(&go_mod, r"(?m)^go\s+(\d+\.\d+(?:\.\d+)?)\s*$");
+        add("go", version_or_any(version), "go.mod");
+    }
+
+    if let Some(pubspec) = read(dir, "pubspec.yaml") {
+        let version = serde_yaml::from_str::<serde_yaml::Value>(&pubspec)
+            .ok()
+            .and_then(|p| p["environment"]["flutter"].as_str().map(|v| v.to_string()));
+        add("flutter", version_or_any(version), "pubspec.yaml");
+    }
+
+    // Maven and Gradle might both have said which Java
+    let mut seen = vec![];
+    found.retain(|d| {
+        let first = !seen.contains(&d.tool);
+        seen.push(d.tool);
+        first
+    });
+    found
+}
+
+/// `gg config add|remove|alias|set`: edit the nearest gg.toml (or a new one
+/// here), keeping its comments and layout.
+pub fn run(action: &str, args: &[String]) -> ExitCode {
+    let path = GgConfig::find_config_file().unwrap_or_else(|| PathBuf::from("gg.toml"));
+    let content = if path.exists() {
+        match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                println!("Error: Failed to read {}: {}", path.display(), e);
+                return ExitCode::from(1);
+            }
+        }
+    } else if action == "remove" {
+        println!("No gg.toml configuration file found");
+        return ExitCode::from(1);
+    } else {
+        String::new()
+    };
+    let mut doc = match content.parse::<DocumentMut>() {
+        Ok(doc) => doc,
+        Err(e) => {
+            println!("Error: {} is not valid TOML: {}", path.display(), e);
+            return ExitCode::from(1);
+        }
+    };
+
+    let result = match (action, args) {
+        ("add", specs) => specs.iter().try_for_each(|spec| {
+            let (name, version) = parse_spec(spec)?;
+            add_dependency(&mut doc, &name, &version)?;
+            println!("Added {} = \"{}\"", name, version);
+            Ok(())
+        }),
+        ("remove", names) => names.iter().try_for_each(|name| {
+            let section = remove(&mut doc, name)?;
+            println!("Removed {} from [{}]", name, section);
+            Ok(())
+        }),
+        ("alias", [name, command @ ..]) if !command.is_empty() => {
+            let command = command.join(" ");
+            set_alias(&mut doc, name, &command)
+                .map(|_| println!("Set alias {} = \"{}\"", name, command))
+        }
+        ("set", [key, value]) => {
+            set(&mut doc, key, value).map(|_| println!("Set {} = {}", key, value))
+        }
+        _ => Err(format!("Usage: gg config {} ...", action)),
+    };
+    if let Err(e) = result {
+        println!("Error: {}", e);
+        return ExitCode::from(1);
+    }
+
+    if let Err(e) = fs::write(&path, doc.to_string()) {
+        println!("Error: Failed to write {}: {}", path.display(), e);
+        return ExitCode::from(1);
+    }
+    println!("Updated {}", path.display());
+    ExitCode::from(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const CONFIG: &str = r#"# Our tools
+[dependencies]
+node = "^18" # LTS please
+
+[aliases]
+build = "gradle build"
+# Checks it
+lint = "gradle check"
+"#;
+
+    #[test]
+    fn test_edits_keep_comments() {
+        let mut doc = CONFIG.parse::<DocumentMut>().unwrap();
+        add_dependency(&mut doc, "node", "^20").unwrap();
+        add_dependency(&mut doc, "java", "21").unwrap();
+        assert_eq!(remove(&mut doc, "build").unwrap(), "aliases");
+        set_alias(&mut doc, "test", "npm test").unwrap();
+        set(&mut doc, "env.GRADLE_OPTS", "-Xmx1g").unwrap();
+        set(&mut doc, "aliases.ci.parallel", "true").unwrap();
+        assert!(remove(&mut doc, "nope").is_err());
+
+        let edited = doc.to_string();
+        assert!(edited.starts_with("# Our tools\n[dependencies]\n"));
+        assert!(edited.contains("node = \"^20\" # LTS please"));
+        assert!(edited.contains("# Checks it\nlint"));
+
+        let config: GgConfig = toml::from_str(&edited).unwrap();
+        assert_eq!(config.dependencies["node"], "^20");
+        assert_eq!(config.dependencies["java"], "21");
+        assert!(!config.aliases.contains_key("build"));
+        assert_eq!(config.aliases["test"], "npm test".into());
+        assert_eq!(config.env["GRADLE_OPTS"], "-Xmx1g");
+    }
+
+    #[test]
+    fn test_parse_spec() {
+        assert_eq!(
+            parse_spec("node@^20").unwrap(),
+            ("node".to_string(), "^20".to_string())
+        );
+        assert_eq!(
+            parse_spec("gradle").unwrap(),
+            ("gradle".to_string(), "*".to_string())
+        );
+        assert!(parse_spec("nope@1").is_err());
+        assert!(parse_spec("node@lol").is_err());
+    }
+
+    #[test]
+    fn test_detect_project() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path();
+        fs::write(
+            path.join("package.json"),
+            r#"{"engines": {"node": ">=18 <21"}}"#,
+        )
+        .unwrap();
+        fs::write(
+            path.join("pom.xml"),
+            "<properties><maven.compiler.release>17</maven.compiler.release></properties>",
+        )
+        .unwrap();
+        fs::write(
+            path.join("build.gradle.kts"),
+            "java { toolchain { languageVersion = JavaLanguageVersion.of(21) } }",
+        )
+        .unwrap();
+        fs::create_dir_all(path.join("gradle/wrapper")).unwrap();
+        fs::write(
+            path.join("gradle/wrapper/gradle-wrapper.properties"),
+            "distributionUrl=https\\://services.gradle.org/distributions/gradle-8.5-bin.zip\n",
+        )
+        .unwrap();
+        fs::write(path.join("go.mod"), "module x\n\ngo 1.21\n").unwrap();
+
+        let found = detect_project(path)
+            .into_iter()
+            .map(|d| (d.tool, d.version))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                ("node", ">=18, <21".to_string()),
+                ("maven", "3".to_string()),
+                ("java", "17".to_string()),
+                ("gradle", "8.5".to_string()),
+                ("go", "1.21".to_string()),
+            ]
+        );
+    }
+}
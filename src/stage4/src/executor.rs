@@ -10,6 +10,8 @@ use std::sync::{Arc, Mutex};
 
 use crate::bloody_indiana_jones::BloodyIndianaJones;
 use crate::executors::github::GitHub;
+use crate::executors::node::{Node, NpmPackageSpec};
+use crate::executors::python::{PipPackageSpec, Python};
 use crate::target::{Arch, Os, Target, Variant};
 use indicatif::ProgressBar;
 use log::{debug, info};
@@ -227,6 +229,17 @@ impl dyn Executor {
             }
         }
 
+        if let Some(spec) = NpmPackageSpec::from_cmd(&executor_cmd.cmd) {
+            return Some(Box::new(Node::new(executor_cmd, Some(spec))));
+        }
+
+        if let Some(spec) = PipPackageSpec::from_cmd(&executor_cmd.cmd) {
+            return Some(Box::new(Python {
+                executor_cmd,
+                pip_package: Some(spec),
+            }));
+        }
+
         if let Some(tool_info) = get_tool_info(&executor_cmd.cmd) {
             return (tool_info.factory)(executor_cmd);
         }
@@ -282,6 +295,16 @@ pub trait Executor {
         HashMap::new()
     }
 
+    /// Env that takes more downloads to work out, after this executor is
+    /// prepped: the JDKs a Gradle build's toolchains ask for
+    fn get_extra_env<'a>(
+        &'a self,
+        _input: &'a AppInput,
+        _pb: &'a ProgressBar,
+    ) -> Pin<Box<dyn Future<Output = HashMap<String, String>> + 'a>> {
+        Box::pin(async { HashMap::new() })
+    }
+
     fn get_bin_dirs(&self) -> Vec<String> {
         vec!["bin".to_string(), ".".to_string()]
     }
@@ -296,7 +319,11 @@ pub trait Executor {
     fn post_download(&self, _download_file_path: String) -> bool {
         true
     }
-    fn post_prep(&self, _cache_path: &str) {}
+    /// After unpacking, before the install counts as cached. An error throws
+    /// the install away.
+    fn post_prep(&self, _cache_path: &str) -> Result<(), String> {
+        Ok(())
+    }
 }
 
 pub fn java_deps<'a>() -> Pin<Box<dyn Future<Output = Vec<ExecutorDep>> + 'a>> {
@@ -328,28 +355,41 @@ fn get_executor_app_path(
     get_app_path(path, input).ok()
 }
 
-pub async fn prep(
-    executor: &dyn Executor,
-    input: &AppInput,
-    pb: &ProgressBar,
-) -> Result<AppPath, String> {
-    if let Some(app_path) = executor.custom_prep(input) {
-        return Ok(app_path);
-    }
+/// Does a cached dir hold what the executor expects to run? Looks only under
+/// the dir's own bin dirs, not $PATH.
+pub fn cached_install_ok(executor: &dyn Executor, input: &AppInput, app_path: &AppPath) -> bool {
+    let path_vars = bin_path_vars(executor, app_path);
+    let sep = if cfg!(windows) { ";" } else { ":" };
+    let all_paths = path_vars.join(sep);
+    resolve_bin_path(
+        &executor.get_bins_for_path(input, app_path),
+        &path_vars,
+        &all_paths,
+    )
+    .is_some()
+        || executor.cached_install_is_valid(app_path)
+}
 
-    let executor_cmd = &executor.get_executor_cmd();
-    let version_req = if let Some(ver) = &executor_cmd.version {
+/// The version requirement an executor installs for, as it goes into the
+/// cache dir name and gg-meta.json
+fn version_req_string(executor: &dyn Executor) -> String {
+    let version_req = if let Some(ver) = &executor.get_executor_cmd().version {
         Some(ver.to_version_req())
     } else {
         executor.get_version_req()
     };
-    let version_req_str = &version_req
+    version_req
         .as_ref()
         .map(|v| v.to_string())
-        .unwrap_or("*".to_string());
-    let path_path = Path::new(executor.get_name()).join(
+        .unwrap_or("*".to_string())
+}
+
+/// Where in the cache an executor installs to, relative to the cache dir
+pub fn cache_subdir(executor: &dyn Executor) -> PathBuf {
+    let executor_cmd = executor.get_executor_cmd();
+    Path::new(executor.get_name()).join(
         executor.get_name().to_string()
-            + &version_req_str
+            + &version_req_string(executor)
                 .as_str()
                 .replace("*", "_star_")
                 .replace("^", "_hat_")
@@ -377,7 +417,20 @@ pub async fn prep(
                 .map(|d| format!("d{d}"))
                 .unwrap_or_default()
                 .as_str(),
-    );
+    )
+}
+
+pub async fn prep(
+    executor: &dyn Executor,
+    input: &AppInput,
+    pb: &ProgressBar,
+) -> Result<AppPath, String> {
+    if let Some(app_path) = executor.custom_prep(input) {
+        return Ok(app_path);
+    }
+
+    let version_req_str = &version_req_string(executor);
+    let path_path = cache_subdir(executor);
     let path = path_path.to_str().unwrap();
 
     let app_path = get_executor_app_path(executor, input, path);
@@ -395,17 +448,8 @@ pub async fn prep(
             // under the cache's own search paths (not $PATH); otherwise drop the
             // stale dir and re-download rather than return a hit that fails at
             // run time.
-            let path_vars = bin_path_vars(executor, &app_path_ok);
-            let sep = if cfg!(windows) { ";" } else { ":" };
-            let all_paths = path_vars.join(sep);
-            if resolve_bin_path(
-                &executor.get_bins_for_path(input, &app_path_ok),
-                &path_vars,
-                &all_paths,
-            )
-            .is_some()
-                || executor.cached_install_is_valid(&app_path_ok)
-            {
+            if cached_install_ok(executor, input, &app_path_ok) {
+                crate::cache::touch_last_used(&app_path_ok.install_dir);
                 return Ok(app_path_ok);
             }
             info!(
@@ -467,7 +511,7 @@ pub async fn prep(
 
     debug!("{:?}", url_string);
 
-    let cache_base_dir = std::env::var("GG_CACHE_DIR").unwrap_or_else(|_| ".cache/gg".to_string());
+    let cache_base_dir = crate::cache::install_dir().to_string_lossy().to_string();
     let cache_path = format!("{cache_base_dir}/{path}");
     let mut bloody_indiana_jones = BloodyIndianaJones::new_with_cache_dir(
         url_string.to_string(),
@@ -496,7 +540,20 @@ pub async fn prep(
         }
     }
 
-    executor.post_prep(cache_path.as_str());
+    if let Err(e) = executor.post_prep(cache_path.as_str()) {
+        let _ = std::fs::remove_dir_all(&cache_path);
+        return Err(e);
+    }
+    // Hashing a JDK takes a while, kept off the runtime so parallel preps go on
+    let dir = PathBuf::from(&cache_path);
+    let written = tokio::task::spawn_blocking(move || crate::manifest::write(&dir))
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|written| written);
+    if let Err(e) = written {
+        info!("Could not write manifest for {}: {}", name, e);
+    }
+    crate::cache::touch_last_used(Path::new(&cache_path));
 
     get_executor_app_path(executor, input, path).ok_or(format!("Error: Unable to locate {} binary after download. The downloaded package may not contain the expected executable.", executor.get_name()))
 }
@@ -530,9 +587,7 @@ fn score_filename_match(filename: &str, tool_name: &str, version_re: &Regex) ->
 /// want when nothing plainer is on offer. A tiebreaker, so `tool-linux-x64.zip`
 /// beats `tool-linux-x64-baseline-profile.zip` when both fit the target.
 fn variant_noise(filename: &str) -> usize {
-    let tokens: Vec<&str> = filename
-        .split(|c: char| c == '-' || c == '_' || c == '.')
-        .collect();
+    let tokens: Vec<&str> = filename.split(['-', '_', '.']).collect();
     ["profile", "debug", "baseline"]
         .iter()
         .filter(|flavor| tokens.contains(flavor))
@@ -675,7 +730,7 @@ fn get_url_matches(urls: &[Download], input: &AppInput, executor: &dyn Executor)
 }
 
 fn get_app_path(path: &str, _input: &AppInput) -> Result<AppPath, String> {
-    let cache_base_dir = std::env::var("GG_CACHE_DIR").unwrap_or_else(|_| ".cache/gg".to_string());
+    let cache_base_dir = crate::cache::install_dir();
     let path = env::current_dir()
         .map_err(|_| "Current dir not found")?
         .join(cache_base_dir)
@@ -1046,15 +1101,22 @@ mod tests {
         // that breaks parsing (#293).
         let ge = GgVersionReq::new(">=18.0.0").unwrap();
         assert_eq!(">=18.0.0", ge.to_string());
-        let m = |v: &str| ge.to_version_req().matches(&GgVersion::new(v).unwrap().to_version());
+        let m = |v: &str| {
+            ge.to_version_req()
+                .matches(&GgVersion::new(v).unwrap().to_version())
+        };
         assert!(m("18.0.0"));
         assert!(m("19.2.0"));
         assert!(!m("17.0.0"));
 
         let lt = GgVersionReq::new("<2.0.0").unwrap();
         assert_eq!("<2.0.0", lt.to_string());
-        assert!(lt.to_version_req().matches(&GgVersion::new("1.9.0").unwrap().to_version()));
-        assert!(!lt.to_version_req().matches(&GgVersion::new("2.0.0").unwrap().to_version()));
+        assert!(lt
+            .to_version_req()
+            .matches(&GgVersion::new("1.9.0").unwrap().to_version()));
+        assert!(!lt
+            .to_version_req()
+            .matches(&GgVersion::new("2.0.0").unwrap().to_version()));
     }
 
     fn parse_release_assets(text: &str) -> Vec<String> {
@@ -10,6 +10,11 @@ use crate::executor::{
 };
 use crate::executors::github::GitHub;
 
+const WRAPPER_PROPERTIES_FILE: &str = "lib/bld/bld-wrapper.properties";
+
+/// Version files in the current dir
+pub const VERSION_FILES: &[&str] = &[WRAPPER_PROPERTIES_FILE];
+
 pub struct Bld {
     github: GitHub,
 }
@@ -28,8 +33,7 @@ impl Bld {
     }
 
     fn get_bld_version() -> Option<String> {
-        let properties_path = "lib/bld/bld-wrapper.properties";
-        if let Ok(content) = fs::read_to_string(properties_path) {
+        if let Ok(content) = fs::read_to_string(WRAPPER_PROPERTIES_FILE) {
             for line in content.lines() {
                 if line.starts_with("bld.version=") {
                     return Some(line.trim_start_matches("bld.version=").to_string());
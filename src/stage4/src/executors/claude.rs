@@ -43,7 +43,11 @@ async fn get_claude_downloads(target: &crate::target::Target) -> Vec<Download> {
         return vec![];
     };
 
-    let version = match reqwest::get(format!("{DOWNLOAD_BASE_URL}/latest")).await {
+    let version = match reqwest::get(crate::mirror::mirrored(&format!(
+        "{DOWNLOAD_BASE_URL}/latest"
+    )))
+    .await
+    {
         Ok(res) => match res.text().await {
             Ok(text) => text.trim().to_string(),
             Err(e) => {
@@ -113,7 +117,7 @@ impl Executor for Claude {
         vec![".".to_string()]
     }
 
-    fn post_prep(&self, cache_path: &str) {
+    fn post_prep(&self, cache_path: &str) -> Result<(), String> {
         #[cfg(unix)]
         {
             use std::os::unix::fs::PermissionsExt;
@@ -125,5 +129,6 @@ impl Executor for Claude {
                 }
             }
         }
+        Ok(())
     }
 }
@@ -54,7 +54,7 @@ impl Executor for Flutter {
             ];
 
             for (url, os) in urls {
-                match reqwest::get(url).await {
+                match reqwest::get(crate::mirror::mirrored(url)).await {
                     Ok(response) => {
                         if let Ok(text) = response.text().await {
                             if let Ok(releases) = serde_json::from_str::<serde_json::Value>(&text) {
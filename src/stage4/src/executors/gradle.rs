@@ -1,18 +1,59 @@
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
 use std::path::Path;
 use std::pin::Pin;
 
+use indicatif::ProgressBar;
 use log::{debug, info};
 use scraper::{Html, Selector};
 use semver::VersionReq;
+use serde::Deserialize;
 use sha256::try_digest;
 
-use crate::executor::{java_deps, AppInput, BinPattern, Download, ExecutorCmd, ExecutorDep};
-use crate::executors::gradle_properties::GradleAndWrapperProperties;
-use crate::fetch::fetch_text;
-use crate::target::Variant;
+use crate::executor::{
+    java_deps, AppInput, BinPattern, Download, ExecutorCmd, ExecutorDep, GgVersion,
+};
+use crate::executors::gradle_properties::{parse_distribution_url, GradleAndWrapperProperties};
+use crate::executors::gradle_toolchains::prep_toolchains;
+use crate::fetch::{fetch_json, fetch_text};
+use crate::target::{Arch, Os, Variant};
 use crate::{target, Executor};
 
+/// Pre-releases, and the bigger distribution with sources and docs
+const OPT_IN_TAGS: [&str; 4] = ["rc", "milestone", "nightly", "all"];
+
+/// One entry of services.gradle.org/versions/all
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GradleVersion {
+    download_url: String,
+    #[serde(default)]
+    broken: bool,
+}
+
+/// Both the -bin the index points at and its -all sibling
+fn version_downloads(versions: Vec<GradleVersion>) -> Vec<Download> {
+    versions
+        .into_iter()
+        .filter(|v| !v.broken)
+        .flat_map(|v| {
+            let all = v.download_url.replace("-bin.zip", "-all.zip");
+            [v.download_url, all]
+        })
+        .filter_map(|url| {
+            let distribution = parse_distribution_url(&url)?;
+            Some(Download {
+                version: GgVersion::new(&distribution.version),
+                download_url: url,
+                os: Some(Os::Any),
+                arch: Some(Arch::Any),
+                variant: Some(Variant::Any),
+                tags: distribution.tags,
+            })
+        })
+        .collect()
+}
+
 pub struct Gradle {
     pub executor_cmd: ExecutorCmd,
     props: GradleAndWrapperProperties,
@@ -54,6 +95,13 @@ impl Executor for Gradle {
                 }
             }
 
+            if let Some(versions) =
+                fetch_json::<Vec<GradleVersion>>("https://services.gradle.org/versions/all").await
+            {
+                return version_downloads(versions);
+            }
+
+            // The releases page only has final releases, but beats nothing
             let body = match fetch_text("https://gradle.org/releases").await {
                 Some(body) => body,
                 None => return vec![],
@@ -64,13 +112,15 @@ impl Executor for Gradle {
                 .select(&Selector::parse("a[name]").unwrap())
                 .map(|link| {
                     let version = link.value().attr("name").unwrap_or("").to_string();
-                    Download::new(
+                    let mut download = Download::new(
                         format!(
                             "https://services.gradle.org/distributions/gradle-{version}-bin.zip"
                         ),
                         version.as_str(),
                         Some(Variant::Any),
-                    )
+                    );
+                    download.tags.insert("bin".to_string());
+                    download
                 })
                 .collect()
         })
@@ -97,6 +147,23 @@ impl Executor for Gradle {
         java_deps()
     }
 
+    fn get_extra_env<'a>(
+        &'a self,
+        input: &'a AppInput,
+        pb: &'a ProgressBar,
+    ) -> Pin<Box<dyn Future<Output = HashMap<String, String>> + 'a>> {
+        Box::pin(prep_toolchains(input, pb))
+    }
+
+    /// Opted into with a tag, `gradle@8+all` or `gradle@+rc`
+    fn get_default_exclude_tags(&self) -> HashSet<String> {
+        OPT_IN_TAGS
+            .iter()
+            .filter(|t| !self.executor_cmd.include_tags.contains(**t))
+            .map(|t| t.to_string())
+            .collect()
+    }
+
     fn post_download(&self, download_file_path: String) -> bool {
         if let Some(checksum) = self.props.get_distribution_sha256sum() {
             info!("Checksum found for {}: {}", &download_file_path, checksum);
@@ -110,3 +177,55 @@ impl Executor for Gradle {
         true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_downloads() {
+        let versions: Vec<GradleVersion> = serde_json::from_str(
+            r#"[
+                {"version": "8.6-rc-1", "downloadUrl": "https://services.gradle.org/distributions/gradle-8.6-rc-1-bin.zip", "rcFor": "8.6", "broken": false},
+                {"version": "8.5", "downloadUrl": "https://services.gradle.org/distributions/gradle-8.5-bin.zip", "current": true},
+                {"version": "8.4", "downloadUrl": "https://services.gradle.org/distributions/gradle-8.4-bin.zip", "broken": true}
+            ]"#,
+        )
+        .unwrap();
+        let downloads = version_downloads(versions);
+        let urls = downloads
+            .iter()
+            .map(|d| d.download_url.rsplit('/').next().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            urls,
+            vec![
+                "gradle-8.6-rc-1-bin.zip",
+                "gradle-8.6-rc-1-all.zip",
+                "gradle-8.5-bin.zip",
+                "gradle-8.5-all.zip"
+            ]
+        );
+        assert_eq!(
+            downloads[0].version.as_ref().unwrap().to_string(),
+            "8.6.0-rc.1"
+        );
+        assert!(downloads[3].tags.contains("all"));
+
+        let gradle = |include: &[&str]| Gradle {
+            executor_cmd: ExecutorCmd {
+                cmd: "gradle".to_string(),
+                version: None,
+                distribution: None,
+                include_tags: include.iter().map(|t| t.to_string()).collect(),
+                exclude_tags: HashSet::new(),
+                gems: None,
+            },
+            props: GradleAndWrapperProperties::new(),
+        };
+        assert_eq!(gradle(&[]).get_default_exclude_tags().len(), 4);
+        let excluded = gradle(&["all", "rc"]).get_default_exclude_tags();
+        assert!(!excluded.contains("all") && !excluded.contains("rc"));
+        assert!(excluded.contains("nightly"));
+    }
+}
@@ -1,4 +1,6 @@
+use std::collections::HashSet;
 use std::fs;
+use std::sync::LazyLock;
 
 use regex::Regex;
 use serde::Deserialize;
@@ -23,24 +25,58 @@ pub struct GradleAndWrapperProperties {
     pub gradle_wrapper_properties: Option<GradleWrapperProperties>,
 }
 
-fn get_version_from_gradle_url(gradle_url: &str) -> Option<String> {
-    if let Ok(r) = Regex::new(r"gradle-(.*)-") {
-        let captures: Vec<_> = r.captures_iter(gradle_url).collect();
-        if !captures.is_empty() {
-            if let Some(cap) = captures[0].get(1) {
-                return Some(cap.as_str().to_string());
-            }
+/// What a Gradle distribution file name says: `gradle-8.5-bin.zip`,
+/// `gradle-8.6-rc-1-all.zip`, `gradle-8.7-milestone-2-bin.zip` or a nightly's
+/// `gradle-8.8-20240101001234+0000-bin.zip`
+#[derive(Debug, PartialEq)]
+pub struct GradleDistribution {
+    /// As semver when it has to be: `8.5`, `8.6.0-rc.1`, `8.8.0-nightly.20240101001234`
+    pub version: String,
+    /// `bin`/`all`, and `rc`, `milestone` or `nightly`
+    pub tags: HashSet<String>,
+}
+
+pub fn parse_distribution_url(gradle_url: &str) -> Option<GradleDistribution> {
+    static DISTRIBUTION: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"^gradle-(\d+(?:\.\d+)*)(?:-(rc|milestone)-(\d+))?(?:-(\d{14})[+-]\d{4})?-(bin|all)\.zip$").unwrap()
+    });
+    let file_name = gradle_url.rsplit('/').next()?;
+    let captures = DISTRIBUTION.captures(file_name)?;
+    let numbers = &captures[1];
+    let mut tags = HashSet::from([captures[5].to_string()]);
+    let pre_release = match (captures.get(2), captures.get(3), captures.get(4)) {
+        (Some(kind), Some(n), _) => Some(format!("{}.{}", kind.as_str(), n.as_str())),
+        (_, _, Some(timestamp)) => Some(format!("nightly.{}", timestamp.as_str())),
+        _ => None,
+    };
+    let version = match pre_release {
+        None => numbers.to_string(),
+        Some(pre_release) => {
+            tags.insert(pre_release.split('.').next().unwrap_or_default().to_string());
+            let mut parts = numbers.split('.').collect::<Vec<_>>();
+            parts.resize(3, "0");
+            format!("{}-{}", parts.join("."), pre_release)
         }
-    }
-    None
+    };
+    Some(GradleDistribution { version, tags })
+}
+
+fn get_version_from_gradle_url(gradle_url: &str) -> Option<String> {
+    parse_distribution_url(gradle_url).map(|d| d.version)
 }
 
+const WRAPPER_PROPERTIES_FILE: &str = "gradle/wrapper/gradle-wrapper.properties";
+const GRADLE_PROPERTIES_FILE: &str = "gradle.properties";
+
+/// Version files in the current dir, the wrapper's first
+pub const VERSION_FILES: &[&str] = &[WRAPPER_PROPERTIES_FILE, GRADLE_PROPERTIES_FILE];
+
 impl GradleAndWrapperProperties {
     pub fn new() -> GradleAndWrapperProperties {
         GradleAndWrapperProperties {
-            gradle_properties: fs::read_to_string("gradle.properties").ok()
+            gradle_properties: fs::read_to_string(GRADLE_PROPERTIES_FILE).ok()
                 .and_then(|text| serde_java_properties::from_str(text.as_str()).ok()),
-            gradle_wrapper_properties: fs::read_to_string("gradle/wrapper/gradle-wrapper.properties").ok()
+            gradle_wrapper_properties: fs::read_to_string(WRAPPER_PROPERTIES_FILE).ok()
                 .and_then(|text| serde_java_properties::from_str(text.as_str()).ok()),
         }
     }
@@ -73,7 +109,9 @@ impl GradleAndWrapperProperties {
 
 #[cfg(test)]
 mod tests {
-    use crate::executors::gradle_properties::{get_version_from_gradle_url, GradleProperties, GradleWrapperProperties};
+    use std::collections::HashSet;
+
+    use crate::executors::gradle_properties::{get_version_from_gradle_url, parse_distribution_url, GradleProperties, GradleWrapperProperties};
 
     #[test]
     fn test_get_version_from_distribution_url() {
@@ -82,6 +120,23 @@ mod tests {
         assert_eq!(version.unwrap(), "6.8.3");
     }
 
+    #[test]
+    fn test_parse_distribution_url() {
+        let tags = |tags: &[&str]| tags.iter().map(|t| t.to_string()).collect::<HashSet<_>>();
+        let parse = |url: &str| parse_distribution_url(url).map(|d| (d.version, d.tags));
+
+        assert_eq!(parse("https://services.gradle.org/distributions/gradle-8.5-all.zip"), Some(("8.5".to_string(), tags(&["all"]))));
+        // A repository with gradle- in its own name threw off the old regex
+        assert_eq!(parse("https://nexus.corp/gradle-dists/gradle-8.10.2-bin.zip"), Some(("8.10.2".to_string(), tags(&["bin"]))));
+        assert_eq!(parse("gradle-8.6-rc-1-bin.zip"), Some(("8.6.0-rc.1".to_string(), tags(&["bin", "rc"]))));
+        assert_eq!(parse("gradle-8.7-milestone-2-all.zip"), Some(("8.7.0-milestone.2".to_string(), tags(&["all", "milestone"]))));
+        assert_eq!(
+            parse("https://services.gradle.org/distributions-snapshots/gradle-8.8-20240101001234+0000-bin.zip"),
+            Some(("8.8.0-nightly.20240101001234".to_string(), tags(&["bin", "nightly"])))
+        );
+        assert_eq!(parse("gradle-8.5-src.zip"), None);
+    }
+
     #[test]
     fn test_gradle_wrapper_properties() {
         let text = "\
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use indicatif::ProgressBar;
+use regex::Regex;
+
+use crate::config::GgConfig;
+use crate::executor::AppInput;
+use crate::executors::java_toolchains::{clean_specs, prep_jdks};
+
+/// Build output and caches, not build scripts
+const SKIP_DIRS: &[&str] = &[
+    "build",
+    ".gradle",
+    ".git",
+    ".cache",
+    "node_modules",
+    "target",
+];
+
+const BUILD_FILES: &[&str] = &[
+    "settings.gradle",
+    "settings.gradle.kts",
+    "build.gradle",
+    "build.gradle.kts",
+];
+
+/// Deep enough for `subprojects/libs/core/build.gradle.kts`
+const MAX_DEPTH: usize = 4;
+
+/// `JavaLanguageVersion.of(21)`, and Kotlin's `jvmToolchain(17)`
+fn versions_in(script: &str) -> Vec<String> {
+    static TOOLCHAIN: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r#"(?:JavaLanguageVersion\.of\(\s*"?|jvmToolchain\s*\(?\s*)(\d+)"#).unwrap()
+    });
+    TOOLCHAIN
+        .captures_iter(script)
+        .map(|c| c[1].to_string())
+        .collect()
+}
+
+fn scan(dir: &Path, depth: usize, out: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if path.is_dir() {
+            if depth < MAX_DEPTH && !SKIP_DIRS.contains(&name.as_str()) {
+                scan(&path, depth + 1, out);
+            }
+        } else if BUILD_FILES.contains(&name.as_str()) {
+            if let Ok(script) = fs::read_to_string(&path) {
+                out.extend(versions_in(&script));
+            }
+        }
+    }
+}
+
+/// The toolchain versions to prepare: gg.toml `[java] toolchains` when set
+/// (empty turns this off), else what the build scripts under `dir` declare
+fn toolchain_versions(dir: &Path, configured: Option<&[String]>) -> Vec<String> {
+    let mut versions = match configured {
+        Some(configured) => clean_specs(configured),
+        // Gradle run outside a build (gg gradle init): nothing to read
+        None if !BUILD_FILES.iter().any(|f| dir.join(f).exists()) => vec![],
+        None => {
+            let mut versions = vec![];
+            scan(dir, 0, &mut versions);
+            versions
+        }
+    };
+    versions.sort();
+    versions.dedup();
+    versions
+}
+
+/// Downloads the JDKs the build's toolchains need and points Gradle at them,
+/// so it doesn't go provisioning its own through foojay. Auto-download stays
+/// on if one of them couldn't be had, Gradle can still try for that one.
+pub async fn prep_toolchains(input: &AppInput, pb: &ProgressBar) -> HashMap<String, String> {
+    let config = GgConfig::loaded();
+    // The build is where gg.toml is, not wherever in it gg was started
+    let root = config.root.as_deref().unwrap_or(Path::new("."));
+    let versions = toolchain_versions(root, config.java.toolchains.as_deref());
+    let (jdks, all) = prep_jdks(&versions, input, pb).await;
+
+    let mut env = HashMap::new();
+    if jdks.is_empty() {
+        return env;
+    }
+    let homes = jdks
+        .iter()
+        .map(|jdk| jdk.home.to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    // ORG_GRADLE_PROJECT_<name> is how Gradle takes properties from the env
+    env.insert(
+        "ORG_GRADLE_PROJECT_org.gradle.java.installations.paths".to_string(),
+        homes.join(","),
+    );
+    if all {
+        env.insert(
+            "ORG_GRADLE_PROJECT_org.gradle.java.installations.auto-download".to_string(),
+            "false".to_string(),
+        );
+    }
+    env
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_versions_in() {
+        let groovy = "java {\n    toolchain {\n        languageVersion = JavaLanguageVersion.of(21)\n    }\n}";
+        assert_eq!(versions_in(groovy), vec!["21"]);
+        let kts = "java.toolchain.languageVersion.set(JavaLanguageVersion.of(\"17\"))\nkotlin { jvmToolchain(11) }";
+        assert_eq!(versions_in(kts), vec!["17", "11"]);
+        assert_eq!(versions_in("kotlin { jvmToolchain 8 }"), vec!["8"]);
+        assert!(versions_in("sourceCompatibility = JavaVersion.VERSION_17").is_empty());
+    }
+
+    #[test]
+    fn test_toolchain_versions() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("app")).unwrap();
+        fs::create_dir_all(root.join("build/tmp")).unwrap();
+        fs::write(root.join("build.gradle.kts"), "kotlin { jvmToolchain(21) }").unwrap();
+        fs::write(
+            root.join("app/build.gradle"),
+            "java { toolchain { languageVersion = JavaLanguageVersion.of(17) } }",
+        )
+        .unwrap();
+        fs::write(
+            root.join("build/tmp/build.gradle"),
+            "kotlin { jvmToolchain(8) }",
+        )
+        .unwrap();
+        // Settings plugins set toolchains too
+        fs::write(
+            root.join("settings.gradle.kts"),
+            "gradle.allprojects { kotlin { jvmToolchain(11) } }",
+        )
+        .unwrap();
+
+        assert_eq!(toolchain_versions(root, None), vec!["11", "17", "21"]);
+        assert_eq!(
+            toolchain_versions(root, Some(&["21-graalvm".to_string(), "11".to_string()])),
+            vec!["11", "21-graalvm"]
+        );
+        assert!(toolchain_versions(root, Some(&[])).is_empty());
+        assert!(toolchain_versions(&root.join("build"), None).is_empty());
+    }
+}
@@ -39,7 +39,9 @@ fn platform_string(target: &crate::target::Target) -> Option<String> {
 
 /// Fetch the latest version from a host's channel file, e.g. `0.2.77`.
 async fn fetch_version(base: &str) -> Option<String> {
-    let res = reqwest::get(format!("{base}/{CHANNEL}")).await.ok()?;
+    let res = reqwest::get(crate::mirror::mirrored(&format!("{base}/{CHANNEL}")))
+        .await
+        .ok()?;
     // reqwest only errors on transport failures, so a 4xx/5xx still arrives as
     // Ok. Without this, a digit-leading error body (e.g. "404 Not Found") would
     // pass the guard below, be taken as the version, and defeat the fallback.
@@ -155,13 +157,13 @@ impl Executor for Grok {
         vec![".".to_string()]
     }
 
-    fn post_prep(&self, cache_path: &str) {
+    fn post_prep(&self, cache_path: &str) -> Result<(), String> {
         // The binary downloads under its versioned URL basename
         // (`grok-<version>-<platform>[.exe]`); rename it to a stable `grok[.exe]`
         // so get_bins can resolve it with an exact match across runs.
         let cache = std::path::Path::new(cache_path);
         let Ok(entries) = std::fs::read_dir(cache) else {
-            return;
+            return Ok(());
         };
         for entry in entries.flatten() {
             let file_name = entry.file_name();
@@ -177,7 +179,7 @@ impl Executor for Grok {
             let dest = cache.join(canonical);
             if let Err(e) = std::fs::rename(entry.path(), &dest) {
                 warn!("grok: failed to rename {name} to {canonical}: {e}");
-                return;
+                return Ok(());
             }
             #[cfg(unix)]
             {
@@ -187,8 +189,9 @@ impl Executor for Grok {
                     warn!("grok: failed to set executable permission: {e}");
                 }
             }
-            return;
+            return Ok(());
         }
+        Ok(())
     }
 }
 
@@ -234,7 +237,7 @@ mod tests {
         std::fs::write(dir.path().join("grok-0.2.77-linux-x86_64"), b"binary").unwrap();
         std::fs::write(dir.path().join("gg-meta.json"), b"{}").unwrap();
 
-        test_grok().post_prep(dir.path().to_str().unwrap());
+        test_grok().post_prep(dir.path().to_str().unwrap()).unwrap();
 
         assert!(dir.path().join("grok").exists(), "renamed to canonical name");
         assert!(
@@ -249,7 +252,7 @@ mod tests {
         let dir = tempfile::tempdir().unwrap();
         std::fs::write(dir.path().join("grok-0.2.77-windows-x86_64.exe"), b"binary").unwrap();
 
-        test_grok().post_prep(dir.path().to_str().unwrap());
+        test_grok().post_prep(dir.path().to_str().unwrap()).unwrap();
 
         assert!(dir.path().join("grok.exe").exists(), "keeps .exe suffix");
     }
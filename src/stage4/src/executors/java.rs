@@ -1,14 +1,19 @@
 use std::collections::{HashMap, HashSet};
+use std::env;
 use std::fs;
 use std::future::Future;
 use std::pin::Pin;
 
+use log::warn;
 use semver::VersionReq;
 use serde::Deserialize;
 
+use crate::config::GgConfig;
 use crate::executor::{AppInput, AppPath, BinPattern, Download, ExecutorCmd};
 use crate::executors::gradle_properties::GradleAndWrapperProperties;
-use crate::executors::java_distributions::{JavaDistributions, FALLBACK_DISTRIBUTION};
+use crate::executors::java_distributions::{
+    requested_major, JavaDistributions, FALLBACK_DISTRIBUTION,
+};
 use crate::target::Os;
 use crate::Executor;
 
@@ -30,6 +35,75 @@ fn has_matching_version(downloads: &[Download], version_req: &VersionReq) -> boo
     })
 }
 
+/// An SDKMAN identifier like `17.0.9-tem` or `17.0.9.fx-librca`: the
+/// version, the distribution its vendor suffix names, and `fx`/`ea` tags.
+/// Plain versions from .java-version come out as just the version.
+#[derive(Debug, PartialEq)]
+struct SdkmanIdentifier {
+    version: String,
+    distribution: Option<&'static str>,
+    tags: Vec<String>,
+}
+
+fn parse_sdkman_identifier(identifier: &str) -> SdkmanIdentifier {
+    let identifier = identifier.trim();
+    let (version, suffix) = match identifier.rsplit_once('-') {
+        Some((version, suffix)) => (version, Some(suffix)),
+        None => (identifier, None),
+    };
+    let distribution = suffix
+        .and_then(JavaDistributions::get_by_name)
+        .map(|dist| dist.name);
+
+    let mut parts = version.split('.').peekable();
+    let mut numbers = vec![];
+    while let Some(part) = parts.next_if(|p| p.chars().all(|c| c.is_ascii_digit())) {
+        numbers.push(part);
+    }
+    // 17.0.9.fx-librca, 22.ea.20-open, 21-ea
+    let tags = parts
+        .chain(suffix.filter(|_| distribution.is_none()))
+        .filter(|p| *p == "fx" || *p == "ea")
+        .map(String::from)
+        .collect();
+    if let Some(suffix) = suffix.filter(|s| distribution.is_none() && *s != "fx" && *s != "ea") {
+        warn!(
+            "Java vendor '{}' is not a distribution gg knows, using the default",
+            suffix
+        );
+    }
+
+    SdkmanIdentifier {
+        version: numbers.join("."),
+        distribution,
+        tags,
+    }
+}
+
+/// `GG_JAVA_DISTRIBUTION`, else `[java] distribution` in gg.toml
+fn configured_distribution() -> Option<&'static str> {
+    let name = env::var("GG_JAVA_DISTRIBUTION")
+        .ok()
+        .or_else(|| GgConfig::loaded().java.distribution.clone())?;
+    match JavaDistributions::get_by_name(name.trim()) {
+        Some(dist) => Some(dist.name),
+        None => {
+            warn!(
+                "'{}' is not a Java distribution gg knows, using the default",
+                name
+            );
+            None
+        }
+    }
+}
+
+const JAVA_VERSION_FILE: &str = ".java-version";
+const SDKMANRC_FILE: &str = ".sdkmanrc";
+
+/// Version files in the current dir, in the order they count. gradle.properties
+/// comes after them.
+pub const VERSION_FILES: &[&str] = &[JAVA_VERSION_FILE, SDKMANRC_FILE];
+
 fn get_jdk_version() -> Option<String> {
     get_jdk_version_from_path(".")
 }
@@ -37,7 +111,7 @@ fn get_jdk_version() -> Option<String> {
 fn get_jdk_version_from_path(base_path: &str) -> Option<String> {
     use std::path::Path;
 
-    let java_version_path = Path::new(base_path).join(".java-version");
+    let java_version_path = Path::new(base_path).join(JAVA_VERSION_FILE);
     if let Ok(content) = fs::read_to_string(&java_version_path) {
         let version = content.trim();
         if !version.is_empty() {
@@ -45,7 +119,7 @@ fn get_jdk_version_from_path(base_path: &str) -> Option<String> {
         }
     }
 
-    let sdkmanrc_path = Path::new(base_path).join(".sdkmanrc");
+    let sdkmanrc_path = Path::new(base_path).join(SDKMANRC_FILE);
     if let Ok(content) = fs::read_to_string(&sdkmanrc_path) {
         if let Ok(sdkmanrc) = serde_java_properties::from_str::<SdkmanRc>(&content) {
             if let Some(java_version) = sdkmanrc.java {
@@ -58,6 +132,21 @@ fn get_jdk_version_from_path(base_path: &str) -> Option<String> {
 }
 
 impl Java {
+    /// Fills in the distribution when the command didn't pick one: from the
+    /// .sdkmanrc suffix when the version comes from there too, else gg.toml.
+    pub fn new(mut executor_cmd: ExecutorCmd) -> Self {
+        if executor_cmd.version.is_none() && executor_cmd.distribution.is_none() {
+            if let Some(identifier) = get_jdk_version().map(|v| parse_sdkman_identifier(&v)) {
+                executor_cmd.distribution = identifier.distribution.map(String::from);
+                executor_cmd.include_tags.extend(identifier.tags);
+            }
+        }
+        if executor_cmd.distribution.is_none() {
+            executor_cmd.distribution = configured_distribution().map(String::from);
+        }
+        Self { executor_cmd }
+    }
+
     fn get_distribution(&self) -> crate::executors::java_distributions::DistributionConfig {
         if let Some(ref dist_name) = self.executor_cmd.distribution {
             JavaDistributions::get_by_name(dist_name)
@@ -85,7 +174,8 @@ impl Executor for Java {
 
     fn get_version_req(&self) -> Option<VersionReq> {
         if let Some(jdk_version) = get_jdk_version() {
-            if let Ok(version) = VersionReq::parse(jdk_version.as_str()) {
+            let identifier = parse_sdkman_identifier(&jdk_version);
+            if let Ok(version) = VersionReq::parse(&identifier.version) {
                 return Some(version);
             }
         }
@@ -99,7 +189,14 @@ impl Executor for Java {
     ) -> Pin<Box<dyn Future<Output = Vec<Download>> + 'a>> {
         Box::pin(async move {
             let distribution = self.get_distribution();
-            let downloads = (distribution.handler)(&input.target).await;
+            // Same precedence as executor.rs, and get_url_matches only knows
+            // executor_cmd.version - so .sdkmanrc and gradle.properties land here.
+            let version_req = match &self.executor_cmd.version {
+                Some(version_req) => Some(version_req.to_version_req()),
+                None => self.get_version_req(),
+            };
+            let major = version_req.as_ref().and_then(requested_major);
+            let downloads = (distribution.handler)(&input.target, major).await;
 
             // An explicit -azul/-tem is the user's call, so never second-guess it.
             if self.executor_cmd.distribution.is_some() && self.dropped_tag().is_none() {
@@ -111,13 +208,6 @@ impl Executor for Java {
             // nothing.
             let matches = (self as &dyn Executor).get_url_matches(&downloads, input);
 
-            // Same precedence as executor.rs, and get_url_matches only knows
-            // executor_cmd.version - so .sdkmanrc and gradle.properties land here.
-            let version_req = match &self.executor_cmd.version {
-                Some(version_req) => Some(version_req.to_version_req()),
-                None => self.get_version_req(),
-            };
-
             let needs_fallback = match &version_req {
                 Some(version_req) => !has_matching_version(&matches, version_req),
                 None => matches.is_empty(),
@@ -125,7 +215,7 @@ impl Executor for Java {
 
             if needs_fallback {
                 if let Some(fallback) = JavaDistributions::get_by_name(FALLBACK_DISTRIBUTION) {
-                    let fallback_downloads = (fallback.handler)(&input.target).await;
+                    let fallback_downloads = (fallback.handler)(&input.target, major).await;
                     // Empty means Azul failed too - keep what we have instead
                     if !fallback_downloads.is_empty() {
                         return fallback_downloads;
@@ -377,4 +467,46 @@ mod tests {
         let version = get_jdk_version_from_path(temp_dir.path().to_str().unwrap());
         assert_eq!(version, None);
     }
+
+    #[test]
+    fn test_parse_sdkman_identifier() {
+        let id = parse_sdkman_identifier("17.0.9-tem");
+        assert_eq!(id.version, "17.0.9");
+        assert_eq!(id.distribution, Some("temurin"));
+        assert!(id.tags.is_empty());
+
+        assert_eq!(
+            parse_sdkman_identifier("11.0.16-zulu").distribution,
+            Some("azul")
+        );
+        assert_eq!(
+            parse_sdkman_identifier("21.0.1-amzn").distribution,
+            Some("corretto")
+        );
+        assert_eq!(
+            parse_sdkman_identifier("21.0.1-graalce").distribution,
+            Some("graalvm")
+        );
+
+        let fx = parse_sdkman_identifier("17.0.9.fx-librca");
+        assert_eq!(fx.version, "17.0.9");
+        assert_eq!(fx.distribution, Some("liberica"));
+        assert_eq!(fx.tags, vec!["fx"]);
+
+        // Vendors gg doesn't have keep their version, with the default distribution
+        let open = parse_sdkman_identifier("22.ea.20-open");
+        assert_eq!(open.version, "22");
+        assert_eq!(open.distribution, None);
+        assert_eq!(open.tags, vec!["ea"]);
+
+        assert_eq!(
+            parse_sdkman_identifier("17"),
+            SdkmanIdentifier {
+                version: "17".to_string(),
+                distribution: None,
+                tags: vec![],
+            }
+        );
+        assert_eq!(parse_sdkman_identifier("21-ea").tags, vec!["ea"]);
+    }
 }
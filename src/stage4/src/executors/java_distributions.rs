@@ -1,15 +1,19 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
 use std::pin::Pin;
 
+use semver::{Op, VersionReq};
 use serde::Deserialize;
 use serde::Serialize;
 
 use crate::executor::{Download, GgVersion};
 use crate::fetch::fetch_json;
+use crate::github_utils::{create_github_client, record_github_error};
 use crate::target::{Arch, Os, Target, Variant};
 
-type DistributionHandler = fn(&Target) -> Pin<Box<dyn Future<Output = Vec<Download>> + Send>>;
+/// The target, and the Java major asked for when the requirement names one
+type DistributionHandler =
+    fn(&Target, Option<u64>) -> Pin<Box<dyn Future<Output = Vec<Download>> + Send>>;
 
 #[derive(Debug, Clone)]
 pub struct DistributionConfig {
@@ -38,10 +42,46 @@ impl JavaDistributions {
             },
             DistributionConfig {
                 name: "azul",
-                short_name: "azul",
+                short_name: "zulu",
                 default_tags: vec!["jdk", "ga"],
                 handler: get_azul_downloads,
             },
+            DistributionConfig {
+                name: "corretto",
+                short_name: "amzn",
+                default_tags: vec!["jdk", "ga"],
+                handler: get_corretto_downloads,
+            },
+            DistributionConfig {
+                name: "liberica",
+                short_name: "librca",
+                default_tags: vec!["jdk", "ga"],
+                handler: get_liberica_downloads,
+            },
+            DistributionConfig {
+                name: "microsoft",
+                short_name: "ms",
+                default_tags: vec!["jdk", "ga"],
+                handler: get_microsoft_downloads,
+            },
+            DistributionConfig {
+                name: "sapmachine",
+                short_name: "sapmchn",
+                default_tags: vec!["jdk", "ga"],
+                handler: get_sapmachine_downloads,
+            },
+            DistributionConfig {
+                name: "graalvm",
+                short_name: "graalce",
+                default_tags: vec!["jdk", "ga"],
+                handler: get_graalvm_downloads,
+            },
+            DistributionConfig {
+                name: "semeru",
+                short_name: "sem",
+                default_tags: vec!["jdk", "ga"],
+                handler: get_semeru_downloads,
+            },
         ]
     }
 
@@ -86,7 +126,10 @@ struct AzulBundle {
     pub url: String,
 }
 
-fn get_azul_downloads(target: &Target) -> Pin<Box<dyn Future<Output = Vec<Download>> + Send>> {
+fn get_azul_downloads(
+    target: &Target,
+    _major: Option<u64>,
+) -> Pin<Box<dyn Future<Output = Vec<Download>> + Send>> {
     let target = *target;
     Box::pin(async move {
         // Azul backs the default now, not just an explicit -azul, so a bad day at
@@ -207,12 +250,14 @@ struct TemurinVersionData {
 const TEMURIN_FALLBACK_VERSIONS: [u32; 5] = [8, 11, 17, 21, 25];
 
 async fn get_temurin_available_releases() -> Option<TemurinAvailableReleases> {
-    let text = reqwest::get("https://api.adoptium.net/v3/info/available_releases")
-        .await
-        .ok()?
-        .text()
-        .await
-        .ok()?;
+    let text = reqwest::get(crate::mirror::mirrored(
+        "https://api.adoptium.net/v3/info/available_releases",
+    ))
+    .await
+    .ok()?
+    .text()
+    .await
+    .ok()?;
     serde_json::from_str(&text).ok()
 }
 
@@ -224,7 +269,7 @@ async fn get_temurin_version_downloads(target: Target, version: u32, lts: bool)
 
     let mut downloads = Vec::new();
 
-    if let Ok(response) = reqwest::get(&url).await {
+    if let Ok(response) = reqwest::get(crate::mirror::mirrored(&url)).await {
         if let Ok(text) = response.text().await {
             if let Ok(releases) = serde_json::from_str::<Vec<TemurinRelease>>(&text) {
                 for release in releases {
@@ -307,7 +352,10 @@ async fn get_temurin_version_downloads(target: Target, version: u32, lts: bool)
     downloads
 }
 
-fn get_temurin_downloads(target: &Target) -> Pin<Box<dyn Future<Output = Vec<Download>> + Send>> {
+fn get_temurin_downloads(
+    target: &Target,
+    _major: Option<u64>,
+) -> Pin<Box<dyn Future<Output = Vec<Download>> + Send>> {
     let target = *target;
     Box::pin(async move {
         let (versions, lts_versions) = match get_temurin_available_releases().await {
@@ -334,3 +382,764 @@ fn get_temurin_downloads(target: &Target) -> Pin<Box<dyn Future<Output = Vec<Dow
         .collect()
     })
 }
+
+/// 8, 11, then every other September: 17, 21, 25...
+fn is_lts(major: u64) -> bool {
+    major == 8 || major == 11 || (major >= 17 && (major - 17).is_multiple_of(4))
+}
+
+/// Vendors tack their own build numbers onto the Java version (Corretto's
+/// `21.0.1.12.1`), and 8 comes as `8u392b08`, `1.8.0_392` or Corretto's
+/// `8.392.08.1`. All of those 8s are `8.0.392`, the rest is the first three
+/// numbers; leading zeros would trip semver.
+fn java_version(s: &str) -> Option<GgVersion> {
+    let s = s.replacen('u', ".0.", 1);
+    let mut parts = s
+        .split(|c: char| !c.is_ascii_digit())
+        .take_while(|p| !p.is_empty())
+        .map(|p| p.parse::<u64>())
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+    if parts.starts_with(&[1, 8]) {
+        parts.remove(0);
+    }
+    if parts.len() > 1 && parts[0] == 8 && parts[1] != 0 {
+        parts.insert(1, 0);
+    }
+    let parts = parts
+        .iter()
+        .take(3)
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>();
+    GgVersion::new(&parts.join("."))
+}
+
+fn major_of(version: &Option<GgVersion>) -> Option<u64> {
+    version.as_ref().map(|v| v.to_version().major)
+}
+
+/// jdk/jre, ga/ea and lts: what every vendor below can tell us
+fn base_tags(kind: &str, ga: bool, lts: bool) -> HashSet<String> {
+    let mut tags = HashSet::from([kind.to_string(), if ga { "ga" } else { "ea" }.to_string()]);
+    if lts {
+        tags.insert("lts".to_string());
+    }
+    tags
+}
+
+/// Only archives gg can unpack, not installers
+fn archive_ext(os: Os) -> &'static str {
+    match os {
+        Os::Windows => "zip",
+        _ => "tar.gz",
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct CorrettoFile {
+    pub resource: String,
+}
+
+/// os -> arch -> jdk/jre -> major -> ext -> file. Only the latest of each major.
+type CorrettoIndex = HashMap<
+    String,
+    HashMap<String, HashMap<String, HashMap<String, HashMap<String, CorrettoFile>>>>,
+>;
+
+fn corretto_downloads(index: &CorrettoIndex) -> Vec<Download> {
+    let mut downloads = vec![];
+    for (os_name, arches) in index {
+        let (os, variant) = match os_name.as_str() {
+            "linux" => (Os::Linux, None),
+            "alpine" | "alpine_linux" => (Os::Linux, Some(Variant::Musl)),
+            "macos" => (Os::Mac, None),
+            "windows" => (Os::Windows, None),
+            _ => continue,
+        };
+        for (arch_name, kinds) in arches {
+            let arch = match arch_name.as_str() {
+                "x64" => Arch::X86_64,
+                "aarch64" => Arch::Arm64,
+                "arm" => Arch::Armv7,
+                _ => continue,
+            };
+            for (kind, majors) in kinds {
+                for files in majors.values() {
+                    let Some(file) = files.get(archive_ext(os)) else {
+                        continue;
+                    };
+                    // /downloads/resources/21.0.1.12.1/amazon-corretto-...
+                    let version = file
+                        .resource
+                        .split('/')
+                        .skip_while(|p| *p != "resources")
+                        .nth(1)
+                        .and_then(java_version);
+                    let lts = major_of(&version).is_some_and(is_lts);
+                    downloads.push(Download {
+                        download_url: format!("https://corretto.aws{}", file.resource),
+                        version,
+                        os: Some(os),
+                        arch: Some(arch),
+                        variant,
+                        tags: base_tags(kind, true, lts),
+                    });
+                }
+            }
+        }
+    }
+    downloads
+}
+
+fn get_corretto_downloads(
+    _target: &Target,
+    _major: Option<u64>,
+) -> Pin<Box<dyn Future<Output = Vec<Download>> + Send>> {
+    Box::pin(async move {
+        match fetch_json::<CorrettoIndex>(
+            "https://corretto.github.io/corretto-downloads/latest_links/indexmap_with_checksum.json",
+        )
+        .await
+        {
+            Some(index) => corretto_downloads(&index),
+            None => vec![],
+        }
+    })
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LibericaRelease {
+    pub bundle_type: String,
+    pub os: String,
+    pub architecture: String,
+    pub bitness: u32,
+    #[serde(rename = "FX")]
+    pub fx: bool,
+    #[serde(rename = "LTS")]
+    pub lts: bool,
+    #[serde(rename = "GA")]
+    pub ga: bool,
+    pub version: String,
+    pub download_url: String,
+}
+
+fn liberica_download(release: LibericaRelease) -> Option<Download> {
+    // jdk, jre, jdk-full (with JavaFX), jdk-lite, jre-full...
+    let mut parts = release.bundle_type.split('-');
+    let kind = parts.next()?;
+    if kind != "jdk" && kind != "jre" {
+        return None;
+    }
+    let mut tags = base_tags(kind, release.ga, release.lts);
+    for part in parts {
+        tags.insert(if part == "full" { "fx" } else { part }.to_string());
+    }
+    if release.fx {
+        tags.insert("fx".to_string());
+    }
+    let (os, variant) = match release.os.as_str() {
+        "linux" => (Os::Linux, None),
+        "linux-musl" => (Os::Linux, Some(Variant::Musl)),
+        "macos" => (Os::Mac, None),
+        "windows" => (Os::Windows, None),
+        _ => return None,
+    };
+    let arch = match (release.architecture.as_str(), release.bitness) {
+        ("x86", 64) => Arch::X86_64,
+        ("arm", 64) => Arch::Arm64,
+        ("arm", 32) => Arch::Armv7,
+        _ => return None,
+    };
+    Some(Download {
+        download_url: release.download_url,
+        version: java_version(&release.version),
+        os: Some(os),
+        arch: Some(arch),
+        variant,
+        tags,
+    })
+}
+
+fn get_liberica_downloads(
+    target: &Target,
+    _major: Option<u64>,
+) -> Pin<Box<dyn Future<Output = Vec<Download>> + Send>> {
+    let target = *target;
+    Box::pin(async move {
+        // Every Liberica build ever is a lot of JSON, so let BellSoft filter
+        let os = match (target.os, target.variant) {
+            (Os::Linux, Some(Variant::Musl)) => "&os=linux-musl",
+            (Os::Linux, _) => "&os=linux",
+            (Os::Mac, _) => "&os=macos",
+            (Os::Windows, _) => "&os=windows",
+            _ => "",
+        };
+        let arch = match target.arch {
+            Arch::X86_64 => "&arch=x86&bitness=64",
+            Arch::Arm64 => "&arch=arm&bitness=64",
+            Arch::Armv7 => "&arch=arm&bitness=32",
+            _ => "",
+        };
+        let url = format!(
+            "https://api.bell-sw.com/v1/liberica/releases?installation-type=archive&package-type={}{}{}",
+            archive_ext(target.os),
+            os,
+            arch
+        );
+        fetch_json::<Vec<LibericaRelease>>(&url)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(liberica_download)
+            .collect()
+    })
+}
+
+/// Microsoft publishes no API for its builds. The list it keeps for
+/// actions/setup-java is the one machine readable index there is.
+const MICROSOFT_VERSIONS: &str = "https://raw.githubusercontent.com/actions/setup-java/main/src/distributions/microsoft/microsoft-openjdk-versions.json";
+
+#[derive(Debug, Clone, Deserialize)]
+struct MicrosoftRelease {
+    pub version: String,
+    pub stable: bool,
+    pub files: Vec<MicrosoftFile>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct MicrosoftFile {
+    pub filename: String,
+    pub arch: String,
+    pub platform: String,
+    pub download_url: String,
+}
+
+fn microsoft_downloads(releases: Vec<MicrosoftRelease>) -> Vec<Download> {
+    let mut downloads = vec![];
+    for release in releases {
+        let version = java_version(&release.version);
+        let lts = major_of(&version).is_some_and(is_lts);
+        for file in release.files {
+            let os = match file.platform.as_str() {
+                "linux" => Os::Linux,
+                "darwin" | "macos" => Os::Mac,
+                "win32" | "windows" => Os::Windows,
+                _ => continue,
+            };
+            if !file.filename.ends_with(&format!(".{}", archive_ext(os))) {
+                continue;
+            }
+            let arch = match file.arch.as_str() {
+                "x64" => Arch::X86_64,
+                "aarch64" | "arm64" => Arch::Arm64,
+                _ => continue,
+            };
+            let kind = if file.filename.contains("-jre-") {
+                "jre"
+            } else {
+                "jdk"
+            };
+            downloads.push(Download {
+                download_url: file.download_url,
+                version: version.clone(),
+                os: Some(os),
+                arch: Some(arch),
+                variant: file.filename.contains("alpine").then_some(Variant::Musl),
+                tags: base_tags(kind, release.stable, lts),
+            });
+        }
+    }
+    downloads
+}
+
+fn get_microsoft_downloads(
+    _target: &Target,
+    _major: Option<u64>,
+) -> Pin<Box<dyn Future<Output = Vec<Download>> + Send>> {
+    Box::pin(async move {
+        fetch_json::<Vec<MicrosoftRelease>>(MICROSOFT_VERSIONS)
+            .await
+            .map(microsoft_downloads)
+            .unwrap_or_default()
+    })
+}
+
+struct ReleaseAsset {
+    name: String,
+    url: String,
+    prerelease: bool,
+}
+
+/// Every release's assets, page by page. GraalVM CE and Semeru don't have an
+/// index beyond their GitHub releases.
+async fn github_release_assets(owner: &str, repo: &str) -> Vec<ReleaseAsset> {
+    let Ok(octocrab) = create_github_client() else {
+        return vec![];
+    };
+    let mut assets = vec![];
+    let mut page: u32 = 1;
+    loop {
+        let releases = match octocrab
+            .repos(owner, repo)
+            .releases()
+            .list()
+            .page(page)
+            .per_page(100)
+            .send()
+            .await
+        {
+            Ok(releases) => releases,
+            Err(err) => {
+                record_github_error(&format!("{}/{}", owner, repo), &err);
+                break;
+            }
+        };
+        for release in releases.items {
+            let prerelease = release.prerelease;
+            assets.extend(release.assets.into_iter().map(|asset| ReleaseAsset {
+                name: asset.name,
+                url: asset.browser_download_url.to_string(),
+                prerelease,
+            }));
+        }
+        if releases.next.is_none() {
+            break;
+        }
+        page += 1;
+    }
+    assets
+}
+
+/// `<prefix>jdk-21.0.1_linux-x64_bin.tar.gz`, the naming SapMachine and GraalVM
+/// CE share. The platform can carry `-musl`, older SapMachines say `osx`.
+fn bin_asset_download(asset: &ReleaseAsset, prefix: &str) -> Option<Download> {
+    let rest = asset.name.strip_prefix(prefix)?;
+    let (rest, ext) = rest
+        .strip_suffix("_bin.tar.gz")
+        .map(|r| (r, "tar.gz"))
+        .or_else(|| rest.strip_suffix("_bin.zip").map(|r| (r, "zip")))?;
+    let (kind_version, platform) = rest.split_once('_')?;
+    let (kind, version) = kind_version.split_once('-')?;
+    if kind != "jdk" && kind != "jre" {
+        return None;
+    }
+    let mut platform = platform.split('-');
+    let os = match platform.next()? {
+        "linux" => Os::Linux,
+        "macos" | "osx" => Os::Mac,
+        "windows" => Os::Windows,
+        _ => return None,
+    };
+    if ext != archive_ext(os) {
+        return None;
+    }
+    let arch = match platform.next()? {
+        "x64" => Arch::X86_64,
+        "aarch64" => Arch::Arm64,
+        _ => return None,
+    };
+    let variant = (platform.next() == Some("musl")).then_some(Variant::Musl);
+    let version = java_version(version);
+    let ga = !asset.prerelease && !kind_version.contains("ea");
+    let lts = major_of(&version).is_some_and(is_lts);
+    Some(Download {
+        download_url: asset.url.clone(),
+        version,
+        os: Some(os),
+        arch: Some(arch),
+        variant,
+        tags: base_tags(kind, ga, lts),
+    })
+}
+
+/// Every SapMachine release, as the sap.github.io download page has them
+const SAPMACHINE_RELEASES: &str =
+    "https://sap.github.io/SapMachine/assets/data/sapmachine-releases-all.json";
+
+/// The archive links anywhere in SapMachine's release index. Which nesting
+/// they sit in doesn't matter, their file names say everything.
+fn sapmachine_assets(index: &serde_json::Value, assets: &mut Vec<ReleaseAsset>) {
+    match index {
+        serde_json::Value::String(url) if url.starts_with("https://") => {
+            if let Some((_, name)) = url.rsplit_once('/') {
+                assets.push(ReleaseAsset {
+                    name: name.to_string(),
+                    url: url.clone(),
+                    prerelease: false,
+                });
+            }
+        }
+        serde_json::Value::Array(values) => {
+            values.iter().for_each(|v| sapmachine_assets(v, assets));
+        }
+        serde_json::Value::Object(values) => {
+            values.values().for_each(|v| sapmachine_assets(v, assets));
+        }
+        _ => {}
+    }
+}
+
+fn sapmachine_downloads(index: &serde_json::Value) -> Vec<Download> {
+    let mut assets = vec![];
+    sapmachine_assets(index, &mut assets);
+    assets.sort_by(|a, b| a.url.cmp(&b.url));
+    assets.dedup_by(|a, b| a.url == b.url);
+    assets
+        .iter()
+        .filter_map(|asset| bin_asset_download(asset, "sapmachine-"))
+        .collect()
+}
+
+fn get_sapmachine_downloads(
+    _target: &Target,
+    _major: Option<u64>,
+) -> Pin<Box<dyn Future<Output = Vec<Download>> + Send>> {
+    Box::pin(async move {
+        fetch_json::<serde_json::Value>(SAPMACHINE_RELEASES)
+            .await
+            .map(|index| sapmachine_downloads(&index))
+            .unwrap_or_default()
+    })
+}
+
+fn get_graalvm_downloads(
+    _target: &Target,
+    _major: Option<u64>,
+) -> Pin<Box<dyn Future<Output = Vec<Download>> + Send>> {
+    Box::pin(async move {
+        // Only the jdk-* releases; the older vm-22.3 ones are versioned by
+        // GraalVM, not Java
+        github_release_assets("graalvm", "graalvm-ce-builds")
+            .await
+            .iter()
+            .filter_map(|asset| bin_asset_download(asset, "graalvm-community-"))
+            .collect()
+    })
+}
+
+/// `ibm-semeru-open-jdk_x64_linux_21.0.1_12_openj9-0.42.0.tar.gz`, and
+/// `..._8u392b08_openj9-...` for 8
+fn semeru_download(asset: &ReleaseAsset) -> Option<Download> {
+    let rest = asset.name.strip_prefix("ibm-semeru-open-")?;
+    let (rest, ext) = rest
+        .strip_suffix(".tar.gz")
+        .map(|r| (r, "tar.gz"))
+        .or_else(|| rest.strip_suffix(".zip").map(|r| (r, "zip")))?;
+    let mut parts = rest.split('_');
+    let kind = parts.next()?;
+    if kind != "jdk" && kind != "jre" {
+        return None;
+    }
+    let arch = match parts.next()? {
+        "x64" => Arch::X86_64,
+        "aarch64" => Arch::Arm64,
+        _ => return None,
+    };
+    let os = match parts.next()? {
+        "linux" => Os::Linux,
+        "mac" => Os::Mac,
+        "windows" => Os::Windows,
+        _ => return None,
+    };
+    if ext != archive_ext(os) {
+        return None;
+    }
+    let version = java_version(parts.next()?);
+    let lts = major_of(&version).is_some_and(is_lts);
+    let mut tags = base_tags(kind, !asset.prerelease, lts);
+    tags.insert("openj9".to_string());
+    Some(Download {
+        download_url: asset.url.clone(),
+        version,
+        os: Some(os),
+        arch: Some(arch),
+        variant: None,
+        tags,
+    })
+}
+
+/// The major a requirement pins (`21`, `~21.0`, `^17`), None for open ranges
+/// like `>=17`
+pub fn requested_major(version_req: &VersionReq) -> Option<u64> {
+    match version_req.comparators.as_slice() {
+        [comparator] => match comparator.op {
+            Op::Exact | Op::Tilde | Op::Caret | Op::Wildcard => Some(comparator.major),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Semeru has one repo per feature release, `semeru21-binaries`
+fn semeru_repo(major: u64) -> String {
+    format!("semeru{}-binaries", major)
+}
+
+/// Only the asked for major's releases are read, every ibmruntimes repo would
+/// use up an unauthenticated rate limit. Without one, the newest feature
+/// release and LTS, as Adoptium knows them.
+async fn semeru_majors(major: Option<u64>) -> Vec<u64> {
+    if let Some(major) = major {
+        return vec![major];
+    }
+    let mut majors = match get_temurin_available_releases().await {
+        Some(available) => vec![
+            available.most_recent_feature_release as u64,
+            available.most_recent_lts as u64,
+        ],
+        None => TEMURIN_FALLBACK_VERSIONS
+            .iter()
+            .max()
+            .map(|m| *m as u64)
+            .into_iter()
+            .collect(),
+    };
+    majors.dedup();
+    majors
+}
+
+fn get_semeru_downloads(
+    _target: &Target,
+    major: Option<u64>,
+) -> Pin<Box<dyn Future<Output = Vec<Download>> + Send>> {
+    Box::pin(async move {
+        let repos = semeru_majors(major)
+            .await
+            .into_iter()
+            .map(semeru_repo)
+            .collect::<Vec<_>>();
+        futures_util::future::join_all(
+            repos
+                .iter()
+                .map(|repo| github_release_assets("ibmruntimes", repo)),
+        )
+        .await
+        .iter()
+        .flatten()
+        .filter_map(semeru_download)
+        .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(download: &Download) -> String {
+        download.version.as_ref().unwrap().to_string()
+    }
+
+    fn tags(download: &Download) -> Vec<&str> {
+        let mut tags = download.tags.iter().map(|t| t.as_str()).collect::<Vec<_>>();
+        tags.sort();
+        tags
+    }
+
+    fn asset(name: &str, prerelease: bool) -> ReleaseAsset {
+        ReleaseAsset {
+            name: name.to_string(),
+            url: format!("https://github.com/x/y/{}", name),
+            prerelease,
+        }
+    }
+
+    #[test]
+    fn test_java_version() {
+        assert_eq!(java_version("21.0.1.12.1").unwrap().to_string(), "21.0.1");
+        assert_eq!(java_version("8.392.08.1").unwrap().to_string(), "8.0.392");
+        assert_eq!(java_version("8u392b08").unwrap().to_string(), "8.0.392");
+        assert_eq!(java_version("1.8.0_392").unwrap().to_string(), "8.0.392");
+        assert_eq!(java_version("8").unwrap().to_string(), "8.0.0");
+        assert_eq!(java_version("17.0.9+11").unwrap().to_string(), "17.0.9");
+        assert_eq!(java_version("22").unwrap().to_string(), "22.0.0");
+        assert!(java_version("latest").is_none());
+        assert!(is_lts(8) && is_lts(11) && is_lts(21) && is_lts(25));
+        assert!(!is_lts(22) && !is_lts(9));
+    }
+
+    #[test]
+    fn test_corretto() {
+        let index: CorrettoIndex = serde_json::from_str(
+            r#"{
+                "linux": {"x64": {"jdk": {"21": {
+                    "tar.gz": {"resource": "/downloads/resources/21.0.1.12.1/amazon-corretto-21.0.1.12.1-linux-x64.tar.gz", "checksum": "x"},
+                    "rpm": {"resource": "/downloads/resources/21.0.1.12.1/java-21-amazon-corretto-devel-21.0.1.12-1.x86_64.rpm"}
+                }}}},
+                "windows": {"x86": {"jdk": {"8": {"zip": {"resource": "/downloads/resources/8.392.08.1/x.zip"}}}}}
+            }"#,
+        )
+        .unwrap();
+        let downloads = corretto_downloads(&index);
+        assert_eq!(downloads.len(), 1);
+        assert_eq!(
+            downloads[0].download_url,
+            "https://corretto.aws/downloads/resources/21.0.1.12.1/amazon-corretto-21.0.1.12.1-linux-x64.tar.gz"
+        );
+        assert_eq!(version(&downloads[0]), "21.0.1");
+        assert_eq!(downloads[0].arch, Some(Arch::X86_64));
+        assert_eq!(tags(&downloads[0]), vec!["ga", "jdk", "lts"]);
+    }
+
+    #[test]
+    fn test_liberica() {
+        let releases: Vec<LibericaRelease> = serde_json::from_str(
+            r#"[
+                {"bundleType": "jdk-full", "os": "linux-musl", "architecture": "x86", "bitness": 64,
+                 "FX": true, "LTS": true, "GA": true, "version": "21.0.1+12",
+                 "downloadUrl": "https://download.bell-sw.com/java/21.0.1+12/bellsoft-jdk21.0.1+12-linux-x64-musl-full.tar.gz"},
+                {"bundleType": "jre", "os": "macos", "architecture": "arm", "bitness": 64,
+                 "FX": false, "LTS": false, "GA": false, "version": "22-ea+20", "downloadUrl": "u"},
+                {"bundleType": "jdk", "os": "solaris", "architecture": "sparc", "bitness": 64,
+                 "FX": false, "LTS": true, "GA": true, "version": "8u392+9", "downloadUrl": "u"}
+            ]"#,
+        )
+        .unwrap();
+        let downloads = releases
+            .into_iter()
+            .filter_map(liberica_download)
+            .collect::<Vec<_>>();
+        assert_eq!(downloads.len(), 2);
+        assert_eq!(version(&downloads[0]), "21.0.1");
+        assert_eq!(downloads[0].variant, Some(Variant::Musl));
+        assert_eq!(tags(&downloads[0]), vec!["fx", "ga", "jdk", "lts"]);
+        assert_eq!(downloads[1].arch, Some(Arch::Arm64));
+        assert_eq!(tags(&downloads[1]), vec!["ea", "jre"]);
+    }
+
+    #[test]
+    fn test_microsoft() {
+        let releases: Vec<MicrosoftRelease> = serde_json::from_str(
+            r#"[
+                {"version": "21.0.1", "stable": true, "release_url": "https://aka.ms/download-jdk", "files": [
+                    {"filename": "microsoft-jdk-21.0.1-linux-aarch64.tar.gz", "arch": "aarch64", "platform": "linux",
+                     "download_url": "https://aka.ms/download-jdk/microsoft-jdk-21.0.1-linux-aarch64.tar.gz"},
+                    {"filename": "microsoft-jdk-21.0.1-windows-x64.msi", "arch": "x64", "platform": "win32",
+                     "download_url": "u"},
+                    {"filename": "microsoft-jdk-21.0.1-linux-x64.zip", "arch": "x64", "platform": "linux",
+                     "download_url": "u"}
+                ]},
+                {"version": "11.0.21", "stable": true, "files": [
+                    {"filename": "microsoft-jdk-11.0.21-windows-x64.zip", "arch": "x64", "platform": "win32",
+                     "download_url": "https://aka.ms/download-jdk/microsoft-jdk-11.0.21-windows-x64.zip"}
+                ]}
+            ]"#,
+        )
+        .unwrap();
+        let downloads = microsoft_downloads(releases);
+        assert_eq!(downloads.len(), 2);
+        assert_eq!(
+            downloads[0].download_url,
+            "https://aka.ms/download-jdk/microsoft-jdk-21.0.1-linux-aarch64.tar.gz"
+        );
+        assert_eq!(version(&downloads[0]), "21.0.1");
+        assert_eq!(downloads[0].arch, Some(Arch::Arm64));
+        assert_eq!(tags(&downloads[0]), vec!["ga", "jdk", "lts"]);
+        assert_eq!(downloads[1].os, Some(Os::Windows));
+        assert_eq!(version(&downloads[1]), "11.0.21");
+    }
+
+    #[test]
+    fn test_sapmachine_index() {
+        let index: serde_json::Value = serde_json::from_str(
+            r#"{"21": {"lts": "true", "updates": {"21.0.1": {"21.0.1+12": {
+                "release_url": "https://github.com/SAP/SapMachine/releases/tag/sapmachine-21.0.1",
+                "assets": {"jdk": {
+                    "linux-x64": {"tar.gz": {"name": "sapmachine-jdk-21.0.1_linux-x64_bin.tar.gz", "checksum": "x",
+                        "url": "https://github.com/SAP/SapMachine/releases/download/sapmachine-21.0.1/sapmachine-jdk-21.0.1_linux-x64_bin.tar.gz"}},
+                    "windows-x64": {"msi": {"url": "https://github.com/SAP/SapMachine/releases/download/sapmachine-21.0.1/sapmachine-jdk-21.0.1_windows-x64_bin.msi"}}
+                }}
+            }}}},
+            "8": [
+                "https://github.com/SAP/SapMachine/releases/download/sapmachine-21.0.1/sapmachine-jdk-21.0.1_linux-x64_bin.tar.gz",
+                "https://github.com/SAP/SapMachine/releases/download/sapmachine-22-ea/sapmachine-jdk-22-ea.25_macos-aarch64_bin.tar.gz"
+            ]}"#,
+        )
+        .unwrap();
+        let mut downloads = sapmachine_downloads(&index);
+        downloads.sort_by_key(version);
+        assert_eq!(downloads.len(), 2);
+        assert_eq!(version(&downloads[0]), "21.0.1");
+        assert_eq!(tags(&downloads[0]), vec!["ga", "jdk", "lts"]);
+        assert_eq!(version(&downloads[1]), "22.0.0");
+        assert_eq!(tags(&downloads[1]), vec!["ea", "jdk"]);
+    }
+
+    #[test]
+    fn test_bin_assets() {
+        let sap = bin_asset_download(
+            &asset("sapmachine-jre-21.0.1_linux-x64-musl_bin.tar.gz", false),
+            "sapmachine-",
+        )
+        .unwrap();
+        assert_eq!(version(&sap), "21.0.1");
+        assert_eq!(sap.os, Some(Os::Linux));
+        assert_eq!(sap.variant, Some(Variant::Musl));
+        assert_eq!(tags(&sap), vec!["ga", "jre", "lts"]);
+
+        let ea = bin_asset_download(
+            &asset("sapmachine-jdk-22-ea.25_macos-aarch64_bin.tar.gz", true),
+            "sapmachine-",
+        )
+        .unwrap();
+        assert_eq!(version(&ea), "22.0.0");
+        assert_eq!(tags(&ea), vec!["ea", "jdk"]);
+
+        let graal = bin_asset_download(
+            &asset("graalvm-community-jdk-21.0.1_windows-x64_bin.zip", false),
+            "graalvm-community-",
+        )
+        .unwrap();
+        assert_eq!(graal.os, Some(Os::Windows));
+        assert_eq!(graal.arch, Some(Arch::X86_64));
+
+        for name in [
+            "sapmachine-jdk-21.0.1_linux-x64_bin.sha256.txt",
+            "sapmachine-jdk-21.0.1_linux-x64_bin.zip",
+            "sapmachine-symbols-21.0.1_linux-x64_bin.tar.gz",
+            "graalvm-ce-java17-linux-amd64-22.3.1.tar.gz",
+        ] {
+            assert!(bin_asset_download(&asset(name, false), "sapmachine-").is_none());
+            assert!(bin_asset_download(&asset(name, false), "graalvm-community-").is_none());
+        }
+    }
+
+    #[test]
+    fn test_semeru() {
+        assert_eq!(semeru_repo(21), "semeru21-binaries");
+        let major = |req: &str| requested_major(&VersionReq::parse(req).unwrap());
+        assert_eq!(major("=21"), Some(21));
+        assert_eq!(major("~21.0"), Some(21));
+        assert_eq!(major("^17"), Some(17));
+        assert_eq!(major("8.*"), Some(8));
+        assert_eq!(major(">=17"), None);
+        assert_eq!(major(">=17, <22"), None);
+
+        let jdk = semeru_download(&asset(
+            "ibm-semeru-open-jdk_x64_linux_21.0.1_12_openj9-0.42.0.tar.gz",
+            false,
+        ))
+        .unwrap();
+        assert_eq!(version(&jdk), "21.0.1");
+        assert_eq!(tags(&jdk), vec!["ga", "jdk", "lts", "openj9"]);
+
+        let eight = semeru_download(&asset(
+            "ibm-semeru-open-jre_aarch64_mac_8u392b08_openj9-0.41.0.tar.gz",
+            false,
+        ))
+        .unwrap();
+        assert_eq!(version(&eight), "8.0.392");
+        assert_eq!(eight.os, Some(Os::Mac));
+
+        assert!(semeru_download(&asset(
+            "ibm-semeru-open-jdk_x64_windows_21.0.1_12_openj9-0.42.0.msi",
+            false
+        ))
+        .is_none());
+        assert!(semeru_download(&asset(
+            "ibm-semeru-open-debugimage_x64_linux_21.0.1_12_openj9-0.42.0.tar.gz",
+            false
+        ))
+        .is_none());
+    }
+}
@@ -0,0 +1,96 @@
+use std::fs;
+use std::path::PathBuf;
+
+use indicatif::ProgressBar;
+use log::{info, warn};
+
+use crate::cli::parse_command_string;
+use crate::config::GgConfig;
+use crate::executor::{cache_subdir, prep, AppInput, Executor, GgMeta};
+use crate::executors::java_distributions::JavaDistributions;
+use crate::prepare::executor_cmd;
+
+/// A JDK prepared for a build tool's toolchains
+pub struct ToolchainJdk {
+    /// As asked for: `17`, `21-graalvm`
+    pub spec: String,
+    /// Absolute, build tools resolve relative paths from somewhere else
+    pub home: PathBuf,
+    /// What got installed, when gg-meta.json says
+    pub version: Option<String>,
+    pub distribution: String,
+}
+
+/// The versions in a gg.toml toolchains list, blanks left out. Empty turns
+/// toolchains off.
+pub fn clean_specs(configured: &[String]) -> Vec<String> {
+    configured
+        .iter()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+fn installed_version(executor: &dyn Executor) -> Option<String> {
+    let meta = crate::cache::install_dir()
+        .join(cache_subdir(executor))
+        .join("gg-meta.json");
+    let meta: GgMeta = serde_json::from_str(&fs::read_to_string(meta).ok()?).ok()?;
+    meta.download.version.map(|v| v.to_string())
+}
+
+/// Prepares `java@<spec>` for each spec, one after the other on `pb`. The
+/// bool says whether every one of them could be had.
+pub async fn prep_jdks(
+    specs: &[String],
+    input: &AppInput,
+    pb: &ProgressBar,
+) -> (Vec<ToolchainJdk>, bool) {
+    let mut jdks = vec![];
+    let mut all = true;
+    for spec in specs {
+        let java = format!("java@{}", spec);
+        let executor = parse_command_string(&java, &GgConfig::default())
+            .first()
+            .and_then(|cmd| <dyn Executor>::new(executor_cmd(cmd)));
+        let Some(executor) = executor else {
+            warn!("Can't make sense of toolchain {}", java);
+            all = false;
+            continue;
+        };
+        match prep(&*executor, input, pb).await {
+            Ok(app_path) => {
+                info!("Toolchain {} in {:?}", java, app_path.install_dir);
+                let distribution = executor
+                    .get_executor_cmd()
+                    .distribution
+                    .as_deref()
+                    .and_then(JavaDistributions::get_by_name)
+                    .unwrap_or_else(JavaDistributions::get_default);
+                jdks.push(ToolchainJdk {
+                    spec: spec.clone(),
+                    home: fs::canonicalize(&app_path.install_dir).unwrap_or(app_path.install_dir),
+                    version: installed_version(&*executor),
+                    distribution: distribution.name.to_string(),
+                });
+            }
+            Err(e) => {
+                warn!("Could not prepare toolchain {}: {}", java, e);
+                all = false;
+            }
+        }
+    }
+    (jdks, all)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_specs() {
+        let specs = ["17", " 21-graalvm", ""].map(String::from);
+        assert_eq!(clean_specs(&specs), vec!["17", "21-graalvm"]);
+        assert!(clean_specs(&[]).is_empty());
+    }
+}
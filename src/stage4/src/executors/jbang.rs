@@ -79,10 +79,9 @@ pub async fn get_jbang_java_version_from_file(file_path: &str) -> Option<String>
             file_path.to_string()
         };
 
-        if let Ok(response) = reqwest::get(&url).await {
-            if let Ok(content) = response.text().await {
-                return parse_jbang_java_version(&content);
-            }
+        // Any repo's file, which the mirror won't fetch, so straight there then
+        if let Some(content) = crate::fetch::fetch_text(&url).await {
+            return parse_jbang_java_version(&content);
         }
     }
 
@@ -1,17 +1,54 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
+use std::sync::OnceLock;
 
+use indicatif::ProgressBar;
+use log::{debug, info};
 use scraper::{Html, Selector};
+use semver::VersionReq;
+use sha256::try_digest;
 
 use crate::executor::{
-    java_deps, AppInput, BinPattern, Download, ExecutorCmd, ExecutorDep, GgVersion,
+    java_deps, AppInput, AppPath, BinPattern, Download, ExecutorCmd, ExecutorDep, GgVersion,
 };
+use crate::executors::maven_toolchains::prep_toolchains;
+use crate::executors::maven_wrapper_properties::{maven_config_args, MavenWrapperProperties};
 use crate::target::{Arch, Os, Variant};
 use crate::Executor;
 
 pub struct Maven {
     pub executor_cmd: ExecutorCmd,
+    /// toolchains.xml for the JDKs gg prepared, once they are
+    toolchains: OnceLock<PathBuf>,
+    wrapper: Option<MavenWrapperProperties>,
+}
+
+impl Maven {
+    pub fn new(executor_cmd: ExecutorCmd) -> Self {
+        Self {
+            executor_cmd,
+            toolchains: OnceLock::new(),
+            wrapper: MavenWrapperProperties::new(),
+        }
+    }
+
+    /// The wrapper's distribution URL and version, unless a command or gg.toml
+    /// version asks for something else
+    fn wrapper_download(&self) -> Option<(&MavenWrapperProperties, String)> {
+        let wrapper = self.wrapper.as_ref()?;
+        let version = wrapper.get_version_from_distribution_url()?;
+        let wanted = match &self.executor_cmd.version {
+            None => true,
+            Some(req) => GgVersion::new(&version)
+                .is_some_and(|v| req.to_version_req().matches(&v.to_version())),
+        };
+        if !wanted {
+            debug!("Not maven-wrapper.properties' {version}, another version was asked for");
+        }
+        wanted.then_some((wrapper, version))
+    }
 }
 
 fn get_tags(version: &str) -> HashSet<String> {
@@ -29,7 +66,7 @@ fn get_tags(version: &str) -> HashSet<String> {
 }
 
 async fn fetch_versions_from_directory(base_url: &str) -> Vec<Download> {
-    let body = match reqwest::get(base_url).await {
+    let body = match reqwest::get(crate::mirror::mirrored(base_url)).await {
         Ok(response) => match response.text().await {
             Ok(text) => text,
             Err(_) => return vec![],
@@ -75,11 +112,27 @@ impl Executor for Maven {
         &self.executor_cmd
     }
 
+    fn get_version_req(&self) -> Option<VersionReq> {
+        let version = self.wrapper.as_ref()?.get_version_from_distribution_url()?;
+        VersionReq::parse(version.as_str()).ok()
+    }
+
     fn get_download_urls<'a>(
         &'a self,
         _input: &'a AppInput,
     ) -> Pin<Box<dyn Future<Output = Vec<Download>> + 'a>> {
         Box::pin(async move {
+            // Same as mvnw would, including a company repository
+            if let Some((wrapper, version)) = self.wrapper_download() {
+                if let Some(distribution_url) = &wrapper.distribution_url {
+                    return vec![Download::new(
+                        distribution_url.clone(),
+                        version.as_str(),
+                        Some(Variant::Any),
+                    )];
+                }
+            }
+
             let mut downloads = Vec::new();
 
             let maven1_versions =
@@ -125,9 +178,86 @@ impl Executor for Maven {
         java_deps()
     }
 
+    fn get_extra_env<'a>(
+        &'a self,
+        input: &'a AppInput,
+        pb: &'a ProgressBar,
+    ) -> Pin<Box<dyn Future<Output = HashMap<String, String>> + 'a>> {
+        Box::pin(async move {
+            if let Some(path) = prep_toolchains(input, pb).await {
+                let _ = self.toolchains.set(path);
+            }
+            HashMap::new()
+        })
+    }
+
+    fn customize_args(&self, input: &AppInput, _app_path: &AppPath) -> Vec<String> {
+        let mut args = input.app_args.clone();
+        let own = args
+            .iter()
+            .chain(maven_config_args().iter())
+            .any(|a| a == "-gt" || a.starts_with("--global-toolchains"));
+        if let (Some(path), false) = (self.toolchains.get(), own) {
+            args.insert(0, "--global-toolchains".to_string());
+            args.insert(1, path.to_string_lossy().to_string());
+        }
+        args
+    }
+
+    fn post_download(&self, download_file_path: String) -> bool {
+        // The wrapper's checksum is only for the wrapper's download
+        if let Some(checksum) = self
+            .wrapper_download()
+            .and_then(|(w, _)| w.distribution_sha256_sum.clone())
+        {
+            info!("Checksum found for {}: {}", &download_file_path, checksum);
+            debug!("Calculating checksum for {}", &download_file_path);
+            let val = try_digest(Path::new(download_file_path.as_str())).unwrap();
+            info!("Calculated checksum: {}", val);
+            return checksum.eq_ignore_ascii_case(&val);
+        }
+        debug!("No checksum found in maven-wrapper.properties (skipping check)");
+        true
+    }
+
     fn get_default_exclude_tags(&self) -> HashSet<String> {
         vec!["alpha".to_string(), "beta".to_string(), "rc".to_string()]
             .into_iter()
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::GgVersionReq;
+
+    fn maven(version: Option<&str>) -> Maven {
+        Maven {
+            executor_cmd: ExecutorCmd {
+                cmd: "maven".to_string(),
+                version: version.and_then(GgVersionReq::new),
+                distribution: None,
+                include_tags: Default::default(),
+                exclude_tags: Default::default(),
+                gems: None,
+            },
+            toolchains: OnceLock::new(),
+            wrapper: Some(MavenWrapperProperties {
+                distribution_url: Some(
+                    "https://nexus.corp/maven2/org/apache/maven/apache-maven/3.9.6/apache-maven-3.9.6-bin.zip"
+                        .to_string(),
+                ),
+                distribution_sha256_sum: Some("abc123".to_string()),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_wrapper_download_only_for_its_version() {
+        assert_eq!(maven(None).wrapper_download().unwrap().1, "3.9.6");
+        assert!(maven(Some("3.9")).wrapper_download().is_some());
+        assert!(maven(Some("3.8")).wrapper_download().is_none());
+        assert!(maven(Some("4")).wrapper_download().is_none());
+    }
+}
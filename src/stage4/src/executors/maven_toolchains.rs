@@ -0,0 +1,318 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use indicatif::ProgressBar;
+use log::{info, warn};
+use regex::Regex;
+
+use crate::executor::AppInput;
+use crate::config::GgConfig;
+use crate::executors::java_toolchains::{clean_specs, prep_jdks, ToolchainJdk};
+
+fn captures(re: &Regex, text: &str) -> Vec<String> {
+    re.captures_iter(text)
+        .map(|c| c[1].trim().to_string())
+        .collect()
+}
+
+/// `${java.version}` from `<properties>`, anything else as is
+fn resolve(value: &str, pom: &str) -> Option<String> {
+    let Some(name) = value.strip_prefix("${").and_then(|v| v.strip_suffix('}')) else {
+        return Some(value.to_string());
+    };
+    let re = Regex::new(&format!(r"<{0}>\s*([^<]+?)\s*</{0}>", regex::escape(name))).ok()?;
+    re.captures(pom).map(|c| c[1].to_string())
+}
+
+/// `17`, `1.8`, `[17,)`, `[11,12)` -> the Java major to install. Ranges
+/// without a lower bound say too little.
+fn java_major(requirement: &str) -> Option<String> {
+    let lower = requirement
+        .trim_start_matches(['[', '('])
+        .split(',')
+        .next()?
+        .trim();
+    let mut parts = lower.split('.');
+    let major = match parts.next()? {
+        "1" => parts.next()?,
+        major => major,
+    };
+    major
+        .chars()
+        .all(|c| c.is_ascii_digit())
+        .then(|| major.to_string())
+        .filter(|m| !m.is_empty())
+}
+
+/// The JDK versions a pom asks its toolchains for, properties resolved: the
+/// configuration of maven-toolchains-plugin, and `<jdkToolchain>` of
+/// compiler/surefire & co
+fn requirements_in(pom: &str) -> Vec<String> {
+    static PLUGIN: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(?s)<plugin>(.*?)</plugin>").unwrap());
+    static CONFIGURATION: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(?s)<configuration>(.*?)</configuration>").unwrap());
+    static JDK_TOOLCHAIN: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(?s)<jdkToolchain>(.*?)</jdkToolchain>").unwrap());
+    static VERSION: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"<version>([^<]+)</version>").unwrap());
+
+    let mut versions = vec![];
+    for plugin in captures(&PLUGIN, pom) {
+        let blocks = if plugin.contains("<artifactId>maven-toolchains-plugin</artifactId>") {
+            captures(&CONFIGURATION, &plugin)
+        } else {
+            captures(&JDK_TOOLCHAIN, &plugin)
+        };
+        for block in blocks {
+            versions.extend(
+                captures(&VERSION, &block)
+                    .iter()
+                    .filter_map(|v| resolve(v, pom))
+                    .filter(|v| java_major(v).is_some()),
+            );
+        }
+    }
+    versions.sort();
+    versions.dedup();
+    versions
+}
+
+fn is_range(requirement: &str) -> bool {
+    requirement.starts_with(['[', '('])
+}
+
+/// JDK spec to prepare -> the plain versions it has to provide. Maven takes a
+/// plain `<version>` requirement as "exactly this", so `21` only matches a
+/// toolchain that provides `21`, not `21.0.1`. Ranges match the installed
+/// version, they add nothing.
+fn wanted_from_pom(requirements: &[String]) -> BTreeMap<String, Vec<String>> {
+    let mut wanted = BTreeMap::<String, Vec<String>>::new();
+    for requirement in requirements {
+        let Some(major) = java_major(requirement) else {
+            continue;
+        };
+        let versions = wanted.entry(major).or_default();
+        if !is_range(requirement) && !versions.contains(requirement) {
+            versions.push(requirement.clone());
+        }
+    }
+    wanted
+}
+
+/// gg.toml specs like `21-graalvm` provide their version part
+fn wanted_from_config(specs: &[String]) -> BTreeMap<String, Vec<String>> {
+    specs
+        .iter()
+        .map(|spec| {
+            let version = spec.split(['-', '+']).next().unwrap_or_default();
+            let versions = if version.is_empty() {
+                vec![]
+            } else {
+                vec![version.to_string()]
+            };
+            (spec.clone(), versions)
+        })
+        .collect()
+}
+
+/// One `<toolchain>` per version a JDK provides: what was asked for, and what
+/// got installed (for ranges and exact requirements)
+fn toolchains_xml(jdks: &[ToolchainJdk], wanted: &BTreeMap<String, Vec<String>>) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<!-- Written by gg, regenerated on every run -->\n<toolchains>\n");
+    for jdk in jdks {
+        let mut versions = wanted.get(&jdk.spec).cloned().unwrap_or_default();
+        let installed = jdk.version.as_ref().unwrap_or(&jdk.spec);
+        if !versions.contains(installed) {
+            versions.push(installed.clone());
+        }
+        for version in versions {
+            xml.push_str(&format!(
+                "  <toolchain>\n    <type>jdk</type>\n    <provides>\n      <version>{}</version>\n      <vendor>{}</vendor>\n    </provides>\n    <configuration>\n      <jdkHome>{}</jdkHome>\n    </configuration>\n  </toolchain>\n",
+                version,
+                jdk.distribution,
+                jdk.home.display()
+            ));
+        }
+    }
+    xml.push_str("</toolchains>\n");
+    xml
+}
+
+/// One file per project dir, so two checkouts don't fight over it
+fn toolchains_path(project: &Path) -> PathBuf {
+    let project = fs::canonicalize(project).unwrap_or(project.to_path_buf());
+    let hash = sha256::digest(project.to_string_lossy().as_bytes());
+    crate::cache::cache_dir()
+        .join("gg-toolchains")
+        .join(format!("maven-{}.xml", &hash[..16]))
+}
+
+/// Prepares the JDKs from gg.toml `[maven] toolchains` or pom.xml and writes
+/// a toolchains.xml for them. The path, for `--global-toolchains`.
+pub async fn prep_toolchains(input: &AppInput, pb: &ProgressBar) -> Option<PathBuf> {
+    let wanted = match &GgConfig::loaded().maven.toolchains {
+        Some(configured) => wanted_from_config(&clean_specs(configured)),
+        None => wanted_from_pom(&requirements_in(&fs::read_to_string("pom.xml").ok()?)),
+    };
+    if wanted.is_empty() {
+        return None;
+    }
+    let specs = wanted.keys().cloned().collect::<Vec<_>>();
+    let (jdks, _) = prep_jdks(&specs, input, pb).await;
+    if jdks.is_empty() {
+        return None;
+    }
+    let path = toolchains_path(Path::new("."));
+    let written = path
+        .parent()
+        .map(fs::create_dir_all)
+        .unwrap_or(Ok(()))
+        .and_then(|_| fs::write(&path, toolchains_xml(&jdks, &wanted)));
+    match written {
+        Ok(_) => {
+            info!("Maven toolchains in {}", path.display());
+            Some(path)
+        }
+        Err(e) => {
+            warn!("Could not write {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POM: &str = r#"<project>
+  <properties>
+    <jdk.version>21</jdk.version>
+  </properties>
+  <build>
+    <plugins>
+      <plugin>
+        <groupId>org.apache.maven.plugins</groupId>
+        <artifactId>maven-toolchains-plugin</artifactId>
+        <version>3.1.0</version>
+        <configuration>
+          <toolchains>
+            <jdk>
+              <version>[17,)</version>
+              <vendor>temurin</vendor>
+            </jdk>
+          </toolchains>
+        </configuration>
+      </plugin>
+      <plugin>
+        <artifactId>maven-surefire-plugin</artifactId>
+        <version>3.2.2</version>
+        <configuration>
+          <jdkToolchain>
+            <version>${jdk.version}</version>
+          </jdkToolchain>
+        </configuration>
+      </plugin>
+      <plugin>
+        <artifactId>maven-compiler-plugin</artifactId>
+        <version>3.11.0</version>
+        <configuration><jdkToolchain><version>1.8</version></jdkToolchain></configuration>
+      </plugin>
+    </plugins>
+  </build>
+</project>"#;
+
+    #[test]
+    fn test_requirements_in() {
+        assert_eq!(requirements_in(POM), vec!["1.8", "21", "[17,)"]);
+        let wanted = wanted_from_pom(&requirements_in(POM));
+        assert_eq!(wanted.keys().collect::<Vec<_>>(), vec!["17", "21", "8"]);
+        assert_eq!(wanted["8"], vec!["1.8"]);
+        assert_eq!(wanted["21"], vec!["21"]);
+        assert!(wanted["17"].is_empty());
+        assert!(requirements_in("<project><build><plugins><plugin><version>3.1</version></plugin></plugins></build></project>").is_empty());
+    }
+
+    #[test]
+    fn test_java_major() {
+        assert_eq!(java_major("17"), Some("17".to_string()));
+        assert_eq!(java_major("1.8"), Some("8".to_string()));
+        assert_eq!(java_major("[11,12)"), Some("11".to_string()));
+        assert_eq!(java_major("17.0.2"), Some("17".to_string()));
+        assert_eq!(java_major("(,17]"), None);
+        assert_eq!(java_major("${missing}"), None);
+    }
+
+    #[test]
+    fn test_toolchains_xml() {
+        let jdk = |spec: &str, version: &str| ToolchainJdk {
+            spec: spec.to_string(),
+            home: PathBuf::from(format!("/cache/java/java_hat_{}", spec)),
+            version: Some(version.to_string()),
+            distribution: "temurin".to_string(),
+        };
+        let xml = toolchains_xml(
+            &[jdk("21-temurin", "21.0.1")],
+            &wanted_from_config(&["21-temurin".to_string()]),
+        );
+        assert!(xml.contains("<version>21</version>"));
+        assert!(xml.contains("<version>21.0.1</version>"));
+        assert!(xml.contains("<vendor>temurin</vendor>"));
+        assert!(xml.contains("<jdkHome>/cache/java/java_hat_21-temurin</jdkHome>"));
+
+        // Every requirement in the pom finds a toolchain, the way Maven looks
+        let jdks = [
+            jdk("8", "8.0.392"),
+            jdk("17", "17.0.9"),
+            jdk("21", "21.0.1"),
+        ];
+        let xml = toolchains_xml(&jdks, &wanted_from_pom(&requirements_in(POM)));
+        let provided = captures(&Regex::new(r"<version>([^<]+)</version>").unwrap(), &xml);
+        for requirement in requirements_in(POM) {
+            assert!(
+                provided.iter().any(|p| maven_matches(&requirement, p)),
+                "nothing provides {} in {:?}",
+                requirement,
+                provided
+            );
+        }
+        assert!(!provided.iter().any(|p| maven_matches("11", p)));
+    }
+
+    /// `1.8.0` -> [1, 8], trailing zeros don't count in Maven's comparison
+    fn numbers(version: &str) -> Vec<u64> {
+        let mut numbers = version
+            .split('.')
+            .map(|n| n.trim().parse().unwrap_or(0))
+            .collect::<Vec<u64>>();
+        while numbers.last() == Some(&0) {
+            numbers.pop();
+        }
+        numbers
+    }
+
+    /// Maven's toolchain version check, for numeric versions: a range has
+    /// to contain what's provided, anything else has to be equal to it
+    fn maven_matches(requirement: &str, provided: &str) -> bool {
+        let provided = numbers(provided);
+        if !is_range(requirement) {
+            return numbers(requirement) == provided;
+        }
+        let inner = &requirement[1..requirement.len() - 1];
+        let (lower, upper) = inner.split_once(',').unwrap_or((inner, inner));
+        let above = lower.trim().is_empty()
+            || match requirement.starts_with('[') {
+                true => provided >= numbers(lower),
+                false => provided > numbers(lower),
+            };
+        let below = upper.trim().is_empty()
+            || match requirement.ends_with(']') {
+                true => provided <= numbers(upper),
+                false => provided < numbers(upper),
+            };
+        above && below
+    }
+}
@@ -0,0 +1,77 @@
+use std::fs;
+
+use regex::Regex;
+use serde::Deserialize;
+
+const WRAPPER_PROPERTIES_FILE: &str = ".mvn/wrapper/maven-wrapper.properties";
+
+/// Version files in the current dir
+pub const VERSION_FILES: &[&str] = &[WRAPPER_PROPERTIES_FILE];
+
+/// `.mvn/wrapper/maven-wrapper.properties`, what `mvnw` would download
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MavenWrapperProperties {
+    pub distribution_url: Option<String>,
+    pub distribution_sha256_sum: Option<String>,
+}
+
+fn get_version_from_maven_url(maven_url: &str) -> Option<String> {
+    Regex::new(r"apache-maven-(.+?)-bin\.(?:zip|tar\.gz)")
+        .ok()?
+        .captures(maven_url)
+        .map(|c| c[1].to_string())
+}
+
+impl MavenWrapperProperties {
+    pub fn new() -> Option<MavenWrapperProperties> {
+        fs::read_to_string(WRAPPER_PROPERTIES_FILE)
+            .ok()
+            .and_then(|text| serde_java_properties::from_str(text.as_str()).ok())
+    }
+
+    pub fn get_version_from_distribution_url(&self) -> Option<String> {
+        self.distribution_url
+            .as_deref()
+            .and_then(get_version_from_maven_url)
+    }
+}
+
+/// `.mvn/maven.config` arguments. mvn reads it (and `.mvn/jvm.config`)
+/// itself, gg only looks so it doesn't pass what's already there.
+pub fn maven_config_args() -> Vec<String> {
+    fs::read_to_string(".mvn/maven.config")
+        .map(|text| text.split_whitespace().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_maven_wrapper_properties() {
+        let text = "\
+wrapperVersion=3.3.2
+distributionUrl=https\\://repo.maven.apache.org/maven2/org/apache/maven/apache-maven/3.9.6/apache-maven-3.9.6-bin.zip
+distributionSha256Sum=abc123
+";
+        let props: MavenWrapperProperties = serde_java_properties::from_str(text).unwrap();
+
+        assert_eq!(
+            props.distribution_url.as_deref(),
+            Some("https://repo.maven.apache.org/maven2/org/apache/maven/apache-maven/3.9.6/apache-maven-3.9.6-bin.zip")
+        );
+        assert_eq!(props.distribution_sha256_sum.as_deref(), Some("abc123"));
+        assert_eq!(props.get_version_from_distribution_url().unwrap(), "3.9.6");
+        assert_eq!(
+            get_version_from_maven_url("https://nexus.corp/apache-maven-4.0.0-rc-2-bin.tar.gz")
+                .unwrap(),
+            "4.0.0-rc-2"
+        );
+        assert_eq!(
+            get_version_from_maven_url("https://example.com/maven.zip"),
+            None
+        );
+    }
+}
@@ -7,12 +7,17 @@ pub mod go;
 pub mod gradle;
 pub mod grok;
 pub mod gradle_properties;
+pub mod gradle_toolchains;
 pub mod java;
 pub mod java_distributions;
+pub mod java_toolchains;
 pub mod jbang;
 pub mod maven;
+pub mod maven_toolchains;
+pub mod maven_wrapper_properties;
 pub mod node;
 pub mod openapigenerator;
+pub mod package_manager;
 pub mod python;
 pub mod rat;
 pub mod ruby;
@@ -1,16 +1,19 @@
 use std::collections::HashSet;
 use std::fs;
 use std::future::Future;
+use std::path::Path;
 use std::pin::Pin;
 
-use log::info;
+use log::{info, warn};
 use package_json::PackageJsonManager;
-use regex::Regex;
 use semver::VersionReq;
 use serde::Deserialize;
 use serde::Serialize;
 
-use crate::executor::{AppInput, BinPattern, Download, Executor, ExecutorCmd, GgVersion};
+use crate::executor::{
+    AppInput, AppPath, BinPattern, Download, Executor, ExecutorCmd, GgMeta, GgVersion,
+};
+use crate::executors::package_manager::fetch_packument;
 use crate::fetch::fetch_json;
 use crate::target::{Arch, Os, Target, Variant};
 
@@ -46,8 +49,10 @@ pub struct Node {
 
 /// A tool distributed as an npm package, executed on a gg-managed Node.js.
 /// Gets its own cache dir (named `name`), with the package installed into
-/// `npm_home/` by `post_prep` (mirrors the Ruby gems pattern).
-#[derive(Clone)]
+/// `npm_home/` by `post_prep` (mirrors the Ruby gems pattern). Its versions
+/// are the package's, from the npm registry, each paired with the newest LTS
+/// Node.js.
+#[derive(Clone, Debug, PartialEq)]
 pub struct NpmPackageSpec {
     /// Tool name (cache dir name), e.g. "gemini-cli"
     pub name: String,
@@ -57,62 +62,251 @@ pub struct NpmPackageSpec {
     pub bin: String,
 }
 
-fn get_package_version() -> Option<Box<VersionReq>> {
+impl NpmPackageSpec {
+    /// `npm:@scope/pkg`, named `npm-scope+pkg` (`npm-pkg` without a scope).
+    /// The prefix keeps it apart from gg's own tools (`npm:gemini-cli` isn't
+    /// the gemini-cli tool), and npm names can't hold a `+`, so no two
+    /// packages get the same cache dir.
+    pub fn from_cmd(cmd: &str) -> Option<Self> {
+        let package = cmd.strip_prefix("npm:")?;
+        let (scope, bin) = match package.strip_prefix('@') {
+            Some(scoped) => {
+                let (scope, bin) = scoped.split_once('/')?;
+                (Some(scope), bin)
+            }
+            None => (None, package),
+        };
+        let valid = |part: &str| !part.is_empty() && !part.contains(['@', '/', '+']);
+        if !valid(bin) || !scope.is_none_or(valid) {
+            return None;
+        }
+        let name = match scope {
+            Some(scope) => format!("npm-{}+{}", scope, bin),
+            None => format!("npm-{}", bin),
+        };
+        Some(Self {
+            name,
+            package: package.to_string(),
+            bin: bin.to_string(),
+        })
+    }
+
+    /// `npm_home/lib/node_modules/<package>` (unix) or `npm_home/node_modules/<package>`
+    fn installed_package_json(&self, install_dir: &Path) -> Option<serde_json::Value> {
+        ["npm_home/lib/node_modules", "npm_home/node_modules"]
+            .iter()
+            .map(|dir| {
+                install_dir
+                    .join(dir)
+                    .join(&self.package)
+                    .join("package.json")
+            })
+            .find_map(|path| fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+    }
+
+    /// The executable to run: `bin`, unless the installed package has no such
+    /// thing, then the first one it does have (npm:typescript gives tsc)
+    fn installed_bin(&self, install_dir: &Path) -> String {
+        let bins = self
+            .installed_package_json(install_dir)
+            .and_then(|json| json.get("bin").cloned());
+        match bins {
+            Some(serde_json::Value::Object(bins)) if !bins.contains_key(&self.bin) => bins
+                .keys()
+                .next()
+                .cloned()
+                .unwrap_or_else(|| self.bin.clone()),
+            _ => self.bin.clone(),
+        }
+    }
+}
+
+fn npm_bin_patterns(os: &Os, bin: &str) -> Vec<BinPattern> {
+    match os {
+        Os::Windows => vec![
+            BinPattern::Exact(format!("npm_home/{bin}.cmd")),
+            BinPattern::Exact(format!("npm_home/{bin}")),
+        ],
+        _ => vec![BinPattern::Exact(format!("npm_home/bin/{bin}"))],
+    }
+}
+
+/// The package's versions, all run on the newest LTS Node.js there is for
+/// the target
+async fn npm_package_downloads(spec: &NpmPackageSpec, nodes: Vec<Download>) -> Vec<Download> {
+    let Some(node) = nodes
+        .into_iter()
+        .filter(|d| d.tags.contains("lts"))
+        .max_by_key(|d| d.version.as_ref().map(|v| v.to_version()))
+    else {
+        return vec![];
+    };
+    let Some(packument) = fetch_packument(&spec.package).await else {
+        return vec![];
+    };
+    packument
+        .versions
+        .keys()
+        .map(|version| Download {
+            version: GgVersion::new(version),
+            tags: HashSet::new(),
+            ..node.clone()
+        })
+        .collect()
+}
+
+/// What a version file or field asks for: a range, or one of nvm's aliases
+#[derive(Debug, Clone, PartialEq)]
+enum NodeVersion {
+    Req(VersionReq),
+    /// `lts/*`, or `lts/hydrogen` for one LTS line
+    Lts(Option<String>),
+    /// `node` or `stable`, whatever is newest
+    Latest,
+}
+
+fn parse_node_version(text: &str) -> Option<NodeVersion> {
+    let text = text.trim();
+    match text.to_lowercase().as_str() {
+        "node" | "stable" | "latest" => return Some(NodeVersion::Latest),
+        "lts/*" | "lts" => return Some(NodeVersion::Lts(None)),
+        alias => {
+            if let Some(codename) = alias.strip_prefix("lts/") {
+                if codename.chars().all(|c| c.is_ascii_alphabetic()) {
+                    return Some(NodeVersion::Lts(Some(codename.to_string())));
+                }
+                warn!("nvm alias {text} is not supported, use lts/* or lts/<codename>");
+                return None;
+            }
+        }
+    }
+    let text = text.strip_prefix('v').unwrap_or(text);
+    VersionReq::parse(text).ok().map(NodeVersion::Req)
+}
+
+/// The node line of asdf/mise's `.tool-versions`; the first version wins
+fn tool_versions_node(content: &str) -> Option<&str> {
+    content.lines().find_map(|line| {
+        let mut parts = line.split('#').next()?.split_whitespace();
+        match parts.next()? {
+            "nodejs" | "node" => parts.next(),
+            _ => None,
+        }
+    })
+}
+
+/// Version files in the current dir, in the order they count
+pub const VERSION_FILES: &[&str] = &[".nvmrc", ".node-version", ".tool-versions"];
+
+/// The Node.js version the project asks for: `volta.node` and `engines.node`
+/// in the closest package.json, then `VERSION_FILES`
+fn get_package_version() -> Option<NodeVersion> {
     let mut manager = PackageJsonManager::new();
     if manager.locate_closest().is_ok() {
         if let Ok(json) = manager.read_ref() {
-            if json.engines.is_some() {
-                return Some(Box::new(
-                    VersionReq::parse(
-                        json.engines
-                            .as_ref()
-                            .unwrap()
-                            .get("node")
-                            .unwrap_or(&"".to_string()),
-                    )
-                    .unwrap_or_default(),
+            let volta = json
+                .unknowns
+                .get("volta")
+                .and_then(|v| v.get("node"))
+                .and_then(|v| v.as_str());
+            if let Some(version) = volta.and_then(parse_node_version) {
+                info!("Got version {version:?} from volta in package.json");
+                return Some(version);
+            }
+            if let Some(engines) = &json.engines {
+                return Some(NodeVersion::Req(
+                    VersionReq::parse(engines.get("node").unwrap_or(&"".to_string()))
+                        .unwrap_or_default(),
                 ));
             }
         }
     }
 
-    if let Ok(nvmrc) = fs::read_to_string(".nvmrc") {
-        let nvmrc = Regex::new("^v").unwrap().replace(&nvmrc, "");
-        let nvmrc = nvmrc.trim();
-        info!("Got version {nvmrc} from .nvmrc");
-        if let Ok(ver) = VersionReq::parse(nvmrc) {
-            info!("Got parsed version {ver} from .nvmrc");
-            return Some(Box::new(ver.clone()));
+    for &file in VERSION_FILES {
+        let Ok(content) = fs::read_to_string(file) else {
+            continue;
+        };
+        let text = if file == ".tool-versions" {
+            match tool_versions_node(&content) {
+                Some(text) => text,
+                None => continue,
+            }
+        } else {
+            content.as_str()
+        };
+        info!("Got version {} from {file}", text.trim());
+        if let Some(version) = parse_node_version(text) {
+            info!("Got parsed version {version:?} from {file}");
+            return Some(version);
         }
     }
     None
 }
 
+impl Node {
+    /// An nvm `lts/...` alias turns into tags, so it gets its own cache dir
+    /// like `node+lts` would, and a codename is matched against the `lts`
+    /// field of index.json
+    pub fn new(mut executor_cmd: ExecutorCmd, npm_package: Option<NpmPackageSpec>) -> Self {
+        if npm_package.is_none()
+            && executor_cmd.version.is_none()
+            && executor_cmd.include_tags.is_empty()
+        {
+            if let Some(NodeVersion::Lts(codename)) = get_package_version() {
+                executor_cmd.include_tags.insert("lts".to_string());
+                executor_cmd.include_tags.extend(codename);
+            }
+        }
+        Self {
+            executor_cmd,
+            npm_package,
+        }
+    }
+}
+
+/// `lts` for every LTS release, plus its lowercased codename
+fn lts_tags(lts: &Lts) -> HashSet<String> {
+    match lts {
+        Lts::String(codename) => vec!["lts".to_string(), codename.to_lowercase()]
+            .into_iter()
+            .collect(),
+        Lts::Bool(_) => HashSet::new(),
+    }
+}
+
 impl Executor for Node {
     fn get_executor_cmd(&self) -> &ExecutorCmd {
         &self.executor_cmd
     }
 
     fn get_version_req(&self) -> Option<VersionReq> {
-        get_package_version().map(|v| *v)
+        // The project's Node.js version has nothing to say about a package's
+        if self.npm_package.is_some() {
+            return None;
+        }
+        match get_package_version()? {
+            NodeVersion::Req(version_req) => Some(version_req),
+            _ => None,
+        }
     }
 
     fn get_download_urls<'a>(
-        &self,
+        &'a self,
         input: &'a AppInput,
     ) -> Pin<Box<dyn Future<Output = Vec<Download>> + 'a>> {
-        Box::pin(async move { get_node_urls(&input.target).await })
+        Box::pin(async move {
+            let nodes = get_node_urls(&input.target).await;
+            match &self.npm_package {
+                Some(spec) => npm_package_downloads(spec, nodes).await,
+                None => nodes,
+            }
+        })
     }
 
     fn get_bins(&self, input: &AppInput) -> Vec<BinPattern> {
         if let Some(spec) = &self.npm_package {
-            return match &input.target.os {
-                Os::Windows => vec![
-                    BinPattern::Exact(format!("npm_home/{}.cmd", spec.bin)),
-                    BinPattern::Exact(format!("npm_home/{}", spec.bin)),
-                ],
-                _ => vec![BinPattern::Exact(format!("npm_home/bin/{}", spec.bin))],
-            };
+            return npm_bin_patterns(&input.target.os, &spec.bin);
         }
         vec![BinPattern::Exact(
             match &input.target.os {
@@ -131,6 +325,15 @@ impl Executor for Node {
         )]
     }
 
+    fn get_bins_for_path(&self, input: &AppInput, app_path: &AppPath) -> Vec<BinPattern> {
+        match &self.npm_package {
+            Some(spec) => {
+                npm_bin_patterns(&input.target.os, &spec.installed_bin(&app_path.install_dir))
+            }
+            None => self.get_bins(input),
+        }
+    }
+
     fn get_name(&self) -> &str {
         match &self.npm_package {
             Some(spec) => &spec.name,
@@ -152,9 +355,9 @@ impl Executor for Node {
         }
     }
 
-    fn post_prep(&self, cache_path: &str) {
+    fn post_prep(&self, cache_path: &str) -> Result<(), String> {
         let Some(spec) = &self.npm_package else {
-            return;
+            return Ok(());
         };
         let cache = std::path::Path::new(cache_path);
         let npm_home = cache.join("npm_home");
@@ -179,16 +382,21 @@ impl Executor for Node {
             std::env::var("PATH").unwrap_or_default()
         );
 
-        info!(
-            "Installing npm package {} into {:?}",
-            spec.package, npm_home
-        );
+        // The version the download was picked for, or whatever is latest
+        let package = fs::read_to_string(cache.join("gg-meta.json"))
+            .ok()
+            .and_then(|json| serde_json::from_str::<GgMeta>(&json).ok())
+            .and_then(|meta| meta.download.version)
+            .map(|version| format!("{}@{}", spec.package, version))
+            .unwrap_or_else(|| spec.package.clone());
+
+        info!("Installing npm package {} into {:?}", package, npm_home);
         let status = std::process::Command::new(&npm)
             .arg("install")
             .arg("-g")
             .arg("--prefix")
             .arg(&npm_home)
-            .arg(&spec.package)
+            .arg(&package)
             .env("PATH", path_env)
             .env("npm_config_cache", &npm_cache)
             .env("npm_config_devdir", &node_gyp_dir)
@@ -196,9 +404,10 @@ impl Executor for Node {
 
         match status {
             Ok(s) if s.success() => {}
-            Ok(s) => println!("npm install {} failed: {}", spec.package, s),
-            Err(e) => println!("npm install {} failed: {}", spec.package, e),
+            Ok(s) => println!("npm install {} failed: {}", package, s),
+            Err(e) => println!("npm install {} failed: {}", package, e),
         }
+        Ok(())
     }
 }
 
@@ -235,18 +444,13 @@ async fn download_urls(host: &str, target: &Target) -> Vec<Download> {
     root.iter().filter(|r|
         r.files.contains(&file.to_string())
     ).map(|r| {
-        let lts = matches!(r.lts, Lts::String(_));
         let file_fix = if file.ends_with("-zip") {
             file.replace("-zip", ".zip")
         } else {
             file.to_string() + ".tar.gz"
         }.replace("osx", "darwin").replace("-tar", "");
 
-        let tags: HashSet<String> = if lts {
-            ["lts".to_string()].iter().cloned().collect()
-        } else {
-            HashSet::new()
-        };
+        let tags = lts_tags(&r.lts);
         let version_string = r.version.as_str();
         let version = GgVersion::new(version_string);
         Download {
@@ -268,3 +472,104 @@ async fn get_node_urls(target: &Target) -> Vec<Download> {
         _ => official_downloads(target).await,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_node_version() {
+        assert_eq!(
+            parse_node_version("v20.11.0\n"),
+            Some(NodeVersion::Req(VersionReq::parse("20.11.0").unwrap()))
+        );
+        assert_eq!(
+            parse_node_version("18"),
+            Some(NodeVersion::Req(VersionReq::parse("18").unwrap()))
+        );
+        assert_eq!(parse_node_version("lts/*"), Some(NodeVersion::Lts(None)));
+        assert_eq!(
+            parse_node_version("lts/Hydrogen"),
+            Some(NodeVersion::Lts(Some("hydrogen".to_string())))
+        );
+        assert_eq!(parse_node_version("node"), Some(NodeVersion::Latest));
+        assert_eq!(parse_node_version("lts/-1"), None);
+        assert_eq!(parse_node_version("system"), None);
+    }
+
+    #[test]
+    fn test_tool_versions_node() {
+        let content = "java temurin-21\nnodejs 20.11.0 18.19.0 # pinned\nruby 3.3.0\n";
+        assert_eq!(tool_versions_node(content), Some("20.11.0"));
+        assert_eq!(tool_versions_node("node lts/iron"), Some("lts/iron"));
+        assert_eq!(tool_versions_node("# nodejs 20\npython 3.12"), None);
+    }
+
+    #[test]
+    fn test_npm_package_spec() {
+        let spec = NpmPackageSpec::from_cmd("npm:@google/gemini-cli").unwrap();
+        assert_eq!(spec.name, "npm-google+gemini-cli");
+        assert_eq!(spec.package, "@google/gemini-cli");
+        assert_eq!(spec.bin, "gemini-cli");
+        assert_eq!(
+            NpmPackageSpec::from_cmd("npm:prettier").unwrap().name,
+            "npm-prettier"
+        );
+        assert_eq!(NpmPackageSpec::from_cmd("npm:"), None);
+        assert_eq!(NpmPackageSpec::from_cmd("npm:@scope"), None);
+        assert_eq!(NpmPackageSpec::from_cmd("npm:@/pkg"), None);
+        assert_eq!(NpmPackageSpec::from_cmd("node"), None);
+
+        // Never the same cache dir for two packages, or as a gg tool
+        let names = [
+            "npm:@angular/cli",
+            "npm:@vue/cli",
+            "npm:cli",
+            "npm:@a/b-c",
+            "npm:@a-b/c",
+            "npm:a-b-c",
+            "npm:gemini-cli",
+        ]
+        .iter()
+        .map(|cmd| NpmPackageSpec::from_cmd(cmd).unwrap().name)
+        .collect::<HashSet<_>>();
+        assert_eq!(names.len(), 7);
+        assert!(names
+            .iter()
+            .all(|n| crate::tools::get_tool_info(n).is_none()));
+    }
+
+    #[test]
+    fn test_installed_bin() {
+        let dir = tempfile::tempdir().unwrap();
+        let spec = NpmPackageSpec::from_cmd("npm:typescript").unwrap();
+        assert_eq!(spec.installed_bin(dir.path()), "typescript");
+
+        let package = dir.path().join("npm_home/lib/node_modules/typescript");
+        fs::create_dir_all(&package).unwrap();
+        fs::write(
+            package.join("package.json"),
+            r#"{"bin": {"tsserver": "bin/tsserver", "tsc": "bin/tsc"}}"#,
+        )
+        .unwrap();
+        assert_eq!(spec.installed_bin(dir.path()), "tsc");
+
+        // A package whose bin is named after it keeps it
+        let spec = NpmPackageSpec::from_cmd("npm:@scope/tool").unwrap();
+        let package = dir.path().join("npm_home/lib/node_modules/@scope/tool");
+        fs::create_dir_all(&package).unwrap();
+        fs::write(
+            package.join("package.json"),
+            r#"{"bin": {"helper": "h.js", "tool": "t.js"}}"#,
+        )
+        .unwrap();
+        assert_eq!(spec.installed_bin(dir.path()), "tool");
+    }
+
+    #[test]
+    fn test_lts_tags() {
+        let tags = lts_tags(&Lts::String("Iron".to_string()));
+        assert!(tags.contains("lts") && tags.contains("iron"));
+        assert!(lts_tags(&Lts::Bool(false)).is_empty());
+    }
+}
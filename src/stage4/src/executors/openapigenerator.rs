@@ -66,12 +66,13 @@ impl Executor for OpenAPIGenerator {
         }
     }
 
-    fn post_prep(&self, cache_path: &str) {
+    fn post_prep(&self, cache_path: &str) -> Result<(), String> {
         let pattern = format!("{}/*openapi-generator-cli*.jar", cache_path);
         if let Ok(paths) = glob::glob(&pattern) {
             for path in paths.flatten() {
                 rename(&path, format!("{}/openapi-generator-cli.jar", cache_path)).unwrap();
             }
         }
+        Ok(())
     }
 }
@@ -0,0 +1,409 @@
+use std::collections::HashMap;
+use std::fs;
+use std::future::Future;
+use std::io::Read;
+use std::path::Path;
+use std::pin::Pin;
+
+use async_compression::futures::bufread::GzipDecoder;
+use futures_util::{AsyncReadExt, FutureExt};
+use log::{info, warn};
+use package_json::PackageJsonManager;
+use semver::VersionReq;
+use serde::Deserialize;
+use sha2::{Digest, Sha224, Sha256, Sha384, Sha512};
+
+use crate::executor::{
+    AppInput, AppPath, BinPattern, Download, Executor, ExecutorCmd, ExecutorDep, GgVersion,
+    GgVersionReq,
+};
+use crate::fetch::fetch_json_accepting;
+
+/// The small "corgi" registry document: versions and their tarballs, none of
+/// the readmes that make the full one tens of megabytes for pnpm
+const ABBREVIATED: &str = "application/vnd.npm.install-v1+json";
+
+/// pnpm, yarn or corepack, straight from their npm tarball and run with the
+/// gg-managed `node` - nothing goes into a global node_modules.
+pub struct PackageManager {
+    pub executor_cmd: ExecutorCmd,
+    pub name: &'static str,
+    package_manager: Option<PackageManagerField>,
+}
+
+/// package.json's `packageManager`, e.g. "pnpm@9.1.0+sha512.1234abcd"
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackageManagerField {
+    pub name: String,
+    pub version: String,
+    /// The hash corepack pins, as (algorithm, hex digest)
+    pub hash: Option<(String, String)>,
+}
+
+/// An npm registry package document, the bits gg needs from it
+#[derive(Deserialize)]
+pub struct Packument {
+    pub versions: HashMap<String, PackumentVersion>,
+}
+
+#[derive(Deserialize)]
+pub struct PackumentVersion {
+    pub dist: Dist,
+}
+
+#[derive(Deserialize)]
+pub struct Dist {
+    pub tarball: String,
+}
+
+pub async fn fetch_packument(package: &str) -> Option<Packument> {
+    let url = format!("https://registry.npmjs.org/{}", package.replace('/', "%2f"));
+    fetch_json_accepting(&url, ABBREVIATED).await
+}
+
+pub fn parse_package_manager(value: &str) -> Option<PackageManagerField> {
+    let (name, rest) = value.trim().rsplit_once('@')?;
+    if name.is_empty() || rest.contains("://") {
+        return None;
+    }
+    let (version, hash) = match rest.split_once('+') {
+        Some((version, hash)) => (version, hash.split_once('.')),
+        None => (rest, None),
+    };
+    GgVersion::new(version)?;
+    Some(PackageManagerField {
+        name: name.to_string(),
+        version: version.to_string(),
+        hash: hash.map(|(algo, hex)| (algo.to_string(), hex.to_lowercase())),
+    })
+}
+
+/// `packageManager` from the closest package.json
+fn package_manager_field() -> Option<PackageManagerField> {
+    let mut manager = PackageJsonManager::new();
+    manager.locate_closest().ok()?;
+    let json = manager.read_ref().ok()?;
+    let value = json.unknowns.get("packageManager")?.as_str()?;
+    let field = parse_package_manager(value);
+    if field.is_none() {
+        warn!("Could not make sense of packageManager {value} in package.json");
+    }
+    field
+}
+
+/// Where the yarn.js corepack pins sits in the @yarnpkg/cli-dist tarball
+const CLI_DIST_YARN_JS: &str = "package/bin/yarn.js";
+
+/// One file out of an npm tarball (.tgz)
+fn packed_file(tgz: &[u8], path: &str) -> Option<Vec<u8>> {
+    let mut tar = vec![];
+    // Reading from memory never waits, so the future is done on first poll
+    GzipDecoder::new(tgz)
+        .read_to_end(&mut tar)
+        .now_or_never()?
+        .ok()?;
+    let mut archive = tar::Archive::new(tar.as_slice());
+    let mut entry = archive
+        .entries()
+        .ok()?
+        .flatten()
+        .find(|e| e.path().is_ok_and(|p| p == Path::new(path)))?;
+    let mut bytes = vec![];
+    entry.read_to_end(&mut bytes).ok()?;
+    Some(bytes)
+}
+
+fn hex_digest(algo: &str, bytes: &[u8]) -> Option<String> {
+    Some(match algo {
+        "sha224" => format!("{:x}", Sha224::digest(bytes)),
+        "sha256" => format!("{:x}", Sha256::digest(bytes)),
+        "sha384" => format!("{:x}", Sha384::digest(bytes)),
+        "sha512" => format!("{:x}", Sha512::digest(bytes)),
+        _ => return None,
+    })
+}
+
+impl PackageManager {
+    pub fn new(executor_cmd: ExecutorCmd, name: &'static str) -> Self {
+        let package_manager = package_manager_field().filter(|f| f.name == name);
+        Self {
+            executor_cmd,
+            name,
+            package_manager,
+        }
+    }
+
+    /// yarn 2+ is published as @yarnpkg/cli-dist, the `yarn` package stopped at 1.x
+    fn packages(&self) -> Vec<&'static str> {
+        match self.name {
+            "yarn" => vec!["yarn", "@yarnpkg/cli-dist"],
+            name => vec![name],
+        }
+    }
+
+    /// The script from the package's `bin`, relative to the install dir
+    fn script(&self, app_path: &AppPath) -> Option<String> {
+        let cmd = self.executor_cmd.cmd.as_str();
+        let json = fs::read_to_string(app_path.install_dir.join("package.json")).ok()?;
+        let json: serde_json::Value = serde_json::from_str(&json).ok()?;
+        match json.get("bin")? {
+            serde_json::Value::String(bin) => Some(bin.clone()),
+            serde_json::Value::Object(bins) => bins
+                .get(cmd)
+                .or_else(|| bins.get(self.name))
+                .and_then(|b| b.as_str())
+                .map(str::to_string),
+            _ => None,
+        }
+    }
+}
+
+impl Executor for PackageManager {
+    fn get_executor_cmd(&self) -> &ExecutorCmd {
+        &self.executor_cmd
+    }
+
+    fn get_version_req(&self) -> Option<VersionReq> {
+        let field = self.package_manager.as_ref()?;
+        info!(
+            "Got version {} from packageManager in package.json",
+            field.version
+        );
+        GgVersionReq::new(&field.version).map(|v| v.to_version_req())
+    }
+
+    fn get_download_urls<'a>(
+        &'a self,
+        _input: &'a AppInput,
+    ) -> Pin<Box<dyn Future<Output = Vec<Download>> + 'a>> {
+        Box::pin(async move {
+            let mut downloads = vec![];
+            for package in self.packages() {
+                if let Some(packument) = fetch_packument(package).await {
+                    downloads.extend(
+                        packument.versions.iter().map(|(version, v)| {
+                            Download::new(v.dist.tarball.clone(), version, None)
+                        }),
+                    );
+                }
+            }
+            downloads
+        })
+    }
+
+    fn get_bins(&self, _input: &AppInput) -> Vec<BinPattern> {
+        vec![BinPattern::Exact("node".to_string())]
+    }
+
+    // Runs via `node` from the node dep, so the cache check can't find a bin in
+    // our own dir - the package's script being there is what makes it a hit.
+    fn cached_install_is_valid(&self, app_path: &AppPath) -> bool {
+        self.script(app_path)
+            .is_some_and(|script| app_path.install_dir.join(script).is_file())
+    }
+
+    fn get_name(&self) -> &str {
+        self.name
+    }
+
+    fn get_deps<'a>(
+        &'a self,
+        _input: &'a AppInput,
+    ) -> Pin<Box<dyn Future<Output = Vec<ExecutorDep>> + 'a>> {
+        Box::pin(async move { vec![ExecutorDep::new("node".to_string(), None)] })
+    }
+
+    fn get_env(&self, app_path: &AppPath) -> HashMap<String, String> {
+        // corepack's own downloads stay in gg's cache instead of ~/.cache/node
+        let mut env = HashMap::new();
+        if self.name == "corepack" {
+            env.insert(
+                "COREPACK_HOME".to_string(),
+                app_path
+                    .install_dir
+                    .join("corepack_home")
+                    .to_string_lossy()
+                    .to_string(),
+            );
+        }
+        env
+    }
+
+    fn customize_args(&self, input: &AppInput, app_path: &AppPath) -> Vec<String> {
+        let mut args = vec![];
+        if let Some(script) = self.script(app_path) {
+            args.push(
+                app_path
+                    .install_dir
+                    .join(script)
+                    .to_string_lossy()
+                    .to_string(),
+            );
+        }
+        args.extend(input.app_args.clone());
+        args
+    }
+
+    fn post_download(&self, download_file_path: String) -> bool {
+        // A command or gg.toml version wins over packageManager, and then its
+        // hash is for some other version
+        let Some(field) = self
+            .package_manager
+            .as_ref()
+            .filter(|_| self.executor_cmd.version.is_none())
+        else {
+            return true;
+        };
+        let Some((algo, expected)) = &field.hash else {
+            return true;
+        };
+        let file_name = Path::new(&download_file_path)
+            .file_name()
+            .and_then(|f| f.to_str())
+            .unwrap_or_default();
+        let Ok(mut bytes) = fs::read(&download_file_path) else {
+            return false;
+        };
+        // corepack fetches yarn 2+ as a bare yarn.js from repo.yarnpkg.com, and
+        // the hash is for that. cli-dist ships the same file as bin/yarn.js.
+        if file_name.starts_with("cli-dist-") {
+            match packed_file(&bytes, CLI_DIST_YARN_JS) {
+                Some(yarn_js) => bytes = yarn_js,
+                None => {
+                    println!("No {CLI_DIST_YARN_JS} in {file_name} to check the packageManager hash against");
+                    return false;
+                }
+            }
+        }
+        match hex_digest(algo, &bytes) {
+            Some(actual) => {
+                info!("Checksum from packageManager: {expected}, calculated: {actual}");
+                if &actual != expected {
+                    println!("{file_name} does not match the {algo} in packageManager");
+                }
+                &actual == expected
+            }
+            None => {
+                println!(
+                    "Can't check the {algo} hash in packageManager, gg knows sha224 to sha512"
+                );
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_compression::futures::write::GzipEncoder;
+    use futures_util::AsyncWriteExt;
+
+    #[test]
+    fn test_parse_package_manager() {
+        assert_eq!(
+            parse_package_manager("pnpm@9.1.0+sha512.ABCdef"),
+            Some(PackageManagerField {
+                name: "pnpm".to_string(),
+                version: "9.1.0".to_string(),
+                hash: Some(("sha512".to_string(), "abcdef".to_string())),
+            })
+        );
+        let yarn = parse_package_manager("yarn@4.1.0-rc.1").unwrap();
+        assert_eq!(yarn.name, "yarn");
+        assert_eq!(yarn.version, "4.1.0-rc.1");
+        assert_eq!(yarn.hash, None);
+        assert_eq!(parse_package_manager("pnpm"), None);
+        assert_eq!(parse_package_manager("pnpm@latest"), None);
+        assert_eq!(
+            parse_package_manager("yarn@https://example.com/yarn-4.0.0.tgz"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_hex_digest() {
+        assert_eq!(
+            hex_digest("sha256", b"abc").as_deref(),
+            Some("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+        );
+        assert_eq!(hex_digest("md5", b"abc"), None);
+    }
+
+    #[test]
+    fn test_script_from_package_bin() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"bin": {"pnpm": "bin/pnpm.cjs", "pnpx": "bin/pnpx.cjs"}}"#,
+        )
+        .unwrap();
+        let app_path = AppPath {
+            install_dir: dir.path().to_path_buf(),
+        };
+        let cmd = |cmd: &str| ExecutorCmd {
+            cmd: cmd.to_string(),
+            version: None,
+            distribution: None,
+            include_tags: Default::default(),
+            exclude_tags: Default::default(),
+            gems: None,
+        };
+        let pnpm = PackageManager {
+            executor_cmd: cmd("pnpm"),
+            name: "pnpm",
+            package_manager: None,
+        };
+        assert_eq!(pnpm.script(&app_path).as_deref(), Some("bin/pnpm.cjs"));
+        assert!(!pnpm.cached_install_is_valid(&app_path));
+        let pnpx = PackageManager {
+            executor_cmd: cmd("pnpx"),
+            name: "pnpm",
+            package_manager: None,
+        };
+        assert_eq!(pnpx.script(&app_path).as_deref(), Some("bin/pnpx.cjs"));
+    }
+
+    #[test]
+    fn test_yarn_hash_checked_against_cli_dist_yarn_js() {
+        let yarn_js = b"#!/usr/bin/env node\n// yarn";
+        let mut tar = tar::Builder::new(vec![]);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(yarn_js.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        tar.append_data(&mut header, CLI_DIST_YARN_JS, &yarn_js[..])
+            .unwrap();
+        let mut encoder = GzipEncoder::new(vec![]);
+        encoder
+            .write_all(&tar.into_inner().unwrap())
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        encoder.close().now_or_never().unwrap().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let tgz = dir.path().join("cli-dist-4.1.0.tgz");
+        fs::write(&tgz, encoder.into_inner()).unwrap();
+        let empty = dir.path().join("cli-dist-4.0.0.tgz");
+        fs::write(&empty, b"").unwrap();
+
+        let yarn = |hash: String| PackageManager {
+            executor_cmd: ExecutorCmd {
+                cmd: "yarn".to_string(),
+                version: None,
+                distribution: None,
+                include_tags: Default::default(),
+                exclude_tags: Default::default(),
+                gems: None,
+            },
+            name: "yarn",
+            package_manager: parse_package_manager(&format!("yarn@4.1.0+{hash}")),
+        };
+        let good = format!("sha256.{}", hex_digest("sha256", yarn_js).unwrap());
+        assert!(yarn(good.clone()).post_download(tgz.to_string_lossy().to_string()));
+        assert!(!yarn(good).post_download(empty.to_string_lossy().to_string()));
+        let bad = format!("sha256.{}", hex_digest("sha256", b"other").unwrap());
+        assert!(!yarn(bad).post_download(tgz.to_string_lossy().to_string()));
+        assert!(!yarn("md5.abc".to_string()).post_download(tgz.to_string_lossy().to_string()));
+    }
+}
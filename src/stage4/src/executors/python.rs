@@ -1,11 +1,17 @@
 use std::collections::{HashMap, HashSet};
+use std::fs;
 use std::future::Future;
+use std::path::Path;
 use std::pin::Pin;
 
-use log::debug;
+use log::{debug, info};
+use regex::Regex;
 use serde::Deserialize;
 
-use crate::executor::{AppInput, BinPattern, Download, Executor, ExecutorCmd, GgVersion};
+use crate::executor::{
+    AppInput, AppPath, BinPattern, Download, Executor, ExecutorCmd, GgMeta, GgVersion,
+};
+use crate::fetch::fetch_json_accepting;
 use crate::target::{Arch, Os, Target, Variant};
 
 /// Python via astral's python-build-standalone (PBS) - the same prebuilt,
@@ -15,6 +21,210 @@ use crate::target::{Arch, Os, Target, Variant};
 /// of every PBS build (os/arch/libc/version + direct url), and pick from it.
 pub struct Python {
     pub executor_cmd: ExecutorCmd,
+    pub pip_package: Option<PipPackageSpec>,
+}
+
+/// A tool distributed as a PyPI package: its own cache dir holding Python and
+/// a venv (`venv/`) the package is pip installed into by `post_prep`. Its
+/// versions are the package's, each paired with the newest stable CPython its
+/// `Requires-Python` allows.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PipPackageSpec {
+    /// Tool name (cache dir name), the normalized package name, e.g. "black"
+    pub name: String,
+    /// What pip installs, extras included, e.g. "black[d]"
+    pub package: String,
+}
+
+impl PipPackageSpec {
+    /// `pip:black[d]`
+    pub fn from_cmd(cmd: &str) -> Option<Self> {
+        let package = cmd.strip_prefix("pip:")?;
+        let name = normalize(package.split('[').next().unwrap_or(package));
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return None;
+        }
+        Some(Self {
+            name,
+            package: package.to_string(),
+        })
+    }
+
+    /// The console script to run: the one named after the package, else the
+    /// first the package declares (pip:httpie gives http)
+    fn installed_bin(&self, install_dir: &Path) -> String {
+        let venv = install_dir.join("venv");
+        let named = ["bin", "Scripts"].iter().any(|dir| {
+            let bin = venv.join(dir).join(&self.name);
+            bin.is_file() || bin.with_extension("exe").is_file()
+        });
+        if named {
+            return self.name.clone();
+        }
+        let pattern = venv.join("**").join("*.dist-info").join("entry_points.txt");
+        glob::glob(&pattern.to_string_lossy())
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|path| {
+                let dist_info = path.parent().and_then(|p| p.file_name());
+                let dist = dist_info
+                    .and_then(|d| d.to_str())
+                    .and_then(|d| d.split('-').next())
+                    .unwrap_or_default();
+                normalize(dist) == self.name
+            })
+            .find_map(|path| {
+                console_scripts(&fs::read_to_string(path).ok()?)
+                    .into_iter()
+                    .next()
+            })
+            .unwrap_or_else(|| self.name.clone())
+    }
+}
+
+/// PEP 503: lowercase, with runs of `-`, `_` and `.` as one `-`
+fn normalize(name: &str) -> String {
+    Regex::new(r"[-_.]+")
+        .unwrap()
+        .replace_all(&name.to_lowercase(), "-")
+        .to_string()
+}
+
+/// The `[console_scripts]` names of an entry_points.txt
+fn console_scripts(entry_points: &str) -> Vec<String> {
+    let mut in_section = false;
+    let mut scripts = vec![];
+    for line in entry_points.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_section = line == "[console_scripts]";
+        } else if let (true, Some((name, _))) = (in_section, line.split_once('=')) {
+            scripts.push(name.trim().to_string());
+        }
+    }
+    scripts
+}
+
+/// PEP 691's JSON take on the simple index, much smaller than /pypi/<name>/json
+const SIMPLE_JSON: &str = "application/vnd.pypi.simple.v1+json";
+
+#[derive(Deserialize)]
+struct SimpleProject {
+    versions: Vec<String>,
+    #[serde(default)]
+    files: Vec<SimpleFile>,
+}
+
+#[derive(Deserialize)]
+struct SimpleFile {
+    filename: String,
+    #[serde(rename = "requires-python")]
+    requires_python: Option<String>,
+}
+
+/// The release a wheel or sdist file name is for
+fn file_version(filename: &str) -> Option<&str> {
+    if let Some(wheel) = filename.strip_suffix(".whl") {
+        return wheel.split('-').nth(1);
+    }
+    let sdist = filename
+        .strip_suffix(".tar.gz")
+        .or_else(|| filename.strip_suffix(".zip"))?;
+    sdist.rsplit_once('-').map(|(_, version)| version)
+}
+
+/// The leading numbers of a version, `3.12.*` gives [3, 12]
+fn numbers(version: &str) -> Vec<u64> {
+    version
+        .split('.')
+        .map_while(|part| part.parse().ok())
+        .collect()
+}
+
+/// Whether `python` (major, minor, patch) meets a `Requires-Python` such as
+/// ">=3.8, !=3.9.*". Clauses gg can't read don't rule anything out.
+fn python_allowed(requires: &str, python: &[u64]) -> bool {
+    requires.split(',').map(str::trim).all(|clause| {
+        let Some(op_end) = clause.find(|c: char| c.is_ascii_digit()) else {
+            return true;
+        };
+        let (op, version) = clause.split_at(op_end);
+        let wanted = numbers(version.trim());
+        if wanted.is_empty() {
+            return true;
+        }
+        let len = python.len().max(wanted.len());
+        let pad = |v: &[u64]| {
+            let mut v = v.to_vec();
+            v.resize(len, 0);
+            v
+        };
+        let cmp = pad(python).cmp(&pad(&wanted));
+        let prefix = python.iter().take(wanted.len()).eq(wanted.iter());
+        match (op.trim(), version.trim().ends_with(".*")) {
+            ("==", true) => prefix,
+            ("!=", true) => !prefix,
+            ("==" | "===", false) => cmp.is_eq(),
+            ("!=", false) => cmp.is_ne(),
+            (">=", _) => cmp.is_ge(),
+            ("<=", _) => cmp.is_le(),
+            (">", _) => cmp.is_gt(),
+            ("<", _) => cmp.is_lt(),
+            ("~=", _) => {
+                let head = &wanted[..wanted.len().saturating_sub(1)];
+                cmp.is_ge() && python.iter().take(head.len()).eq(head.iter())
+            }
+            _ => true,
+        }
+    })
+}
+
+/// The package's releases, pre- and post-releases left out, each on the
+/// newest of `pythons` its files' `Requires-Python` allows. Releases no
+/// Python here can run are left out.
+fn pair_with_pythons(project: &SimpleProject, mut pythons: Vec<Download>) -> Vec<Download> {
+    pythons.sort_by_key(|d| std::cmp::Reverse(d.version.as_ref().map(|v| v.to_version())));
+    let release = Regex::new(r"^\d+(\.\d+)*$").unwrap();
+    project
+        .versions
+        .iter()
+        .filter(|version| release.is_match(version))
+        .filter_map(|version| {
+            let files = project
+                .files
+                .iter()
+                .filter(|f| file_version(&f.filename) == Some(version.as_str()))
+                .collect::<Vec<_>>();
+            let python = pythons.iter().find(|python| {
+                let Some(python) = &python.version else {
+                    return false;
+                };
+                let python = python.to_version();
+                let python = [python.major, python.minor, python.patch];
+                files.is_empty()
+                    || files.iter().any(|f| {
+                        f.requires_python
+                            .as_deref()
+                            .is_none_or(|requires| python_allowed(requires, &python))
+                    })
+            })?;
+            Some(Download {
+                version: GgVersion::new(version),
+                ..python.clone()
+            })
+        })
+        .collect()
+}
+
+async fn pip_package_downloads(spec: &PipPackageSpec, pythons: Vec<Download>) -> Vec<Download> {
+    if pythons.is_empty() {
+        return vec![];
+    }
+    let url = format!("https://pypi.org/simple/{}/", spec.name);
+    let Some(project) = fetch_json_accepting::<SimpleProject>(&url, SIMPLE_JSON).await else {
+        return vec![];
+    };
+    pair_with_pythons(&project, pythons)
 }
 
 const METADATA_URL: &str =
@@ -147,7 +357,7 @@ async fn get_python_urls(target: &Target) -> Vec<Download> {
 // uv's metadata is on their moving main branch - a rename or blip should leave
 // "no python found", not panic every gg python user.
 async fn fetch_metadata() -> Option<String> {
-    match reqwest::get(METADATA_URL)
+    match reqwest::get(crate::mirror::mirrored(METADATA_URL))
         .await
         .and_then(|r| r.error_for_status())
     {
@@ -165,13 +375,22 @@ impl Executor for Python {
     }
 
     fn get_download_urls<'a>(
-        &self,
+        &'a self,
         input: &'a AppInput,
     ) -> Pin<Box<dyn Future<Output = Vec<Download>> + 'a>> {
-        Box::pin(async move { get_python_urls(&input.target).await })
+        Box::pin(async move {
+            let pythons = get_python_urls(&input.target).await;
+            match &self.pip_package {
+                Some(spec) => pip_package_downloads(spec, pythons).await,
+                None => pythons,
+            }
+        })
     }
 
     fn get_bins(&self, input: &AppInput) -> Vec<BinPattern> {
+        if let Some(spec) = &self.pip_package {
+            return venv_bin_patterns(&input.target.os, &spec.name);
+        }
         match &input.target.os {
             Os::Windows => vec![BinPattern::Exact("python.exe".to_string())],
             _ => vec![
@@ -181,13 +400,90 @@ impl Executor for Python {
         }
     }
 
+    fn get_bins_for_path(&self, input: &AppInput, app_path: &AppPath) -> Vec<BinPattern> {
+        match &self.pip_package {
+            Some(spec) => {
+                venv_bin_patterns(&input.target.os, &spec.installed_bin(&app_path.install_dir))
+            }
+            None => self.get_bins(input),
+        }
+    }
+
     fn get_name(&self) -> &str {
-        "python"
+        match &self.pip_package {
+            Some(spec) => &spec.name,
+            None => "python",
+        }
     }
 
     fn get_bin_dirs(&self) -> Vec<String> {
         // unix: bin/python3, bin/pip; windows: python.exe at root, Scripts/pip.exe
-        vec!["bin".to_string(), ".".to_string(), "Scripts".to_string()]
+        let dirs = vec!["bin".to_string(), ".".to_string(), "Scripts".to_string()];
+        if self.pip_package.is_some() {
+            // The package's console scripts (and the venv's python) first
+            let venv = vec!["venv/bin".to_string(), "venv/Scripts".to_string()];
+            return venv.into_iter().chain(dirs).collect();
+        }
+        dirs
+    }
+
+    fn post_prep(&self, cache_path: &str) -> Result<(), String> {
+        let Some(spec) = &self.pip_package else {
+            return Ok(());
+        };
+        let cache = Path::new(cache_path);
+        let venv = cache.join("venv");
+        let (python, pip) = if cfg!(windows) {
+            (
+                cache.join("python.exe"),
+                venv.join("Scripts").join("pip.exe"),
+            )
+        } else {
+            (
+                cache.join("bin").join("python3"),
+                venv.join("bin").join("pip"),
+            )
+        };
+
+        // The version the download was picked for, or whatever is latest
+        let package = fs::read_to_string(cache.join("gg-meta.json"))
+            .ok()
+            .and_then(|json| serde_json::from_str::<GgMeta>(&json).ok())
+            .and_then(|meta| meta.download.version)
+            .map(|version| format!("{}=={}", spec.package, version))
+            .unwrap_or_else(|| spec.package.clone());
+
+        info!("Creating venv for {} in {:?}", package, venv);
+        let status = std::process::Command::new(&python)
+            .arg("-m")
+            .arg("venv")
+            .arg(&venv)
+            .status();
+        match status {
+            Ok(s) if s.success() => {}
+            Ok(s) => return Err(format!("python -m venv failed: {}", s)),
+            Err(e) => return Err(format!("python -m venv failed: {}", e)),
+        }
+
+        // pip's download cache stays in gg's cache dir too
+        let status = std::process::Command::new(&pip)
+            .arg("install")
+            .arg("--disable-pip-version-check")
+            .arg(&package)
+            .env("PIP_CACHE_DIR", cache.join("pip_cache"))
+            .status();
+        match status {
+            Ok(s) if s.success() => Ok(()),
+            Ok(s) => Err(format!("pip install {} failed: {}", package, s)),
+            Err(e) => Err(format!("pip install {} failed: {}", package, e)),
+        }
+    }
+}
+
+fn venv_bin_patterns(os: &Os, bin: &str) -> Vec<BinPattern> {
+    match os {
+        Os::Windows => vec![BinPattern::Exact(format!("venv/Scripts/{bin}.exe"))],
+        _ => vec![BinPattern::Exact(format!("venv/bin/{bin}"))],
     }
 }
 
@@ -307,6 +603,50 @@ mod tests {
         assert!(versions.contains(&"3.11.9".to_string()));
     }
 
+    #[test]
+    fn test_pip_package_spec() {
+        let spec = PipPackageSpec::from_cmd("pip:Black[d]").unwrap();
+        assert_eq!(spec.name, "black");
+        assert_eq!(spec.package, "Black[d]");
+        assert_eq!(
+            PipPackageSpec::from_cmd("pip:ruamel.yaml").unwrap().name,
+            "ruamel-yaml"
+        );
+        assert_eq!(PipPackageSpec::from_cmd("pip:"), None);
+        assert_eq!(PipPackageSpec::from_cmd("pip:../evil"), None);
+        assert_eq!(PipPackageSpec::from_cmd("python"), None);
+    }
+
+    #[test]
+    fn test_console_scripts() {
+        let entry_points = "[console_scripts]\nhttp = httpie.__main__:main\nhttps = httpie.__main__:main_https\n\n[httpie.plugins]\nx = y\n";
+        assert_eq!(console_scripts(entry_points), vec!["http", "https"]);
+        assert!(console_scripts("[gui_scripts]\na = b:c\n").is_empty());
+    }
+
+    #[test]
+    fn test_installed_bin() {
+        let dir = tempfile::tempdir().unwrap();
+        let spec = PipPackageSpec::from_cmd("pip:httpie").unwrap();
+        assert_eq!(spec.installed_bin(dir.path()), "httpie");
+
+        let dist_info = dir
+            .path()
+            .join("venv/lib/python3.12/site-packages/httpie-3.2.2.dist-info");
+        fs::create_dir_all(&dist_info).unwrap();
+        fs::write(
+            dist_info.join("entry_points.txt"),
+            "[console_scripts]\nhttp = httpie.__main__:main\n",
+        )
+        .unwrap();
+        assert_eq!(spec.installed_bin(dir.path()), "http");
+
+        // A script named after the package wins
+        fs::create_dir_all(dir.path().join("venv/bin")).unwrap();
+        fs::write(dir.path().join("venv/bin/httpie"), "").unwrap();
+        assert_eq!(spec.installed_bin(dir.path()), "httpie");
+    }
+
     #[test]
     fn test_select_dedups_same_version_prefers_stripped() {
         // Same 3.12.3 as plain and stripped - only one Download should survive,
@@ -320,4 +660,57 @@ mod tests {
         assert_eq!(downloads.len(), 1, "same version must collapse to one");
         assert!(downloads[0].download_url.contains("install_only_stripped"));
     }
+
+    #[test]
+    fn test_python_allowed() {
+        let py = |requires: &str, python: &[u64]| python_allowed(requires, python);
+        assert!(py(">=3.8", &[3, 12, 3]));
+        assert!(!py(">=3.8, <3.12", &[3, 12, 3]));
+        assert!(py(">=3.8,<3.13", &[3, 12, 3]));
+        assert!(!py("!=3.12.*", &[3, 12, 3]));
+        assert!(py("==3.12.*", &[3, 12, 3]));
+        assert!(py("~=3.9", &[3, 13, 0]));
+        assert!(!py("~=3.9.1", &[3, 10, 0]));
+        assert!(py(">3.12", &[3, 12, 1]));
+        assert!(py("", &[3, 12, 3]));
+        assert!(py(">=3.8 # odd", &[3, 12, 3]));
+    }
+
+    #[test]
+    fn test_pip_versions_paired_with_newest_allowed_python() {
+        let project: SimpleProject = serde_json::from_str(
+            r#"{
+              "versions": ["1.0", "2.0", "3.0b1", "4.0"],
+              "files": [
+                {"filename": "tool-1.0.tar.gz", "requires-python": ">=3.6,<3.12"},
+                {"filename": "tool-2.0-py3-none-any.whl", "requires-python": ">=3.8"},
+                {"filename": "tool-4.0-py3-none-any.whl", "requires-python": ">=3.14"}
+              ]
+            }"#,
+        )
+        .unwrap();
+        let pythons = ["3.11.9", "3.13.1", "3.12.3"]
+            .iter()
+            .map(|v| Download::new(format!("https://example.com/{v}.tar.gz"), v, None))
+            .collect();
+        let pairs = pair_with_pythons(&project, pythons)
+            .into_iter()
+            .map(|d| (d.version.unwrap().to_string(), d.download_url))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            pairs,
+            vec![
+                (
+                    "1.0.0".to_string(),
+                    "https://example.com/3.11.9.tar.gz".to_string()
+                ),
+                (
+                    "2.0.0".to_string(),
+                    "https://example.com/3.13.1.tar.gz".to_string()
+                ),
+            ]
+        );
+        assert_eq!(file_version("ruamel.yaml-0.18.6.tar.gz"), Some("0.18.6"));
+        assert_eq!(file_version("tool-2.0-1-py3-none-any.whl"), Some("2.0"));
+    }
 }
@@ -68,7 +68,7 @@ impl Executor for Rat {
         "rat"
     }
 
-    fn post_prep(&self, cache_path: &str) {
+    fn post_prep(&self, cache_path: &str) -> Result<(), String> {
         let patterns = [
             format!("{}/*.bin", cache_path),
             format!("{}/*.exe", cache_path),
@@ -98,5 +98,6 @@ impl Executor for Rat {
                 }
             }
         }
+        Ok(())
     }
 }
@@ -167,7 +167,7 @@ impl Executor for Ruby {
         vec![".".to_string(), "bin".to_string()]
     }
 
-    fn post_prep(&self, cache_path: &str) {
+    fn post_prep(&self, cache_path: &str) -> Result<(), String> {
         #[cfg(unix)]
         {
             let ruby_bin_dir = std::path::Path::new(cache_path).join("bin");
@@ -208,6 +208,7 @@ impl Executor for Ruby {
                 }
             }
         }
+        Ok(())
     }
 }
 
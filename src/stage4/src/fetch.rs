@@ -19,7 +19,26 @@ static CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
 /// GET a URL and hand back the body. Someone else's index being down is a bad day, not
 /// a reason to panic. Warn level, so the reason shows up without -v.
 pub async fn fetch_text(url: &str) -> Option<String> {
-    let response = match CLIENT.get(url).send().await {
+    fetch_text_accepting(url, None).await
+}
+
+async fn fetch_text_accepting(url: &str, accept: Option<&str>) -> Option<String> {
+    let via_mirror = crate::mirror::mirrored(url);
+    if via_mirror != url {
+        if let Some(text) = fetch_text_from(&via_mirror, accept).await {
+            return Some(text);
+        }
+        warn!("Mirror could not get {url}, trying it directly");
+    }
+    fetch_text_from(url, accept).await
+}
+
+async fn fetch_text_from(url: &str, accept: Option<&str>) -> Option<String> {
+    let mut request = CLIENT.get(url);
+    if let Some(accept) = accept {
+        request = request.header(reqwest::header::ACCEPT, accept);
+    }
+    let response = match request.send().await {
         Ok(response) => response,
         Err(e) => {
             warn!("Could not reach {url}: {e}");
@@ -48,8 +67,16 @@ pub async fn fetch_text(url: &str) -> Option<String> {
 
 /// Same deal for the endpoints handing back JSON.
 pub async fn fetch_json<T: DeserializeOwned>(url: &str) -> Option<T> {
-    let text = fetch_text(url).await?;
+    parse_json(url, fetch_text(url).await?)
+}
+
+/// `fetch_json` asking for a particular content type. The npm registry hands
+/// out a far smaller "abbreviated" package document when asked for one.
+pub async fn fetch_json_accepting<T: DeserializeOwned>(url: &str, accept: &str) -> Option<T> {
+    parse_json(url, fetch_text_accepting(url, Some(accept)).await?)
+}
 
+fn parse_json<T: DeserializeOwned>(url: &str, text: String) -> Option<T> {
     match serde_json::from_str(&text) {
         Ok(value) => Some(value),
         Err(e) => {
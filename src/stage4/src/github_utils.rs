@@ -5,6 +5,7 @@ use std::time::Duration;
 
 use log::debug;
 
+use crate::config::GgConfig;
 use crate::target::{Arch, Os};
 
 const DEFAULT_GITHUB_API_URL: &str = "https://api.github.com";
@@ -47,6 +48,10 @@ fn github_token() -> Option<&'static str> {
                 debug!("Using GitHub token from environment");
                 return Some(token);
             }
+            if let Some((token, file)) = GgConfig::loaded().github_token() {
+                debug!("Using GitHub token from {}", file.display());
+                return Some(token.to_string());
+            }
             if let Some(token) = token_from_gh_cli() {
                 debug!("Using GitHub token from gh CLI");
                 return Some(token);
@@ -57,6 +62,31 @@ fn github_token() -> Option<&'static str> {
         .as_deref()
 }
 
+/// Where the token `create_github_client` would send comes from (an env var
+/// name, the user config or the gh CLI), for `gg doctor`.
+pub fn github_token_source() -> Option<String> {
+    let keys = ["GG_GITHUB_TOKEN", "GITHUB_TOKEN", "GH_TOKEN"];
+    let from_env = |keys: &[&str]| {
+        keys.iter()
+            .find(|key| first_nonempty([env::var(key).ok()]).is_some())
+            .map(|key| key.to_string())
+    };
+    if !is_default_github_host(&github_api_base_url()) {
+        return from_env(&keys[..1]);
+    }
+    github_token()?;
+    let from_config = || {
+        GgConfig::loaded()
+            .github_token()
+            .map(|(_, file)| format!("github.token in {}", file.display()))
+    };
+    Some(
+        from_env(&keys)
+            .or_else(from_config)
+            .unwrap_or_else(|| "gh auth token".to_string()),
+    )
+}
+
 fn normalize_base_url(raw: Option<String>) -> String {
     raw.map(|v| v.trim().trim_end_matches('/').to_string())
         .filter(|v| !v.is_empty())
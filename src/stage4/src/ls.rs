@@ -0,0 +1,165 @@
+use std::collections::BTreeSet;
+use std::process::ExitCode;
+
+use crate::cache::{self, ago, format_date, human_size, CacheEntry};
+use crate::target::{Arch, Os};
+use crate::tools::canonical_name;
+
+fn target(os: Option<Os>, arch: Option<Arch>) -> String {
+    match (os, arch) {
+        (None | Some(Os::Any), None | Some(Arch::Any)) => "any".to_string(),
+        (os, arch) => format!(
+            "{}-{}",
+            os.map(|o| format!("{:?}", o)).unwrap_or("any".to_string()),
+            arch.map(|a| format!("{:?}", a))
+                .unwrap_or("any".to_string())
+        )
+        .to_lowercase(),
+    }
+}
+
+/// The name the user would type. The cache dir is named after the executor,
+/// which isn't always the same (gh lives in `cli`).
+fn tool_name(entry: &CacheEntry) -> String {
+    entry
+        .meta
+        .as_ref()
+        .map(|m| canonical_name(&m.cmd.cmd))
+        .unwrap_or_else(|| entry.tool.clone())
+}
+
+fn row(entry: &CacheEntry, size: u64, now: u64) -> Vec<String> {
+    let dash = || "-".to_string();
+    let size = human_size(size);
+    let installed = cache::installed_at(&entry.path)
+        .map(format_date)
+        .unwrap_or_else(dash);
+    let last_used = cache::last_used(&entry.path)
+        .map(|t| ago(t, now))
+        .unwrap_or_else(dash);
+    let Some(meta) = &entry.meta else {
+        let dir = entry
+            .path
+            .file_name()
+            .map(|d| d.to_string_lossy().to_string())
+            .unwrap_or_default();
+        return vec![
+            tool_name(entry),
+            format!("({})", dir),
+            "?".to_string(),
+            dash(),
+            dash(),
+            dash(),
+            size,
+            installed,
+            last_used,
+        ];
+    };
+    let mut tags = meta.download.tags.iter().cloned().collect::<Vec<_>>();
+    tags.sort();
+    vec![
+        tool_name(entry),
+        meta.version_req.to_string(),
+        meta.download
+            .version
+            .as_ref()
+            .map(|v| v.to_string())
+            .unwrap_or("?".to_string()),
+        meta.cmd.distribution.clone().unwrap_or_else(dash),
+        if tags.is_empty() {
+            dash()
+        } else {
+            tags.join(",")
+        },
+        target(meta.download.os, meta.download.arch),
+        size,
+        installed,
+        last_used,
+    ]
+}
+
+fn print_table(rows: &[Vec<String>]) {
+    let mut widths = vec![0; rows[0].len()];
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+    for row in rows {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ");
+        println!("{}", line.trim_end());
+    }
+}
+
+/// `gg ls [tool]`: what is in the cache
+pub fn run(tool: Option<&str>) -> ExitCode {
+    let dir = cache::cache_dir();
+    let tool = tool.map(canonical_name);
+    let entries = cache::entries(&dir)
+        .into_iter()
+        .filter(|e| {
+            tool.as_ref()
+                .is_none_or(|t| *t == tool_name(e) || *t == e.tool)
+        })
+        .collect::<Vec<_>>();
+    if entries.is_empty() {
+        match tool {
+            Some(tool) => println!("{} is not installed in {}", tool, dir.display()),
+            None => println!("Nothing installed in {}", dir.display()),
+        }
+        return ExitCode::from(0);
+    }
+
+    let now = cache::now();
+    let header = [
+        "TOOL",
+        "SELECTOR",
+        "VERSION",
+        "DISTRIBUTION",
+        "TAGS",
+        "TARGET",
+        "SIZE",
+        "INSTALLED",
+        "LAST USED",
+    ];
+    let mut rows = vec![header.iter().map(|h| h.to_string()).collect::<Vec<_>>()];
+    let sizes = entries
+        .iter()
+        .map(|e| cache::dir_size(&e.path))
+        .collect::<Vec<_>>();
+    rows.extend(
+        entries
+            .iter()
+            .zip(&sizes)
+            .map(|(e, size)| row(e, *size, now)),
+    );
+    print_table(&rows);
+
+    let tools = entries.iter().map(tool_name).collect::<BTreeSet<_>>();
+    println!(
+        "\n{} versions of {} tools, {} in {}",
+        entries.len(),
+        tools.len(),
+        human_size(sizes.iter().sum()),
+        dir.display()
+    );
+    ExitCode::from(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target() {
+        assert_eq!(target(Some(Os::Any), Some(Arch::Any)), "any");
+        assert_eq!(target(None, None), "any");
+        assert_eq!(target(Some(Os::Linux), Some(Arch::X86_64)), "linux-x86_64");
+        assert_eq!(target(Some(Os::Mac), None), "mac-any");
+    }
+}
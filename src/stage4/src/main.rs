@@ -1,38 +1,50 @@
-use std::collections::HashMap;
 use std::env;
 use std::fs;
+use std::path::Path;
 use std::process::ExitCode;
 
-use futures_util::future::join_all;
-use indicatif::MultiProgress;
 use log::{debug, info, LevelFilter};
 
-use crate::barus::create_barus;
 use crate::cli::Cli;
 use crate::config::GgConfig;
-use crate::executor::{prep, try_run, AppInput, Executor, ExecutorCmd, GgVersionReq};
+use crate::executor::{try_run, AppInput, Executor};
+use crate::prepare::{executors_for, prep_all, with_deps};
+use crate::shell::Shell;
 use crate::target::Target;
 use clap::Parser;
 
+mod alias;
 mod barus;
 mod bloody_indiana_jones;
 mod bloody_maven;
+mod bundle;
+mod cache;
 mod checker;
 mod cleaner;
 mod cli;
 mod config;
+mod config_check;
+mod config_edit;
+mod doctor;
 mod executor;
 mod executors;
 mod fetch;
 mod gem_utils;
 mod github_utils;
+mod ls;
+mod manifest;
+mod mirror;
+mod prepare;
+mod prune;
+mod shell;
+mod shims;
 mod target;
 mod tools;
 mod updater;
 
-use crate::tools::{canonical_name, get_all_tools, get_tool_info, registry_name, ToolCategory};
+use crate::tools::{get_all_tools, get_tool_info, ToolCategory};
 
-fn print_help(ver: &str) {
+fn print_help(ver: &str, config: &GgConfig) {
     println!(
         r"
 https://github.com/eirikb/gg
@@ -60,9 +72,32 @@ Built in commands:
     update <tool> -u -f Force update specific tool even if up to date (e.g., update gg -u -f)
     help            Print help
     tools           List all available tools
+    ls              List installed tools with version, size, install date and last use
+    ls <tool>       Same, for one tool (e.g., ls java)
+    verify          Check gg.toml tools (or all, --all) for modified, missing or extra files
+    verify <tool>   Same, for one tool (e.g., verify java); --repair reinstalls broken ones
+    bundle create -o <file>  Pack gg.toml tools into one archive (e.g., bundle create --for linux-x86_64 -o tools.tar.zst)
+    bundle import <file>     Unpack a bundle into the cache, for machines without internet
     clean-cache     Clean cache (prompts for confirmation)
-    config init     Create a new gg.toml configuration file
+    prune           Remove some of the cache: --older-than 30d, --keep-latest N, --max-size 10G,
+                    --tool <tool>, --dry-run, --yes (e.g., prune --older-than 30d --dry-run)
+    config init     Create a new gg.toml configuration file, with the tools the project uses
+    config add <tool@version>  Add dependencies to gg.toml (e.g., config add node@^20 java@17)
+    config remove <name>       Remove a dependency or alias from gg.toml
+    config alias <name> <cmd>  Add or change an alias (e.g., config alias build 'gradle clean build')
+    config set <key> <value>   Set any value (e.g., config set env.GRADLE_OPTS -Xmx1g)
+    config check    Check gg.toml for unknown keys, tools, bad versions and alias loops
     config show     Show current configuration
+    config show --resolved  Show the merged configuration and where each value came from
+    install         Download everything in gg.toml without running it
+    install <tool>  Download specific tools (e.g., install node@20 java@21-azul --os windows)
+    aliases         List aliases from gg.toml
+    shims           Create launchers for gg.toml tools and aliases in .gg/bin
+    shims --global  Same, but in ~/.local/bin
+    doctor          Show target, cache, GitHub token and rate limit, network and version files
+    serve           Serve downloads as a pull-through mirror (--port 8080, --bind 0.0.0.0 to share it)
+    shell           Start a shell with all gg.toml dependencies on PATH
+    activate <sh>   Print a hook that loads gg.toml dependencies on cd (bash, zsh, fish, pwsh)
 
 Update options:
     -u              Actually perform the update (vs just checking)
@@ -105,19 +140,23 @@ Example tools:
 
 Run 'gg tools' to see all available tools with descriptions
 
-GitHub repos can be accessed directly:
+GitHub repos and packages can be accessed directly:
     gh/<owner>/<repo>    Any GitHub release (e.g. gh/cli/cli)
+    npm:<package>        Any npm package, run on Node.js (e.g. npm:prettier@3)
+    pip:<package>        Any PyPI package, in a venv of its own (e.g. pip:black@24)
 
 Available tags by tools:
-    java: +jdk, +jre, +lts, +sts, +mts, +ea, +ga, +headless, +headfull, +fx, +normal, +hotspot (defaults: +jdk, +ga)
-    node: +lts
+    java: +jdk, +jre, +lts, +sts, +mts, +ea, +ga, +headless, +headfull, +fx, +normal, +hotspot, +openj9 (defaults: +jdk, +ga)
+    node: +lts, +<lts codename> (e.g. +iron)
     go: +beta (excluded by default)
+    gradle: +bin, +all, +rc, +milestone, +nightly (only +bin by default)
     openapi: +beta (excluded by default)
 "
     );
+    alias::print_project_aliases(config);
 }
 
-fn print_tools() {
+fn print_tools(config: &GgConfig) {
     println!("Available tools in gg:\n");
 
     let tools = get_all_tools();
@@ -168,8 +207,12 @@ fn print_tools() {
         println!();
     }
 
-    println!("GitHub repos can be accessed directly:");
+    alias::print_project_aliases(config);
+
+    println!("GitHub repos and packages can be accessed directly:");
     println!("    gh/<owner>/<repo>    Any GitHub release (e.g. gh/cli/cli)");
+    println!("    npm:<package>        Any npm package, run on Node.js (e.g. npm:prettier@3)");
+    println!("    pip:<package>        Any PyPI package, in a venv of its own (e.g. pip:black@24)");
     println!("\nFor more information about a specific tool, use 'gg tools <tool_name>'");
 }
 
@@ -191,21 +234,6 @@ fn print_tool_info(tool: &tools::ToolInfo) {
     }
 }
 
-/// Is this dependency already covered? Registry names on both sides: gh
-/// depends on "git" but a built git executor calls itself "portable-git", so
-/// comparing those raw built a second executor over the same cache dir and
-/// prepped both at once.
-fn dep_satisfied(
-    executors: &[Box<dyn Executor>],
-    to_add: &[Box<dyn Executor>],
-    processed: &std::collections::HashSet<String>,
-    dep_name: &str,
-) -> bool {
-    executors.iter().any(|e| registry_name(&**e) == dep_name)
-        || to_add.iter().any(|e| registry_name(&**e) == dep_name)
-        || processed.contains(dep_name)
-}
-
 #[tokio::main]
 async fn main() -> ExitCode {
     let ver = option_env!("VERSION").unwrap_or("dev");
@@ -239,17 +267,28 @@ async fn main() -> ExitCode {
         }
     });
 
-    env::set_var("GG_CACHE_DIR", &cache_base_dir);
-    info!("Using cache directory: {}", cache_base_dir);
     let system = fs::read_to_string(format!("{}/gg-{ver}/system", cache_base_dir))
         .unwrap_or(String::from("x86_64-linux"))
         .trim()
         .to_string();
+    let host = Target::parse_with_overrides(&system, None, None);
     let target =
         Target::parse_with_overrides(&system, cli.override_os.clone(), cli.override_arch.clone());
+    env::set_var("GG_CACHE_DIR", &cache_base_dir);
+    cache::set_target(&host.name(), &target.name());
+    info!("Using cache directory: {}", cache::install_dir().display());
 
     let config = GgConfig::load();
+    config.set_loaded();
+    let cli = if cli::prefer_aliases(&mut raw_args, &config) {
+        Cli::parse_from(&raw_args)
+    } else {
+        cli
+    };
     let (cmds, app_args) = cli.parse_args(&config);
+    if cmds.first().map(|c| c.cmd.as_str()) != Some("config-check") {
+        config_check::report(&config);
+    }
 
     let input = &AppInput {
         target,
@@ -262,50 +301,22 @@ async fn main() -> ExitCode {
     }
 
     if cli.help {
-        print_help(ver);
+        print_help(ver, &config);
         return ExitCode::from(0);
     }
 
     debug!(target: "main", "{:?}", &cli);
 
     if let Some(cmd) = cmds.first() {
-        if cmd.cmd.starts_with("__multi_alias__") {
-            let alias_name = cmd.cmd.strip_prefix("__multi_alias__").unwrap();
-            if let Some(alias_commands) = config.resolve_alias_with_and(alias_name) {
-                for command_args in alias_commands {
-                    println!("Executing: {}", command_args.join(" "));
-
-                    if command_args.is_empty() {
-                        continue;
-                    }
-
-                    let mut gg_args = vec![env::args().next().unwrap_or_else(|| {
-                        env::current_exe().unwrap().to_string_lossy().to_string()
-                    })];
-                    gg_args.extend(command_args.iter().map(|s| s.to_string()));
-
-                    let status = std::process::Command::new(&gg_args[0])
-                        .args(&gg_args[1..])
-                        .status();
-
-                    match status {
-                        Ok(exit_status) => {
-                            if !exit_status.success() {
-                                println!("Command failed: {}", command_args.join(" "));
-                                return ExitCode::from(exit_status.code().unwrap_or(1) as u8);
-                            }
-                        }
-                        Err(e) => {
-                            println!("Failed to execute command: {}", e);
-                            return ExitCode::from(1);
-                        }
-                    }
-                }
-                return ExitCode::from(0);
-            }
+        if let Some(alias_name) = cmd.cmd.strip_prefix("__multi_alias__") {
+            return alias::run(&config, alias_name, &app_args);
         }
 
         match cmd.cmd.as_str() {
+            "help" => {
+                print_help(ver, &config);
+                return ExitCode::from(0);
+            }
             "update" => {
                 let tool_name = app_args.first().cloned();
                 let should_update = cli.get_update_flag();
@@ -363,10 +374,37 @@ async fn main() -> ExitCode {
                         println!("\nRun 'gg tools' to see all available tools");
                     }
                 } else {
-                    print_tools();
+                    print_tools(&config);
                 }
                 return ExitCode::from(0);
             }
+            "ls" => {
+                return ls::run(app_args.first().map(String::as_str));
+            }
+            "prune" => {
+                return prune::run(&cli.get_prune_args());
+            }
+            "verify" => {
+                let (all, repair) = cli.get_verify_flags();
+                return manifest::run(input, &config, &app_args, all, repair).await;
+            }
+            "bundle-create" => {
+                let (target, output) = cli.get_bundle_create_args();
+                return bundle::create(
+                    input,
+                    Path::new(&cache_base_dir),
+                    &host,
+                    &config,
+                    &app_args,
+                    target.as_deref(),
+                    &output,
+                )
+                .await;
+            }
+            "bundle-import" => {
+                let file = app_args.first().cloned().unwrap_or_default();
+                return bundle::import(Path::new(&cache_base_dir), &host, &file).await;
+            }
             "clean-cache" => {
                 if let Err(e) = cleaner::clean_cache() {
                     println!("Error: {}", e);
@@ -381,13 +419,69 @@ async fn main() -> ExitCode {
                 }
                 return ExitCode::from(0);
             }
+            "config-add" | "config-remove" | "config-alias" | "config-set" => {
+                let action = cmd.cmd.trim_start_matches("config-");
+                return config_edit::run(action, &app_args);
+            }
+            "config-check" => {
+                return config_check::run(&config);
+            }
             "config-show" => {
-                if let Err(e) = config.show_config() {
+                if let Err(e) = config.show_config(app_args.iter().any(|a| a == "--resolved")) {
+                    println!("Error: {}", e);
+                    return ExitCode::from(1);
+                }
+                return ExitCode::from(0);
+            }
+            "install" => {
+                return prepare::install(input, &config, &app_args).await;
+            }
+            "aliases" => {
+                alias::print_aliases(&config);
+                return ExitCode::from(0);
+            }
+            "shims" => {
+                if let Err(e) = shims::create_shims(&config, cli.get_global_flag()) {
                     println!("Error: {}", e);
                     return ExitCode::from(1);
                 }
                 return ExitCode::from(0);
             }
+            "doctor" => {
+                return doctor::run(
+                    input,
+                    &config,
+                    ver,
+                    cli.override_os.as_deref(),
+                    cli.override_arch.as_deref(),
+                )
+                .await;
+            }
+            "serve" => {
+                let (bind, port) = cli.get_serve_args();
+                return mirror::serve(&bind, port).await;
+            }
+            "shell" => {
+                return shell::run_shell(input, &config).await;
+            }
+            "activate" | "hook-env" => {
+                let name = app_args.first().cloned().unwrap_or_default();
+                let Some(sh) = Shell::parse(&name) else {
+                    println!(
+                        "Unsupported shell '{}'. Use one of: bash, zsh, fish, pwsh",
+                        name
+                    );
+                    return ExitCode::from(1);
+                };
+                if cmd.cmd == "activate" {
+                    print!("{}", shell::activate_script(sh));
+                } else if let Err(e) = shell::hook_env(sh, input, &config).await {
+                    // stdout is being eval'd by the shell
+                    eprintln!("{}", e);
+                    return ExitCode::from(1);
+                }
+                return ExitCode::from(0);
+            }
             _ => {}
         };
     }
@@ -401,117 +495,23 @@ async fn main() -> ExitCode {
     info!("System is {system}{}. {:?}", override_info, &target);
 
     if !cmds.is_empty() {
-        let mut executors = cmds
-            .iter()
-            .filter_map(|cmd| {
-                <dyn Executor>::new(ExecutorCmd {
-                    cmd: cmd.cmd.to_string(),
-                    version: GgVersionReq::new(
-                        cmd.version.clone().unwrap_or("".to_string()).as_str(),
-                    ),
-                    distribution: cmd.distribution.clone(),
-                    include_tags: cmd.include_tags.clone(),
-                    exclude_tags: cmd.exclude_tags.clone(),
-                    gems: cmd.gems.clone(),
-                })
-            })
-            .collect::<Vec<Box<dyn Executor>>>();
-
-        let mut look_for_deps = true;
-        let mut processed_deps = std::collections::HashSet::new();
-        while look_for_deps {
-            look_for_deps = false;
-            let mut to_add: Vec<Box<dyn Executor>> = Vec::new();
-            for x in &executors {
-                let deps = x.get_deps(input).await;
-                for dep in deps {
-                    let dep_name = canonical_name(&dep.name);
-                    if !dep_satisfied(&executors, &to_add, &processed_deps, &dep_name) {
-                        if dep.optional {
-                            if which::which(&dep.name).is_ok() {
-                                info!(
-                                    "Optional dependency '{}' found in PATH, using system version",
-                                    dep.name
-                                );
-                                processed_deps.insert(dep_name.clone());
-                                continue;
-                            } else {
-                                info!(
-                                    "Optional dependency '{}' not found in PATH, falling back to managed version",
-                                    dep.name
-                                );
-                            }
-                        }
-
-                        if let Some(e) = <dyn Executor>::new(ExecutorCmd {
-                            cmd: dep.name.clone(),
-                            version: dep.version.as_ref().and_then(|v| GgVersionReq::new(v)),
-                            distribution: None,
-                            include_tags: Default::default(),
-                            exclude_tags: Default::default(),
-                            gems: None,
-                        }) {
-                            look_for_deps = true;
-                            processed_deps.insert(dep_name.clone());
-                            to_add.push(e);
-                        }
-                    }
-                }
-            }
-            for x in to_add {
-                executors.push(x);
-            }
-        }
+        let executors = with_deps(executors_for(&cmds), input).await;
 
         if !executors.is_empty() {
-            let mut env_vars: HashMap<String, String> = HashMap::new();
-            let mut path_vars: Vec<String> = vec![];
-
-            let m = MultiProgress::new();
-
-            let alles = executors
-                .iter()
-                .enumerate()
-                .map(|(i, x)| {
-                    let pb = create_barus();
-                    let pb = m.insert(i, pb);
-                    (x, pb)
-                })
-                .map(|(x, pb)| async move {
-                    let app_path = prep(&**x, input, &pb).await?;
-                    let env = x.get_env(&app_path);
-                    let bin_dirs = x.get_bin_dirs();
-                    Ok::<_, String>((app_path, env, bin_dirs))
-                });
-            let res = join_all(alles).await;
-
-            res.iter().filter(|x| x.is_err()).for_each(|x| {
-                println!("Prep failed: {}", x.clone().err().unwrap());
-            });
-            if res.iter().any(|x| x.is_err()) {
-                return ExitCode::from(1);
-            }
-
-            let res = res.into_iter().filter_map(|x| x.ok()).collect::<Vec<_>>();
-
-            for (app_path, env, bin_dirs) in res.clone() {
-                for bin_dir in &bin_dirs {
-                    path_vars.push(
-                        app_path
-                            .install_dir
-                            .clone()
-                            .join(bin_dir)
-                            .to_str()
-                            .unwrap_or("")
-                            .to_string(),
-                    );
-                }
-                for (key, value) in env {
-                    env_vars.insert(key.to_string(), value.to_string());
+            let mut prepared = match prep_all(&executors, input).await {
+                Ok(prepared) => prepared,
+                Err(errors) => {
+                    for e in errors {
+                        println!("Prep failed: {}", e);
+                    }
+                    return ExitCode::from(1);
                 }
-            }
+            };
+            prepared.apply_config(&config, &executors);
+            let path_vars = prepared.path_vars;
+            let env_vars = prepared.env_vars;
 
-            let (app_path, _, _) = &res[0];
+            let app_path = &prepared.app_paths[0];
             let executor = &executors[0];
 
             info!("Path vars: {}", &path_vars.join(", "));
@@ -531,44 +531,7 @@ async fn main() -> ExitCode {
             ExitCode::from(1)
         }
     } else {
-        print_help(ver);
+        print_help(ver, &config);
         ExitCode::from(0)
     }
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    fn ex(name: &str) -> Box<dyn Executor> {
-        <dyn Executor>::new(ExecutorCmd {
-            cmd: name.to_string(),
-            version: None,
-            distribution: None,
-            include_tags: Default::default(),
-            exclude_tags: Default::default(),
-            gems: None,
-        })
-        .unwrap()
-    }
-
-    #[test]
-    fn test_dep_satisfied_matches_on_registry_name() {
-        let git = vec![ex("git")];
-        let empty: Vec<Box<dyn Executor>> = vec![];
-        let none = std::collections::HashSet::new();
-
-        // The gh -> git case, the one that used to build a second executor
-        assert!(dep_satisfied(&git, &empty, &none, "git"));
-        assert!(dep_satisfied(&empty, &git, &none, "git"));
-
-        // ...without swallowing a dep that really is missing
-        assert!(!dep_satisfied(&git, &empty, &none, "java"));
-        assert!(!dep_satisfied(&empty, &empty, &none, "git"));
-
-        // an optional dep taken from PATH is recorded here, canonically
-        let mut done = std::collections::HashSet::new();
-        done.insert("java".to_string());
-        assert!(dep_satisfied(&empty, &empty, &done, "java"));
-    }
-}
@@ -0,0 +1,395 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use indicatif::ProgressBar;
+use log::info;
+use serde::{Deserialize, Serialize};
+use sha256::try_digest;
+
+use crate::cache::{self, CacheEntry};
+use crate::config::GgConfig;
+use crate::executor::{cache_subdir, prep, AppInput, Executor, ExecutorCmd, GgVersionReq};
+use crate::prepare::{config_cmds, executors_for};
+use crate::tools::canonical_name;
+
+pub const MANIFEST_FILE: &str = "gg-manifest.json";
+
+/// gg's own files in a tool dir, not part of the tool
+const GG_FILES: &[&str] = &["gg-meta.json", MANIFEST_FILE, "gg-last-used"];
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct FileEntry {
+    pub size: u64,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub sha256: String,
+    /// Symlinks are recorded by target, not followed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+}
+
+/// Every file of an install as it was right after download, keyed by path
+/// relative to the tool dir (always with `/`)
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct Manifest {
+    pub files: BTreeMap<String, FileEntry>,
+}
+
+#[derive(Default, Debug)]
+pub struct Report {
+    pub modified: Vec<String>,
+    pub missing: Vec<String>,
+    /// Not necessarily foul play: tools write caches next to themselves
+    pub extra: Vec<String>,
+}
+
+impl Report {
+    pub fn broken(&self) -> bool {
+        !self.modified.is_empty() || !self.missing.is_empty()
+    }
+}
+
+fn walk(root: &Path, dir: &Path, out: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let metadata = fs::symlink_metadata(&path)?;
+        if metadata.is_dir() {
+            walk(root, &path, out)?;
+        } else if dir != root
+            || !GG_FILES.contains(&path.file_name().unwrap_or_default().to_str().unwrap_or(""))
+        {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn relative(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn file_entry(path: &Path) -> io::Result<FileEntry> {
+    let metadata = fs::symlink_metadata(path)?;
+    if metadata.file_type().is_symlink() {
+        return Ok(FileEntry {
+            size: 0,
+            sha256: String::new(),
+            link: Some(fs::read_link(path)?.to_string_lossy().to_string()),
+        });
+    }
+    Ok(FileEntry {
+        size: metadata.len(),
+        sha256: try_digest(path)?,
+        link: None,
+    })
+}
+
+pub fn build(dir: &Path) -> io::Result<Manifest> {
+    let mut paths = vec![];
+    walk(dir, dir, &mut paths)?;
+    let mut manifest = Manifest::default();
+    for path in paths {
+        manifest
+            .files
+            .insert(relative(dir, &path), file_entry(&path)?);
+    }
+    Ok(manifest)
+}
+
+/// Records what `dir` holds right now, for `gg verify` to compare against
+pub fn write(dir: &Path) -> Result<(), String> {
+    let manifest = build(dir).map_err(|e| e.to_string())?;
+    let json = serde_json::to_string(&manifest).map_err(|e| e.to_string())?;
+    fs::write(dir.join(MANIFEST_FILE), json).map_err(|e| e.to_string())
+}
+
+pub fn read(dir: &Path) -> Option<Manifest> {
+    let json = fs::read_to_string(dir.join(MANIFEST_FILE)).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// Compares `dir` against its manifest. Sizes first so only files that could
+/// be the same get hashed.
+pub fn verify(dir: &Path, manifest: &Manifest) -> Report {
+    let mut report = Report::default();
+    let mut paths = vec![];
+    let _ = walk(dir, dir, &mut paths);
+    let present = paths
+        .iter()
+        .map(|p| (relative(dir, p), p))
+        .collect::<BTreeMap<_, _>>();
+
+    for (name, expected) in &manifest.files {
+        match present.get(name) {
+            None => report.missing.push(name.clone()),
+            Some(path) => {
+                let same = match fs::symlink_metadata(path) {
+                    Ok(m) if m.file_type().is_symlink() || expected.link.is_some() => {
+                        file_entry(path).ok().as_ref() == Some(expected)
+                    }
+                    Ok(m) if m.len() != expected.size => false,
+                    Ok(_) => file_entry(path).ok().as_ref() == Some(expected),
+                    Err(_) => false,
+                };
+                if !same {
+                    report.modified.push(name.clone());
+                }
+            }
+        }
+    }
+    report.extra = present
+        .keys()
+        .filter(|name| !manifest.files.contains_key(*name))
+        .cloned()
+        .collect();
+    report
+}
+
+fn print_files(what: &str, files: &[String]) {
+    const MAX: usize = 10;
+    for file in files.iter().take(MAX) {
+        println!("        {}: {}", what, file);
+    }
+    if files.len() > MAX {
+        println!("        ... and {} more {}", files.len() - MAX, what);
+    }
+}
+
+fn entry_name(entry: &CacheEntry) -> String {
+    entry
+        .meta
+        .as_ref()
+        .map(|m| canonical_name(&m.cmd.cmd))
+        .unwrap_or_else(|| entry.tool.clone())
+}
+
+/// An executor that installs into `entry` again, as long as the version
+/// requirement it was installed for still points at the same dir
+fn reinstaller(entry: &CacheEntry, cache_dir: &Path) -> Option<Box<dyn Executor>> {
+    let meta = entry.meta.as_ref()?;
+    let executor = <dyn Executor>::new(ExecutorCmd {
+        version: GgVersionReq::new(&meta.version_req.to_string()),
+        ..meta.cmd.clone()
+    })?;
+    (cache_dir.join(cache_subdir(&*executor)) == entry.path).then_some(executor)
+}
+
+fn confirm(question: &str) -> bool {
+    if !io::stdin().is_terminal() {
+        return false;
+    }
+    print!("{} (y/N): ", question);
+    let _ = io::stdout().flush();
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer).is_ok()
+        && matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// The cache entries to check: the named tools, else what gg.toml depends on,
+/// else everything
+fn selected(
+    cache_dir: &Path,
+    install_dir: &Path,
+    config: &GgConfig,
+    tools: &[String],
+    all: bool,
+) -> Vec<CacheEntry> {
+    let entries = cache::entries(cache_dir);
+    if !tools.is_empty() {
+        let tools = tools.iter().map(|t| canonical_name(t)).collect::<Vec<_>>();
+        return entries
+            .into_iter()
+            .filter(|e| tools.contains(&entry_name(e)) || tools.contains(&e.tool))
+            .collect();
+    }
+    let project = executors_for(&config_cmds(config))
+        .iter()
+        .map(|e| install_dir.join(cache_subdir(&**e)))
+        .collect::<Vec<_>>();
+    if all || project.is_empty() {
+        return entries;
+    }
+    entries
+        .into_iter()
+        .filter(|e| project.contains(&e.path))
+        .collect()
+}
+
+/// `gg verify [tool...]`: compare installs against the manifest written when
+/// they were downloaded, and offer to reinstall the broken ones
+pub async fn run(
+    input: &AppInput,
+    config: &GgConfig,
+    tools: &[String],
+    all: bool,
+    repair: bool,
+) -> ExitCode {
+    let cache_dir = cache::cache_dir();
+    let install_dir = cache::install_dir();
+    let entries = selected(&cache_dir, &install_dir, config, tools, all);
+    if entries.is_empty() {
+        println!("Nothing installed to verify in {}", cache_dir.display());
+        return ExitCode::from(0);
+    }
+
+    let mut broken = vec![];
+    for entry in &entries {
+        let name = entry_name(entry);
+        let Some(manifest) = read(&entry.path) else {
+            println!(
+                "  ? {} ({}): no manifest, installed by an older gg",
+                name,
+                entry.path.display()
+            );
+            continue;
+        };
+        let report = verify(&entry.path, &manifest);
+        if report.broken() {
+            println!(
+                "  ✗ {} ({}): {} modified, {} missing, {} extra",
+                name,
+                entry.path.display(),
+                report.modified.len(),
+                report.missing.len(),
+                report.extra.len()
+            );
+            broken.push(entry);
+        } else if !report.extra.is_empty() {
+            println!(
+                "  ✓ {} ({}): {} files ok, {} extra",
+                name,
+                entry.path.display(),
+                manifest.files.len(),
+                report.extra.len()
+            );
+        } else {
+            println!(
+                "  ✓ {} ({}): {} files ok",
+                name,
+                entry.path.display(),
+                manifest.files.len()
+            );
+        }
+        print_files("modified", &report.modified);
+        print_files("missing", &report.missing);
+        print_files("extra", &report.extra);
+    }
+
+    if broken.is_empty() {
+        return ExitCode::from(0);
+    }
+    if !repair && !confirm(&format!("Reinstall {} broken tools?", broken.len())) {
+        println!("Run 'gg verify --repair' to reinstall them");
+        return ExitCode::from(1);
+    }
+
+    let mut failed = false;
+    for entry in broken {
+        let name = entry_name(entry);
+        if entry.path.parent().and_then(Path::parent) != Some(install_dir.as_path()) {
+            println!(
+                "{} ({}) is for another platform, repair it with that --os/--arch",
+                name,
+                entry.path.display()
+            );
+            failed = true;
+            continue;
+        }
+        let Some(executor) = reinstaller(entry, &install_dir) else {
+            println!(
+                "Can't tell how {} was installed, remove {} and run it again",
+                name,
+                entry.path.display()
+            );
+            failed = true;
+            continue;
+        };
+        info!("Reinstalling {} in {}", name, entry.path.display());
+        // Kept aside until the new download is in, a broken install beats none
+        let aside = aside_path(&entry.path);
+        let _ = fs::remove_dir_all(&aside);
+        if let Err(e) = fs::rename(&entry.path, &aside) {
+            println!("Could not move {} aside: {}", entry.path.display(), e);
+            failed = true;
+            continue;
+        }
+        match prep(&*executor, input, &ProgressBar::hidden()).await {
+            Ok(_) => {
+                let _ = fs::remove_dir_all(&aside);
+                println!("Reinstalled {}", name);
+            }
+            Err(e) => {
+                let _ = fs::remove_dir_all(&entry.path);
+                let _ = fs::rename(&aside, &entry.path);
+                println!("Reinstalling {} failed: {}", name, e);
+                failed = true;
+            }
+        }
+    }
+    ExitCode::from(if failed { 1 } else { 0 })
+}
+
+/// `node_eq_20.1.0` -> `node_eq_20.1.0.broken`. Not `with_extension`, that
+/// would give `node_eq_20.1.broken`, the same for every 20.1.x.
+fn aside_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".broken");
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_aside_path() {
+        assert_eq!(
+            aside_path(Path::new("/cache/node/node_eq_20.1.0")),
+            Path::new("/cache/node/node_eq_20.1.0.broken")
+        );
+        assert_ne!(
+            aside_path(Path::new("/cache/node/node_eq_20.1.0")),
+            aside_path(Path::new("/cache/node/node_eq_20.1.1"))
+        );
+    }
+
+    #[test]
+    fn test_verify() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("bin")).unwrap();
+        fs::write(root.join("bin/tool"), "binary").unwrap();
+        fs::write(root.join("lib.so"), "library").unwrap();
+        fs::write(root.join("README"), "readme").unwrap();
+        fs::write(root.join("gg-meta.json"), "{}").unwrap();
+
+        write(root).unwrap();
+        let manifest = read(root).unwrap();
+        assert_eq!(
+            manifest.files.keys().collect::<Vec<_>>(),
+            vec!["README", "bin/tool", "lib.so"]
+        );
+        let report = verify(root, &manifest);
+        assert!(!report.broken());
+        assert!(report.extra.is_empty());
+
+        // Same size, different content
+        fs::write(root.join("bin/tool"), "BINARY").unwrap();
+        fs::remove_file(root.join("README")).unwrap();
+        fs::write(root.join("bin/other"), "added").unwrap();
+        cache::touch_last_used(root);
+        let report = verify(root, &manifest);
+        assert!(report.broken());
+        assert_eq!(report.modified, vec!["bin/tool"]);
+        assert_eq!(report.missing, vec!["README"]);
+        assert_eq!(report.extra, vec!["bin/other"]);
+    }
+}
@@ -0,0 +1,556 @@
+use std::collections::HashMap;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use futures_util::StreamExt;
+use log::{info, warn};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::cache;
+use crate::config::GgConfig;
+
+/// Version lists change, archives don't. Indexes are fetched again once they
+/// are older than this.
+const INDEX_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Extensions of files that never change once published
+const ARCHIVE_EXTENSIONS: &[&str] = &[
+    ".zip", ".tar", ".gz", ".tgz", ".xz", ".zst", ".7z", ".jar", ".gem", ".msi", ".pkg", ".exe",
+    ".dmg", ".sha256", ".sha512", ".asc",
+];
+
+/// Where gg's tools download from, and where those hosts redirect to, down to
+/// the paths gg fetches. The mirror fetches nothing else, or it would be an
+/// open proxy into whatever network it runs in, and onto all of GitHub or GCS.
+/// Hosts match exactly; in a path `*` is any one segment.
+const UPSTREAMS: &[(&str, &[&str])] = &[
+    ("nodejs.org", &["/dist/"]),
+    ("unofficial-builds.nodejs.org", &["/download/"]),
+    ("registry.npmjs.org", &["/"]),
+    ("pypi.org", &["/simple/", "/pypi/"]),
+    ("files.pythonhosted.org", &["/packages/"]),
+    ("go.dev", &["/dl/"]),
+    ("dl.google.com", &["/go/"]),
+    (
+        "storage.googleapis.com",
+        &["/flutter_infra_release/", "/grok-build-public-artifacts/"],
+    ),
+    (
+        "services.gradle.org",
+        &["/versions/", "/distributions/", "/distributions-snapshots/"],
+    ),
+    (
+        "downloads.gradle.org",
+        &["/distributions/", "/distributions-snapshots/"],
+    ),
+    ("gradle.org", &["/releases"]),
+    ("archive.apache.org", &["/dist/"]),
+    ("dlcdn.apache.org", &["/maven/"]),
+    ("repo.maven.apache.org", &["/maven2/"]),
+    ("repo1.maven.org", &["/maven2/"]),
+    ("github.com", &["/*/*/releases/download/"]),
+    (
+        "objects.githubusercontent.com",
+        &["/github-production-release-asset-"],
+    ),
+    ("release-assets.githubusercontent.com", &["/"]),
+    (
+        "raw.githubusercontent.com",
+        &["/actions/setup-java/", "/astral-sh/uv/"],
+    ),
+    ("api.adoptium.net", &["/v3/"]),
+    ("www.azul.com", &["/wp-admin/admin-ajax.php"]),
+    ("cdn.azul.com", &["/zulu/"]),
+    ("corretto.aws", &["/downloads/"]),
+    ("corretto.github.io", &["/corretto-downloads/"]),
+    // Where corretto.aws sends downloads
+    ("d3pxv6yz143wms.cloudfront.net", &["/"]),
+    ("api.bell-sw.com", &["/v1/"]),
+    ("download.bell-sw.com", &["/java/"]),
+    ("aka.ms", &["/download-jdk/"]),
+    ("download.visualstudio.microsoft.com", &["/download/"]),
+    ("sap.github.io", &["/SapMachine/"]),
+    ("downloads.claude.ai", &["/claude-code-releases/"]),
+    ("x.ai", &["/cli/"]),
+    ("ratbinsa.z1.web.core.windows.net", &["/"]),
+];
+
+/// `/*/*/releases/download/` against `/SAP/SapMachine/releases/download/x.zip`
+fn path_matches(pattern: &str, path: &str) -> bool {
+    let mut pattern = pattern.split('/').peekable();
+    let mut path = path.split('/');
+    while let Some(want) = pattern.next() {
+        let Some(got) = path.next() else {
+            return false;
+        };
+        // The last part is a prefix, `/dist/` leaves "" for it
+        let last = pattern.peek().is_none();
+        let ok = match want {
+            "*" => !got.is_empty(),
+            _ if last => got.starts_with(want),
+            _ => got == want,
+        };
+        if !ok {
+            return false;
+        }
+    }
+    true
+}
+
+/// Plain https/http to one of `UPSTREAMS` on its default port
+fn is_upstream(url: &reqwest::Url) -> bool {
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    let host = host.to_lowercase();
+    matches!(url.scheme(), "http" | "https")
+        && url.port().is_none()
+        && url.username().is_empty()
+        && url.password().is_none()
+        && UPSTREAMS
+            .iter()
+            .any(|(h, paths)| host == *h && paths.iter().any(|p| path_matches(p, url.path())))
+}
+
+/// The mirror other gg instances should fetch through, from `GG_MIRROR` or
+/// gg.toml's `mirror`
+pub fn mirror_url() -> Option<String> {
+    env::var("GG_MIRROR")
+        .ok()
+        .or_else(|| GgConfig::loaded().mirror.clone())
+        .map(|m| m.trim().trim_end_matches('/').to_string())
+        .filter(|m| !m.is_empty())
+}
+
+/// `https://nodejs.org/dist/index.json` -> `<mirror>/https/nodejs.org/dist/index.json`.
+/// Unchanged when there is no mirror, or for anything but http(s).
+pub fn mirrored(url: &str) -> String {
+    match mirror_url() {
+        Some(mirror) => mirrored_via(&mirror, url),
+        None => url.to_string(),
+    }
+}
+
+fn mirrored_via(mirror: &str, url: &str) -> String {
+    for scheme in ["https", "http"].iter() {
+        if let Some(rest) = url.strip_prefix(&format!("{}://", scheme)) {
+            return format!("{}/{}/{}", mirror, scheme, rest);
+        }
+    }
+    url.to_string()
+}
+
+/// The other way round, for the server: `/https/nodejs.org/dist/index.json`
+/// -> `https://nodejs.org/dist/index.json`. None for hosts gg doesn't
+/// download from.
+fn upstream_url(path: &str, allowed: Allowed) -> Option<String> {
+    let path = path.strip_prefix('/')?;
+    let (scheme, rest) = path.split_once('/')?;
+    let url = reqwest::Url::parse(&format!("{}://{}", scheme, rest)).ok()?;
+    allowed(&url).then(|| format!("{}://{}", scheme, rest))
+}
+
+fn is_archive(url: &str) -> bool {
+    let path = url.split('?').next().unwrap_or(url).to_lowercase();
+    ARCHIVE_EXTENSIONS.iter().any(|ext| path.ends_with(ext))
+}
+
+/// Where the mirror keeps a URL: `<store>/<host>/<hash>-<file name>`. Hashed
+/// so query strings and odd paths can't escape the store or collide.
+fn store_path(store: &Path, url: &str) -> PathBuf {
+    let rest = url.split_once("://").map(|(_, r)| r).unwrap_or(url);
+    let host = rest
+        .split(['/', '?'])
+        .next()
+        .unwrap_or("unknown")
+        .replace(':', "_");
+    let name = rest
+        .split('?')
+        .next()
+        .unwrap_or("")
+        .rsplit('/')
+        .next()
+        .unwrap_or("")
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+        .collect::<String>();
+    let hash = sha256::digest(url);
+    store
+        .join(host)
+        .join(format!("{}-{}", &hash[..16], name).trim_end_matches('-'))
+}
+
+fn is_fresh(path: &Path, url: &str) -> bool {
+    let Ok(modified) = path.metadata().and_then(|m| m.modified()) else {
+        return false;
+    };
+    is_archive(url)
+        || SystemTime::now()
+            .duration_since(modified)
+            .map(|age| age < INDEX_TTL)
+            .unwrap_or(true)
+}
+
+/// `is_upstream`, or anything for the tests' local upstream
+type Allowed = fn(&reqwest::Url) -> bool;
+
+struct Mirror {
+    store: PathBuf,
+    allowed: Allowed,
+    client: reqwest::Client,
+    /// One upstream fetch per URL at a time, the rest wait for it
+    locks: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl Mirror {
+    fn lock_for(&self, url: &str) -> Arc<tokio::sync::Mutex<()>> {
+        self.locks
+            .lock()
+            .unwrap()
+            .entry(url.to_string())
+            .or_default()
+            .clone()
+    }
+
+    /// Streams upstream into the store. Via a temp file, so a dropped
+    /// connection never leaves half a JDK to be served.
+    async fn fetch(&self, url: &str, path: &Path) -> Result<(), String> {
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("upstream answered {}", response.status()));
+        }
+        let dir = path.parent().unwrap_or(&self.store);
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|e| e.to_string())?;
+        let temp = tempfile::NamedTempFile::new_in(dir).map_err(|e| e.to_string())?;
+        let mut file = tokio::fs::File::create(temp.path())
+            .await
+            .map_err(|e| e.to_string())?;
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| e.to_string())?;
+            file.write_all(&chunk).await.map_err(|e| e.to_string())?;
+        }
+        file.flush().await.map_err(|e| e.to_string())?;
+        temp.persist(path).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// The stored file for `url`, fetched first if missing or stale. A stale
+    /// index beats none when upstream is down.
+    async fn get(&self, url: &str) -> Result<PathBuf, String> {
+        let path = store_path(&self.store, url);
+        let lock = self.lock_for(url);
+        let _guard = lock.lock().await;
+        if is_fresh(&path, url) {
+            info!("Hit {}", url);
+            // Archives never go stale, their time is when they were last
+            // used, for gg prune
+            if is_archive(url) {
+                let _ = std::fs::File::options()
+                    .write(true)
+                    .open(&path)
+                    .and_then(|f| f.set_modified(SystemTime::now()));
+            }
+            return Ok(path);
+        }
+        info!("Miss {}", url);
+        match self.fetch(url, &path).await {
+            Ok(()) => Ok(path),
+            Err(e) if path.exists() => {
+                warn!("{}: {}, serving what we have", url, e);
+                Ok(path)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+async fn respond(socket: &mut TcpStream, status: &str, body: &str) {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let _ = socket.write_all(response.as_bytes()).await;
+}
+
+/// Method and path of the request
+async fn read_request(socket: &mut TcpStream) -> Option<(String, String)> {
+    let mut reader = BufReader::new(socket);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await.ok()?;
+    // Headers don't matter, but have to be read before answering
+    loop {
+        let mut line = String::new();
+        match reader.read_line(&mut line).await {
+            Ok(0) | Err(_) => break,
+            Ok(_) if line.trim().is_empty() => break,
+            Ok(_) => {}
+        }
+    }
+    let mut parts = request_line.split_whitespace();
+    Some((parts.next()?.to_string(), parts.next()?.to_string()))
+}
+
+async fn handle(mirror: &Mirror, mut socket: TcpStream) {
+    let Some((method, path)) = read_request(&mut socket).await else {
+        return;
+    };
+    let (method, path) = (method.as_str(), path.as_str());
+    if method != "GET" && method != "HEAD" {
+        return respond(&mut socket, "405 Method Not Allowed", "GET only\n").await;
+    }
+    if path == "/" {
+        return respond(&mut socket, "200 OK", "gg mirror\n").await;
+    }
+    let Some(url) = upstream_url(path, mirror.allowed) else {
+        return respond(
+            &mut socket,
+            "404 Not Found",
+            "Expected /<scheme>/<host>/<path>, for a host gg downloads from\n",
+        )
+        .await;
+    };
+
+    let file = match mirror.get(&url).await {
+        Ok(file) => file,
+        Err(e) => {
+            warn!("{}: {}", url, e);
+            return respond(&mut socket, "502 Bad Gateway", &format!("{}: {}\n", url, e)).await;
+        }
+    };
+    let Ok(mut file) = tokio::fs::File::open(&file).await else {
+        return respond(&mut socket, "500 Internal Server Error", "Lost the file\n").await;
+    };
+    let len = file.metadata().await.map(|m| m.len()).unwrap_or(0);
+    let header = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/octet-stream\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        len
+    );
+    if socket.write_all(header.as_bytes()).await.is_err() {
+        return;
+    }
+    if method == "GET" {
+        let _ = tokio::io::copy(&mut file, &mut socket).await;
+    }
+    let _ = socket.shutdown().await;
+}
+
+async fn serve_on(listener: TcpListener, store: PathBuf, allowed: Allowed) {
+    // Redirects are held to the same hosts, or one would get around them
+    let redirects = reqwest::redirect::Policy::custom(move |attempt| {
+        if attempt.previous().len() >= 10 || !allowed(attempt.url()) {
+            attempt.stop()
+        } else {
+            attempt.follow()
+        }
+    });
+    let mirror = Arc::new(Mirror {
+        store,
+        allowed,
+        client: reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(10))
+            .redirect(redirects)
+            .build()
+            .unwrap_or_default(),
+        locks: Mutex::new(HashMap::new()),
+    });
+    loop {
+        let Ok((socket, peer)) = listener.accept().await else {
+            continue;
+        };
+        info!("Connection from {}", peer);
+        let mirror = mirror.clone();
+        tokio::spawn(async move { handle(&mirror, socket).await });
+    }
+}
+
+/// Where `gg serve` keeps what it fetched
+pub fn store_dir(cache_dir: &Path) -> PathBuf {
+    cache_dir.join("gg-mirror")
+}
+
+/// Every file in the store, `<store>/<host>/<file>`
+pub fn stored_files(cache_dir: &Path) -> Vec<PathBuf> {
+    let Ok(hosts) = std::fs::read_dir(store_dir(cache_dir)) else {
+        return vec![];
+    };
+    let mut files = hosts
+        .flatten()
+        .filter_map(|host| std::fs::read_dir(host.path()).ok())
+        .flat_map(|dir| dir.flatten().map(|f| f.path()))
+        .filter(|f| f.is_file())
+        .collect::<Vec<_>>();
+    files.sort();
+    files
+}
+
+/// `gg serve`: a pull-through mirror of everything gg downloads, kept in
+/// `<cache>/gg-mirror`
+pub async fn serve(bind: &str, port: u16) -> ExitCode {
+    let store = store_dir(&cache::cache_dir());
+    let listener = match TcpListener::bind((bind, port)).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            println!("Could not listen on {}:{}: {}", bind, port, e);
+            return ExitCode::from(1);
+        }
+    };
+    let addr = listener
+        .local_addr()
+        .map(|a| a.to_string())
+        .unwrap_or_default();
+    println!(
+        "Serving a gg mirror on http://{} from {}",
+        addr,
+        store.display()
+    );
+    if listener.local_addr().is_ok_and(|a| a.ip().is_loopback()) {
+        println!("Only this machine can reach it, --bind 0.0.0.0 lets others in");
+    } else {
+        println!(
+            "Point other machines at it with GG_MIRROR=http://<this host>:{}",
+            port
+        );
+    }
+    serve_on(listener, store, is_upstream).await;
+    ExitCode::from(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_urls() {
+        let mirror = "http://mirror:8080";
+        assert_eq!(
+            mirrored_via(mirror, "https://nodejs.org/dist/index.json"),
+            "http://mirror:8080/https/nodejs.org/dist/index.json"
+        );
+        assert_eq!(
+            mirrored_via(mirror, "https://go.dev/dl/?mode=json"),
+            "http://mirror:8080/https/go.dev/dl/?mode=json"
+        );
+        assert_eq!(mirrored_via(mirror, "file:///tmp/x"), "file:///tmp/x");
+
+        let upstream = |path| upstream_url(path, is_upstream);
+        assert_eq!(
+            upstream("/https/go.dev/dl/?mode=json").as_deref(),
+            Some("https://go.dev/dl/?mode=json")
+        );
+        assert_eq!(
+            upstream("/https/unofficial-builds.nodejs.org/download/release/index.json").as_deref(),
+            Some("https://unofficial-builds.nodejs.org/download/release/index.json")
+        );
+        assert!(upstream(
+            "/https/github.com/SAP/SapMachine/releases/download/sapmachine-21/jdk.tar.gz"
+        )
+        .is_some());
+        assert_eq!(upstream("/ftp/go.dev/x"), None);
+        assert_eq!(upstream("/https/"), None);
+        // Nothing that isn't a download host, however it's written
+        for path in [
+            "/http/169.254.169.254/latest/meta-data/",
+            "/http/localhost:8080/admin",
+            "/http/10.0.0.1/x",
+            "/https/nodejs.org.evil.com/x",
+            "/https/evilnodejs.org/x",
+            "/https/nodejs.org@169.254.169.254/x",
+            "/https/nodejs.org:6379/x",
+            "/https/evil.nodejs.org/dist/x",
+            "/https/nodejs.org/api/x",
+            "/https/github.com/evil/repo/archive/main.zip",
+            "/https/github.com/releases/download/x",
+            "/https/gist.githubusercontent.com/evil/x",
+            "/https/raw.githubusercontent.com/evil/repo/main/x",
+            "/https/evil.cloudfront.net/x",
+            "/https/storage.googleapis.com/evil-bucket/x",
+        ]
+        .iter()
+        {
+            assert_eq!(upstream(path), None, "{}", path);
+        }
+
+        assert!(is_archive("https://x/node-v20.0.0-linux-x64.tar.xz"));
+        assert!(!is_archive("https://x/dist/index.json"));
+
+        let store = Path::new("/store");
+        let path = store_path(store, "https://host:8443/a/../../etc/passwd?x=1");
+        assert!(path.starts_with("/store/host_8443"));
+        assert!(path
+            .file_name()
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .ends_with("-passwd"));
+        assert_ne!(
+            path,
+            store_path(store, "https://host:8443/a/../../etc/passwd?x=2")
+        );
+    }
+
+    /// Upstream that counts its requests
+    async fn upstream(body: &'static str) -> (String, Arc<Mutex<usize>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let count = Arc::new(Mutex::new(0));
+        let counter = count.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                *counter.lock().unwrap() += 1;
+                let mut request = [0u8; 2048];
+                use tokio::io::AsyncReadExt;
+                let _ = socket.read(&mut request).await;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+                let _ = socket.shutdown().await;
+            }
+        });
+        (format!("http://{}", addr), count)
+    }
+
+    #[tokio::test]
+    async fn test_pull_through() {
+        let (upstream, count) = upstream("archive bytes").await;
+        let store = TempDir::new().unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mirror = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(serve_on(listener, store.path().to_path_buf(), |_| true));
+
+        let url = format!("{}/tool-1.0.0.tar.gz", upstream);
+        for _ in 0..2 {
+            let body = reqwest::get(mirrored_via(&mirror, &url))
+                .await
+                .unwrap()
+                .text()
+                .await
+                .unwrap();
+            assert_eq!(body, "archive bytes");
+        }
+        assert_eq!(*count.lock().unwrap(), 1);
+
+        let status = reqwest::get(format!("{}/nope", mirror))
+            .await
+            .unwrap()
+            .status();
+        assert_eq!(status.as_u16(), 404);
+    }
+}
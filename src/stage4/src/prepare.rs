@@ -0,0 +1,433 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::process::ExitCode;
+
+use futures_util::future::join_all;
+use indicatif::MultiProgress;
+use log::{info, warn};
+
+use crate::barus::create_barus;
+use crate::cli::{parse_command_string, split_name_version, ClapCmd};
+use crate::config::GgConfig;
+use crate::executor::{prep, AppInput, AppPath, Executor, ExecutorCmd, GgVersionReq};
+use crate::tools::{canonical_name, registry_name};
+
+/// Everything a prepared set of tools needs to run: where each one landed
+/// (same order as the executors) and the PATH/env to run them with.
+pub struct Prepared {
+    pub app_paths: Vec<AppPath>,
+    pub path_vars: Vec<String>,
+    pub env_vars: HashMap<String, String>,
+}
+
+impl Prepared {
+    /// Layer gg.toml's `[env]`/`path` and the `[tools.<name>]` tables of the
+    /// tools in use on top of what the executors set. gg.toml wins.
+    pub fn apply_config(&mut self, config: &GgConfig, executors: &[Box<dyn Executor>]) {
+        let names = executors
+            .iter()
+            .map(|e| registry_name(&**e))
+            .collect::<Vec<_>>();
+        let mut homes = names
+            .iter()
+            .cloned()
+            .zip(self.app_paths.iter().map(|p| p.install_dir.clone()))
+            .collect::<HashMap<_, _>>();
+        // A `[tools.<name>]` with a source answers to <name>, not the package
+        for (name, tool) in &config.tools {
+            let source = tool.source.as_deref().map(split_name_version);
+            let cmd = source.as_ref().and_then(|parts| parts.first());
+            let used = executors
+                .iter()
+                .zip(&self.app_paths)
+                .find(|(e, _)| Some(&e.get_executor_cmd().cmd) == cmd);
+            if let Some((_, app_path)) = used {
+                homes.insert(name.to_string(), app_path.install_dir.clone());
+            }
+        }
+
+        let mut tables = vec![(&config.env, &config.path)];
+        let mut tools = config
+            .tools
+            .iter()
+            .filter(|(name, t)| {
+                names.contains(&canonical_name(name))
+                    || (t.source.is_some() && homes.contains_key(name.as_str()))
+            })
+            .collect::<Vec<_>>();
+        tools.sort_by_key(|(name, _)| name.to_string());
+        tables.extend(tools.into_iter().map(|(_, t)| (&t.env, &t.path)));
+
+        for (env, path) in tables {
+            for (key, value) in env {
+                self.env_vars
+                    .insert(key.to_string(), config.interpolate(value, &homes));
+            }
+            for entry in path {
+                let entry = config.interpolate(entry, &homes);
+                let entry = match &config.root {
+                    Some(root) if Path::new(&entry).is_relative() => {
+                        root.join(&entry).to_string_lossy().to_string()
+                    }
+                    _ => entry,
+                };
+                self.path_vars.push(entry);
+            }
+        }
+    }
+}
+
+pub fn executor_cmd(cmd: &ClapCmd) -> ExecutorCmd {
+    ExecutorCmd {
+        cmd: cmd.cmd.to_string(),
+        version: GgVersionReq::new(cmd.version.clone().unwrap_or("".to_string()).as_str()),
+        distribution: cmd.distribution.clone(),
+        include_tags: cmd.include_tags.clone(),
+        exclude_tags: cmd.exclude_tags.clone(),
+        gems: cmd.gems.clone(),
+    }
+}
+
+pub fn executors_for(cmds: &[ClapCmd]) -> Vec<Box<dyn Executor>> {
+    cmds.iter()
+        .filter_map(|cmd| <dyn Executor>::new(executor_cmd(cmd)))
+        .collect()
+}
+
+/// Every tool in gg.toml `[dependencies]`, versions filled in from there just
+/// like `gg <tool>` would. Sorted so output and prep order are stable.
+pub fn config_cmds(config: &GgConfig) -> Vec<ClapCmd> {
+    let mut names = config.dependencies.keys().collect::<Vec<_>>();
+    names.sort();
+    names
+        .into_iter()
+        .flat_map(|name| {
+            let cmds = parse_command_string(name, config);
+            if cmds
+                .iter()
+                .any(|c| <dyn Executor>::new(executor_cmd(c)).is_none())
+            {
+                warn!(
+                    "Unknown tool '{}' in gg.toml [dependencies], skipping",
+                    name
+                );
+                return vec![];
+            }
+            cmds
+        })
+        .collect()
+}
+
+/// Is this dependency already covered? Registry names on both sides: gh
+/// depends on "git" but a built git executor calls itself "portable-git", so
+/// comparing those raw built a second executor over the same cache dir and
+/// prepped both at once.
+pub fn dep_satisfied(
+    executors: &[Box<dyn Executor>],
+    to_add: &[Box<dyn Executor>],
+    processed: &HashSet<String>,
+    dep_name: &str,
+) -> bool {
+    executors.iter().any(|e| registry_name(&**e) == dep_name)
+        || to_add.iter().any(|e| registry_name(&**e) == dep_name)
+        || processed.contains(dep_name)
+}
+
+/// Pull in everything the executors depend on, transitively.
+pub async fn with_deps(
+    mut executors: Vec<Box<dyn Executor>>,
+    input: &AppInput,
+) -> Vec<Box<dyn Executor>> {
+    let mut look_for_deps = true;
+    let mut processed_deps = HashSet::new();
+    while look_for_deps {
+        look_for_deps = false;
+        let mut to_add: Vec<Box<dyn Executor>> = Vec::new();
+        for x in &executors {
+            let deps = x.get_deps(input).await;
+            for dep in deps {
+                let dep_name = canonical_name(&dep.name);
+                if !dep_satisfied(&executors, &to_add, &processed_deps, &dep_name) {
+                    if dep.optional {
+                        if which::which(&dep.name).is_ok() {
+                            info!(
+                                "Optional dependency '{}' found in PATH, using system version",
+                                dep.name
+                            );
+                            processed_deps.insert(dep_name.clone());
+                            continue;
+                        } else {
+                            info!(
+                                "Optional dependency '{}' not found in PATH, falling back to managed version",
+                                dep.name
+                            );
+                        }
+                    }
+
+                    if let Some(e) = <dyn Executor>::new(ExecutorCmd {
+                        cmd: dep.name.clone(),
+                        version: dep.version.as_ref().and_then(|v| GgVersionReq::new(v)),
+                        distribution: None,
+                        include_tags: Default::default(),
+                        exclude_tags: Default::default(),
+                        gems: None,
+                    }) {
+                        look_for_deps = true;
+                        processed_deps.insert(dep_name.clone());
+                        to_add.push(e);
+                    }
+                }
+            }
+        }
+        for x in to_add {
+            executors.push(x);
+        }
+    }
+    executors
+}
+
+/// Prep all executors in parallel, one progress bar each. Errors are the
+/// reasons each failed prep gave.
+pub async fn prep_all(
+    executors: &[Box<dyn Executor>],
+    input: &AppInput,
+) -> Result<Prepared, Vec<String>> {
+    let m = MultiProgress::new();
+
+    let alles = executors
+        .iter()
+        .enumerate()
+        .map(|(i, x)| {
+            let pb = create_barus();
+            let pb = m.insert(i, pb);
+            (x, pb)
+        })
+        .map(|(x, pb)| async move {
+            let app_path = prep(&**x, input, &pb).await?;
+            let mut env = x.get_env(&app_path);
+            env.extend(x.get_extra_env(input, &pb).await);
+            let bin_dirs = x.get_bin_dirs();
+            Ok::<_, String>((app_path, env, bin_dirs))
+        });
+    let res = join_all(alles).await;
+
+    let errors = res
+        .iter()
+        .filter_map(|x| x.clone().err())
+        .collect::<Vec<_>>();
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut prepared = Prepared {
+        app_paths: vec![],
+        path_vars: vec![],
+        env_vars: HashMap::new(),
+    };
+    for (app_path, env, bin_dirs) in res.into_iter().filter_map(|x| x.ok()) {
+        for bin_dir in &bin_dirs {
+            prepared.path_vars.push(
+                app_path
+                    .install_dir
+                    .clone()
+                    .join(bin_dir)
+                    .to_str()
+                    .unwrap_or("")
+                    .to_string(),
+            );
+        }
+        for (key, value) in env {
+            prepared.env_vars.insert(key.to_string(), value.to_string());
+        }
+        prepared.app_paths.push(app_path);
+    }
+    Ok(prepared)
+}
+
+/// The tools given on the command line, or everything in gg.toml
+pub fn specs_or_config(config: &GgConfig, specs: &[String]) -> Result<Vec<ClapCmd>, String> {
+    let cmds = if specs.is_empty() {
+        config_cmds(config)
+    } else {
+        specs
+            .iter()
+            .flat_map(|spec| parse_command_string(spec, config))
+            .collect()
+    };
+    if let Some(unknown) = cmds
+        .iter()
+        .find(|c| <dyn Executor>::new(executor_cmd(c)).is_none())
+    {
+        return Err(format!(
+            "Unknown tool '{}'. Run 'gg tools' to see available tools",
+            unknown.cmd
+        ));
+    }
+    if cmds.is_empty() {
+        return Err("No tools given and no [dependencies] in gg.toml".to_string());
+    }
+    Ok(cmds)
+}
+
+/// `gg install [specs...]`: prep the given tools, or everything in gg.toml, and
+/// their deps, without running anything. For warming a cache in CI or a Docker
+/// build - `--os`/`--arch` prefetch for another platform.
+pub async fn install(input: &AppInput, config: &GgConfig, specs: &[String]) -> ExitCode {
+    let cmds = match specs_or_config(config, specs) {
+        Ok(cmds) => cmds,
+        Err(e) => {
+            println!("{}", e);
+            return ExitCode::from(1);
+        }
+    };
+
+    let executors = with_deps(executors_for(&cmds), input).await;
+    match prep_all(&executors, input).await {
+        Ok(prepared) => {
+            for (executor, app_path) in executors.iter().zip(&prepared.app_paths) {
+                println!(
+                    "Installed {} in {}",
+                    executor.get_name(),
+                    app_path.install_dir.display()
+                );
+            }
+            ExitCode::from(0)
+        }
+        Err(errors) => {
+            for e in errors {
+                println!("Prep failed: {}", e);
+            }
+            ExitCode::from(1)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn ex(name: &str) -> Box<dyn Executor> {
+        <dyn Executor>::new(ExecutorCmd {
+            cmd: name.to_string(),
+            version: None,
+            distribution: None,
+            include_tags: Default::default(),
+            exclude_tags: Default::default(),
+            gems: None,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_dep_satisfied_matches_on_registry_name() {
+        let git = vec![ex("git")];
+        let empty: Vec<Box<dyn Executor>> = vec![];
+        let none = HashSet::new();
+
+        // The gh -> git case, the one that used to build a second executor
+        assert!(dep_satisfied(&git, &empty, &none, "git"));
+        assert!(dep_satisfied(&empty, &git, &none, "git"));
+
+        // ...without swallowing a dep that really is missing
+        assert!(!dep_satisfied(&git, &empty, &none, "java"));
+        assert!(!dep_satisfied(&empty, &empty, &none, "git"));
+
+        // an optional dep taken from PATH is recorded here, canonically
+        let mut done = HashSet::new();
+        done.insert("java".to_string());
+        assert!(dep_satisfied(&empty, &empty, &done, "java"));
+    }
+
+    #[test]
+    fn test_apply_config_env_and_path() {
+        let mut config: GgConfig = toml::from_str(
+            r#"
+path = ["bin"]
+
+[env]
+A = "${java.home}/x"
+
+[tools.maven.env]
+MAVEN_OPTS = "-Xmx2g"
+
+[tools.java.env]
+B = "only with java"
+"#,
+        )
+        .unwrap();
+        config.root = Some(PathBuf::from("/p"));
+
+        let mut env_vars = HashMap::new();
+        env_vars.insert("A".to_string(), "from executor".to_string());
+        let mut prepared = Prepared {
+            app_paths: vec![AppPath {
+                install_dir: PathBuf::from("/c/java"),
+            }],
+            path_vars: vec!["/c/java/bin".to_string()],
+            env_vars,
+        };
+        prepared.apply_config(&config, &[ex("java")]);
+
+        assert_eq!(prepared.env_vars["A"], "/c/java/x");
+        assert_eq!(prepared.env_vars["B"], "only with java");
+        assert!(!prepared.env_vars.contains_key("MAVEN_OPTS"));
+        assert_eq!(
+            prepared.path_vars,
+            vec![
+                "/c/java/bin".to_string(),
+                Path::new("/p").join("bin").to_string_lossy().to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply_config_for_tool_source() {
+        let config: GgConfig = toml::from_str(
+            r#"
+[tools.fmt]
+source = "npm:prettier@3"
+env = { HOME_OF_FMT = "${fmt.home}" }
+"#,
+        )
+        .unwrap();
+        let mut prepared = Prepared {
+            app_paths: vec![AppPath {
+                install_dir: PathBuf::from("/c/prettier"),
+            }],
+            path_vars: vec![],
+            env_vars: HashMap::new(),
+        };
+        prepared.apply_config(&config, &[ex("npm:prettier")]);
+        assert_eq!(prepared.env_vars["HOME_OF_FMT"], "/c/prettier");
+
+        let mut prepared = Prepared {
+            app_paths: vec![AppPath {
+                install_dir: PathBuf::from("/c/java"),
+            }],
+            path_vars: vec![],
+            env_vars: HashMap::new(),
+        };
+        prepared.apply_config(&config, &[ex("java")]);
+        assert!(prepared.env_vars.is_empty());
+    }
+
+    #[test]
+    fn test_config_cmds_skips_unknown_tools() {
+        let mut config = GgConfig::default();
+        config
+            .dependencies
+            .insert("node".to_string(), "18".to_string());
+        config
+            .dependencies
+            .insert("not-a-tool".to_string(), "1".to_string());
+        config
+            .dependencies
+            .insert("java".to_string(), "17".to_string());
+
+        let cmds = config_cmds(&config);
+        let names = cmds.iter().map(|c| c.cmd.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["java", "node"]);
+        assert_eq!(cmds[1].version.as_deref(), Some("18"));
+    }
+}
@@ -0,0 +1,381 @@
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::UNIX_EPOCH;
+
+use semver::Version;
+
+use crate::cache::{self, ago, human_size};
+use crate::tools::canonical_name;
+
+/// `gg prune` as typed, before parsing
+#[derive(Debug, Default)]
+pub struct PruneArgs {
+    pub older_than: Option<String>,
+    pub keep_latest: Option<usize>,
+    pub max_size: Option<String>,
+    pub tool: Option<String>,
+    pub dry_run: bool,
+    pub yes: bool,
+}
+
+#[derive(Debug, Default)]
+struct Policy {
+    older_than: Option<u64>,
+    keep_latest: Option<usize>,
+    max_size: Option<u64>,
+}
+
+/// One cache entry as far as pruning cares
+#[derive(Debug)]
+struct Item {
+    tool: String,
+    version: Option<Version>,
+    last_used: u64,
+    size: u64,
+    /// A file in the `gg serve` store. Those have no versions to keep.
+    mirrored: bool,
+}
+
+/// `--tool` for the `gg serve` store
+const MIRROR: &str = "mirror";
+
+/// `30d`, `12h`, `2w`, `45m` -> seconds. A bare number is days.
+fn parse_age(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let (num, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()));
+    let num = num
+        .parse::<u64>()
+        .map_err(|_| format!("Bad age '{}', expected e.g. 30d, 12h or 2w", s))?;
+    let unit = match unit {
+        "m" => 60,
+        "h" => 3600,
+        "" | "d" => 86400,
+        "w" => 7 * 86400,
+        _ => return Err(format!("Bad age '{}', expected e.g. 30d, 12h or 2w", s)),
+    };
+    Ok(num * unit)
+}
+
+/// `10G`, `500M`, `1.5GB` -> bytes. A bare number is bytes.
+fn parse_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(s.len());
+    let (num, unit) = s.split_at(split);
+    let num = num
+        .parse::<f64>()
+        .map_err(|_| format!("Bad size '{}', expected e.g. 10G or 500M", s))?;
+    let unit: u64 = match unit.to_uppercase().trim_end_matches('B') {
+        "" => 1,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        "T" => 1 << 40,
+        _ => return Err(format!("Bad size '{}', expected e.g. 10G or 500M", s)),
+    };
+    Ok((num * unit as f64) as u64)
+}
+
+/// Which items to remove, and why. Each policy adds to the list; `max_size`
+/// then evicts the least recently used of what is left until it fits.
+fn select(items: &[Item], policy: &Policy, now: u64) -> Vec<(usize, String)> {
+    let mut remove: Vec<(usize, String)> = vec![];
+    let picked = |remove: &Vec<(usize, String)>, i: usize| remove.iter().any(|(r, _)| *r == i);
+
+    if let Some(age) = policy.older_than {
+        for (i, item) in items.iter().enumerate() {
+            if item.last_used + age < now {
+                remove.push((i, format!("last used {}", ago(item.last_used, now))));
+            }
+        }
+    }
+
+    if let Some(keep) = policy.keep_latest {
+        let mut tools = items
+            .iter()
+            .filter(|i| !i.mirrored)
+            .map(|i| i.tool.as_str())
+            .collect::<Vec<_>>();
+        tools.sort();
+        tools.dedup();
+        for tool in tools {
+            let mut versions = items
+                .iter()
+                .enumerate()
+                .filter(|(_, item)| item.tool == tool)
+                .collect::<Vec<_>>();
+            // Newest first, unknown versions last
+            versions.sort_by(|(_, a), (_, b)| b.version.cmp(&a.version));
+            for (i, _) in versions.into_iter().skip(keep) {
+                if !picked(&remove, i) {
+                    remove.push((i, format!("more than {} {} versions", keep, tool)));
+                }
+            }
+        }
+    }
+
+    if let Some(max) = policy.max_size {
+        let mut left = items
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !picked(&remove, *i))
+            .collect::<Vec<_>>();
+        let mut total: u64 = left.iter().map(|(_, item)| item.size).sum();
+        left.sort_by_key(|(_, item)| item.last_used);
+        for (i, item) in left {
+            if total <= max {
+                break;
+            }
+            total -= item.size;
+            remove.push((i, format!("least recently used, over {}", human_size(max))));
+        }
+    }
+
+    remove.sort_by_key(|(i, _)| *i);
+    remove
+}
+
+fn policy(args: &PruneArgs) -> Result<Policy, String> {
+    Ok(Policy {
+        older_than: args.older_than.as_deref().map(parse_age).transpose()?,
+        keep_latest: args.keep_latest,
+        max_size: args.max_size.as_deref().map(parse_size).transpose()?,
+    })
+}
+
+fn confirm() -> bool {
+    print!("Remove these? (y/N): ");
+    let _ = io::stdout().flush();
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer).is_ok()
+        && matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// `gg prune`: remove some of the cache, by age, count or total size
+pub fn run(args: &PruneArgs) -> ExitCode {
+    let policy = match policy(args) {
+        Ok(policy) => policy,
+        Err(e) => {
+            println!("{}", e);
+            return ExitCode::from(1);
+        }
+    };
+    if policy.older_than.is_none() && policy.keep_latest.is_none() && policy.max_size.is_none() {
+        println!("Nothing to prune by. Give --older-than, --keep-latest and/or --max-size");
+        println!("('gg clean-cache' removes everything)");
+        return ExitCode::from(1);
+    }
+
+    let dir = cache::cache_dir();
+    let tool = args.tool.as_deref().map(canonical_name);
+    let entries = cache::entries(&dir)
+        .into_iter()
+        .map(|e| {
+            let name = e
+                .meta
+                .as_ref()
+                .map(|m| canonical_name(&m.cmd.cmd))
+                .unwrap_or_else(|| e.tool.clone());
+            (name, e)
+        })
+        .filter(|(name, e)| tool.as_ref().is_none_or(|t| t == name || *t == e.tool))
+        .collect::<Vec<_>>();
+    // Installs from before gg kept track count as last used when installed
+    let mut items = entries
+        .iter()
+        .map(|(name, e)| Item {
+            tool: name.clone(),
+            version: e
+                .meta
+                .as_ref()
+                .and_then(|m| m.download.version.as_ref())
+                .and_then(|v| Version::parse(&v.to_string()).ok()),
+            last_used: cache::last_used(&e.path)
+                .or_else(|| cache::installed_at(&e.path))
+                .unwrap_or(0),
+            size: cache::dir_size(&e.path),
+            mirrored: false,
+        })
+        .collect::<Vec<_>>();
+    let mut paths = entries
+        .iter()
+        .map(|(_, e)| e.path.clone())
+        .collect::<Vec<PathBuf>>();
+    // The mirror's archives are touched on every hit, indexes on every fetch
+    if tool.as_ref().is_none_or(|t| t == MIRROR) {
+        for file in crate::mirror::stored_files(&dir) {
+            let meta = fs::metadata(&file).ok();
+            items.push(Item {
+                tool: MIRROR.to_string(),
+                version: None,
+                last_used: meta
+                    .as_ref()
+                    .and_then(|m| m.modified().ok())
+                    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0),
+                size: meta.map(|m| m.len()).unwrap_or(0),
+                mirrored: true,
+            });
+            paths.push(file);
+        }
+    }
+
+    let remove = select(&items, &policy, cache::now());
+    if remove.is_empty() {
+        println!("Nothing to prune in {}", dir.display());
+        return ExitCode::from(0);
+    }
+    for (i, reason) in &remove {
+        let item = &items[*i];
+        let version = match (&item.version, item.mirrored) {
+            (Some(v), _) => v.to_string(),
+            (None, true) => "file".to_string(),
+            (None, false) => "?".to_string(),
+        };
+        println!(
+            "    {} {} ({}, {}): {}",
+            item.tool,
+            version,
+            paths[*i].display(),
+            human_size(item.size),
+            reason
+        );
+    }
+    let freed: u64 = remove.iter().map(|(i, _)| items[*i].size).sum();
+
+    if args.dry_run {
+        println!(
+            "Would remove {} versions, freeing {}",
+            remove.len(),
+            human_size(freed)
+        );
+        return ExitCode::from(0);
+    }
+    if !args.yes && !io::stdin().is_terminal() {
+        println!("Not removing anything without --yes when not run interactively");
+        return ExitCode::from(1);
+    }
+    if !args.yes && !confirm() {
+        println!("Nothing removed");
+        return ExitCode::from(0);
+    }
+
+    let mut failed = false;
+    for (i, _) in &remove {
+        let path = &paths[*i];
+        let removed = if items[*i].mirrored {
+            fs::remove_file(path)
+        } else {
+            fs::remove_dir_all(path)
+        };
+        if let Err(e) = removed {
+            println!("Could not remove {}: {}", path.display(), e);
+            failed = true;
+        } else if let Some(parent) = path.parent() {
+            // Only succeeds if it was the last version of the tool, or the
+            // mirror's last file from a host
+            let _ = fs::remove_dir(parent);
+        }
+    }
+    println!(
+        "Removed {} versions, freed {}",
+        remove.len(),
+        human_size(freed)
+    );
+    ExitCode::from(if failed { 1 } else { 0 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(tool: &str, version: &str, last_used: u64, size: u64) -> Item {
+        Item {
+            tool: tool.to_string(),
+            version: Version::parse(version).ok(),
+            last_used,
+            size,
+            mirrored: false,
+        }
+    }
+
+    fn removed(items: &[Item], policy: &Policy, now: u64) -> Vec<usize> {
+        select(items, policy, now)
+            .into_iter()
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse_age("30d"), Ok(30 * 86400));
+        assert_eq!(parse_age("12h"), Ok(12 * 3600));
+        assert_eq!(parse_age("2w"), Ok(14 * 86400));
+        assert_eq!(parse_age("7"), Ok(7 * 86400));
+        assert!(parse_age("soon").is_err());
+        assert!(parse_age("3y").is_err());
+
+        assert_eq!(parse_size("10G"), Ok(10 << 30));
+        assert_eq!(parse_size("500mb"), Ok(500 << 20));
+        assert_eq!(parse_size("1.5G"), Ok(3 << 29));
+        assert_eq!(parse_size("100"), Ok(100));
+        assert!(parse_size("lots").is_err());
+    }
+
+    #[test]
+    fn test_select() {
+        let day = 86400;
+        let now = 100 * day;
+        let items = vec![
+            item("java", "17.0.9", now - 40 * day, 300),
+            item("java", "21.0.1", now - day, 300),
+            item("java", "11.0.2", now - 5 * day, 200),
+            item("node", "20.1.0", now - 10 * day, 100),
+        ];
+
+        let older = Policy {
+            older_than: Some(30 * day),
+            ..Default::default()
+        };
+        assert_eq!(removed(&items, &older, now), vec![0]);
+
+        let keep = Policy {
+            keep_latest: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(removed(&items, &keep, now), vec![0, 2]);
+
+        // 900 total, evict oldest use first: java 17 (40d), then node (10d)
+        let size = Policy {
+            max_size: Some(550),
+            ..Default::default()
+        };
+        assert_eq!(removed(&items, &size, now), vec![0, 3]);
+
+        // What the others already remove counts towards the size
+        let both = Policy {
+            older_than: Some(30 * day),
+            max_size: Some(600),
+            ..Default::default()
+        };
+        assert_eq!(removed(&items, &both, now), vec![0]);
+
+        // Mirrored files go by age and size, never by count
+        let mut items = items;
+        items.push(Item {
+            mirrored: true,
+            ..item(MIRROR, "", now - 50 * day, 1000)
+        });
+        items.push(Item {
+            mirrored: true,
+            ..item(MIRROR, "", now - 2 * day, 10)
+        });
+        assert_eq!(removed(&items, &keep, now), vec![0, 2]);
+        assert_eq!(removed(&items, &older, now), vec![0, 4]);
+        assert_eq!(removed(&items, &size, now), vec![0, 3, 4]);
+    }
+}
@@ -0,0 +1,340 @@
+use std::collections::HashMap;
+use std::env;
+use std::process::{Command, ExitCode};
+
+use log::info;
+
+use crate::config::GgConfig;
+use crate::executor::AppInput;
+use crate::prepare::{config_cmds, executors_for, prep_all, with_deps, Prepared};
+
+/// Shells `gg activate` knows how to hook into.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Pwsh,
+}
+
+impl Shell {
+    pub fn parse(name: &str) -> Option<Shell> {
+        match name {
+            "bash" => Some(Shell::Bash),
+            "zsh" => Some(Shell::Zsh),
+            "fish" => Some(Shell::Fish),
+            "pwsh" | "powershell" => Some(Shell::Pwsh),
+            _ => None,
+        }
+    }
+
+    fn quote(self, value: &str) -> String {
+        match self {
+            Shell::Bash | Shell::Zsh => format!("'{}'", value.replace('\'', r"'\''")),
+            Shell::Fish => format!("'{}'", value.replace('\\', r"\\").replace('\'', r"\'")),
+            Shell::Pwsh => format!("'{}'", value.replace('\'', "''")),
+        }
+    }
+
+    fn export(self, key: &str, value: &str) -> String {
+        let value = self.quote(value);
+        match self {
+            Shell::Bash | Shell::Zsh => format!("export {key}={value};"),
+            Shell::Fish => format!("set -gx {key} {value};"),
+            Shell::Pwsh => format!("$env:{key} = {value};"),
+        }
+    }
+
+    fn unset(self, key: &str) -> String {
+        match self {
+            Shell::Bash | Shell::Zsh => format!("unset {key};"),
+            Shell::Fish => format!("set -e {key};"),
+            Shell::Pwsh => format!("Remove-Item Env:{key} -ErrorAction SilentlyContinue;"),
+        }
+    }
+}
+
+/// How the hook calls back into gg. gg.cmd sets GG_CMD_PATH, and going through
+/// it keeps the hook working after gg updates itself.
+fn gg_cmd_path() -> String {
+    env::var("GG_CMD_PATH").unwrap_or_else(|_| {
+        env::current_exe()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|_| "gg".to_string())
+    })
+}
+
+/// The prompt hook printed by `gg activate <shell>`, meant to be eval'd from the
+/// shell's rc file. It only calls gg when the directory changes.
+pub fn activate_script(shell: Shell) -> String {
+    let gg = shell.quote(&gg_cmd_path());
+    match shell {
+        Shell::Bash => format!(
+            r#"_gg_hook() {{
+  local previous_exit_status=$?
+  if [ "$PWD" != "${{_GG_LAST_PWD:-}}" ]; then
+    _GG_LAST_PWD="$PWD"
+    eval "$({gg} hook-env bash)"
+  fi
+  return $previous_exit_status
+}}
+if [[ ";${{PROMPT_COMMAND:-}};" != *";_gg_hook;"* ]]; then
+  PROMPT_COMMAND="_gg_hook${{PROMPT_COMMAND:+;$PROMPT_COMMAND}}"
+fi
+"#
+        ),
+        Shell::Zsh => format!(
+            r#"_gg_hook() {{
+  eval "$({gg} hook-env zsh)"
+}}
+typeset -ag chpwd_functions
+if (( ! ${{chpwd_functions[(I)_gg_hook]}} )); then
+  chpwd_functions=(_gg_hook $chpwd_functions)
+fi
+_gg_hook
+"#
+        ),
+        Shell::Fish => format!(
+            r#"function _gg_hook --on-variable PWD
+    {gg} hook-env fish | source
+end
+_gg_hook
+"#
+        ),
+        Shell::Pwsh => format!(
+            r#"function global:_gg_hook {{
+  if ($PWD.Path -ne $global:_gg_last_pwd) {{
+    $global:_gg_last_pwd = $PWD.Path
+    $code = & {gg} hook-env pwsh | Out-String
+    if ($code.Trim()) {{ Invoke-Expression $code }}
+  }}
+}}
+if (-not $global:_gg_orig_prompt) {{
+  $global:_gg_orig_prompt = $function:prompt
+  function global:prompt {{ _gg_hook; & $global:_gg_orig_prompt }}
+}}
+"#
+        ),
+    }
+}
+
+/// Prep every `[dependencies]` tool (and what they depend on).
+async fn prep_project(input: &AppInput, config: &GgConfig) -> Result<Prepared, String> {
+    let executors = with_deps(executors_for(&config_cmds(config)), input).await;
+    let mut prepared = prep_all(&executors, input)
+        .await
+        .map_err(|errors| format!("Prep failed: {}", errors.join(", ")))?;
+    prepared.apply_config(config, &executors);
+    Ok(prepared)
+}
+
+fn joined_path(path_vars: &[String], base_path: &str) -> String {
+    let sep = if cfg!(windows) { ";" } else { ":" };
+    path_vars
+        .iter()
+        .map(String::as_str)
+        .chain(std::iter::once(base_path).filter(|p| !p.is_empty()))
+        .collect::<Vec<_>>()
+        .join(sep)
+}
+
+/// Shell code that moves the current shell into the project it's in: undo
+/// whatever the previous project set (we remember the original PATH and which
+/// vars we exported), then export the new one. Empty when nothing changed.
+fn hook_env_script(
+    shell: Shell,
+    current: &HashMap<String, String>,
+    root: Option<&str>,
+    prepared: Option<&Prepared>,
+) -> String {
+    let active = current.get("_GG_DIR").map(String::as_str);
+    if active == root {
+        return String::new();
+    }
+
+    let mut out = vec![];
+    let base_path = current
+        .get("_GG_ORIG_PATH")
+        .or_else(|| current.get("PATH"))
+        .cloned()
+        .unwrap_or_default();
+
+    if active.is_some() {
+        out.push(shell.export("PATH", &base_path));
+        if let Some(keys) = current.get("_GG_ENV_KEYS") {
+            for key in keys.split(',').filter(|k| !k.is_empty()) {
+                out.push(shell.unset(key));
+            }
+        }
+        for key in ["_GG_ORIG_PATH", "_GG_ENV_KEYS", "_GG_DIR"] {
+            out.push(shell.unset(key));
+        }
+    }
+
+    if let (Some(root), Some(prepared)) = (root, prepared) {
+        let mut keys = prepared.env_vars.keys().cloned().collect::<Vec<_>>();
+        keys.sort();
+        out.push(shell.export("_GG_ORIG_PATH", &base_path));
+        out.push(shell.export("PATH", &joined_path(&prepared.path_vars, &base_path)));
+        for key in &keys {
+            out.push(shell.export(key, &prepared.env_vars[key]));
+        }
+        out.push(shell.export("_GG_ENV_KEYS", &keys.join(",")));
+        out.push(shell.export("_GG_DIR", root));
+    }
+
+    out.join("\n")
+}
+
+/// `gg hook-env <shell>`: what the activate hook evals on every cd.
+pub async fn hook_env(shell: Shell, input: &AppInput, config: &GgConfig) -> Result<(), String> {
+    let current = env::vars().collect::<HashMap<_, _>>();
+    let root = config.root.as_ref().map(|p| p.to_string_lossy().to_string());
+
+    // Nothing to do, skip prepping
+    if current.get("_GG_DIR") == root.as_ref() {
+        return Ok(());
+    }
+
+    let prepared = match &root {
+        Some(_) if !config.dependencies.is_empty() => Some(prep_project(input, config).await?),
+        _ => None,
+    };
+    let root = prepared.as_ref().and(root);
+    let script = hook_env_script(shell, &current, root.as_deref(), prepared.as_ref());
+    if !script.is_empty() {
+        println!("{}", script);
+    }
+    Ok(())
+}
+
+/// `gg shell`: a subshell with every configured tool on PATH.
+pub async fn run_shell(input: &AppInput, config: &GgConfig) -> ExitCode {
+    if config.dependencies.is_empty() {
+        println!("No [dependencies] in gg.toml, nothing to put on PATH");
+        println!("Run 'gg config init' to create one");
+        return ExitCode::from(1);
+    }
+
+    let prepared = match prep_project(input, config).await {
+        Ok(prepared) => prepared,
+        Err(e) => {
+            println!("{}", e);
+            return ExitCode::from(1);
+        }
+    };
+
+    let shell = if cfg!(windows) {
+        env::var("COMSPEC").unwrap_or_else(|_| "cmd.exe".to_string())
+    } else {
+        env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string())
+    };
+    let path = joined_path(&prepared.path_vars, &env::var("PATH").unwrap_or_default());
+    info!("Starting {shell} with PATH: {path}");
+    println!("Entering gg shell ({shell}). Type 'exit' to leave.");
+
+    // Ctrl+C belongs to the shell now, not to us
+    let _ = ctrlc::set_handler(|| {});
+
+    let mut command = Command::new(&shell);
+    command.env("PATH", path).envs(&prepared.env_vars);
+    if let Some(root) = &config.root {
+        command.env("_GG_DIR", root);
+    }
+    match command.status() {
+        Ok(status) => ExitCode::from(status.code().unwrap_or(1) as u8),
+        Err(e) => {
+            println!("Failed to start {}: {}", shell, e);
+            ExitCode::from(1)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::AppPath;
+    use std::path::PathBuf;
+
+    fn prepared() -> Prepared {
+        let mut env_vars = HashMap::new();
+        env_vars.insert("JAVA_HOME".to_string(), "/c/java".to_string());
+        Prepared {
+            app_paths: vec![AppPath {
+                install_dir: PathBuf::from("/c/java"),
+            }],
+            path_vars: vec!["/c/java/bin".to_string()],
+            env_vars,
+        }
+    }
+
+    #[test]
+    fn test_quoting() {
+        assert_eq!(Shell::Bash.quote("it's"), r"'it'\''s'");
+        assert_eq!(Shell::Fish.quote(r"it's \o/"), r"'it\'s \\o/'");
+        assert_eq!(Shell::Pwsh.quote("it's"), "'it''s'");
+    }
+
+    #[test]
+    fn test_hook_env_enters_project() {
+        let mut current = HashMap::new();
+        current.insert("PATH".to_string(), "/usr/bin".to_string());
+
+        let script = hook_env_script(Shell::Bash, &current, Some("/p"), Some(&prepared()));
+        assert!(script.contains("export _GG_ORIG_PATH='/usr/bin';"));
+        assert!(script.contains(&format!(
+            "export PATH='{}';",
+            joined_path(&["/c/java/bin".to_string()], "/usr/bin")
+        )));
+        assert!(script.contains("export JAVA_HOME='/c/java';"));
+        assert!(script.contains("export _GG_ENV_KEYS='JAVA_HOME';"));
+        assert!(script.contains("export _GG_DIR='/p';"));
+        assert!(!script.contains("unset"));
+    }
+
+    #[test]
+    fn test_hook_env_leaves_project() {
+        let mut current = HashMap::new();
+        current.insert("PATH".to_string(), "/c/java/bin:/usr/bin".to_string());
+        current.insert("_GG_ORIG_PATH".to_string(), "/usr/bin".to_string());
+        current.insert("_GG_ENV_KEYS".to_string(), "JAVA_HOME".to_string());
+        current.insert("_GG_DIR".to_string(), "/p".to_string());
+
+        let script = hook_env_script(Shell::Fish, &current, None, None);
+        assert_eq!(
+            script,
+            [
+                "set -gx PATH '/usr/bin';",
+                "set -e JAVA_HOME;",
+                "set -e _GG_ORIG_PATH;",
+                "set -e _GG_ENV_KEYS;",
+                "set -e _GG_DIR;",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_hook_env_same_project_is_a_noop() {
+        let mut current = HashMap::new();
+        current.insert("_GG_DIR".to_string(), "/p".to_string());
+        assert_eq!(
+            hook_env_script(Shell::Pwsh, &current, Some("/p"), Some(&prepared())),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_activate_script_calls_hook_env() {
+        for (shell, name) in [
+            (Shell::Bash, "bash"),
+            (Shell::Zsh, "zsh"),
+            (Shell::Fish, "fish"),
+            (Shell::Pwsh, "pwsh"),
+        ] {
+            assert_eq!(Shell::parse(name), Some(shell));
+            assert!(activate_script(shell).contains(&format!("hook-env {name}")));
+        }
+        assert_eq!(Shell::parse("tcsh"), None);
+    }
+}
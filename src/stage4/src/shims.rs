@@ -0,0 +1,246 @@
+use std::collections::BTreeSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::warn;
+
+use crate::config::GgConfig;
+use crate::tools::get_tool_info;
+
+/// How a shim finds gg.cmd: next to the project (relative, so `.gg/bin` can be
+/// committed and still work on another machine), or wherever it lives.
+#[derive(Debug, PartialEq)]
+enum GgRef {
+    /// gg.cmd relative to the shim dir, '/'-separated
+    Relative(String),
+    Absolute(PathBuf),
+}
+
+/// Names that work as a file name and as a command on every platform
+fn is_plain_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+}
+
+/// Names to shim: every dependency, the names its tool also answers to (npm,
+/// npx for node) and every alias. Dependencies like `gh/cli/cli` or
+/// `npm:@biomejs/biome` have no name a shim could go by, those are skipped.
+fn shim_names(config: &GgConfig) -> Vec<String> {
+    let mut names = BTreeSet::new();
+    for dep in config.dependencies.keys() {
+        if !is_plain_name(dep) {
+            warn!("No shim for {}, run it with 'gg {}'", dep, dep);
+            continue;
+        }
+        names.insert(dep.to_string());
+        if let Some(tool) = get_tool_info(dep) {
+            names.insert(tool.name.to_string());
+            names.extend(tool.aliases.iter().map(|a| a.to_string()));
+        }
+    }
+    for alias in config.aliases.keys() {
+        if is_plain_name(alias) {
+            names.insert(alias.clone());
+        } else {
+            warn!("No shim for alias {}, run it with 'gg {}'", alias, alias);
+        }
+    }
+    names.into_iter().collect()
+}
+
+fn is_cmd_file(gg: &GgRef) -> bool {
+    match gg {
+        GgRef::Relative(p) => p.ends_with(".cmd"),
+        GgRef::Absolute(p) => p.extension().is_some_and(|e| e.eq_ignore_ascii_case("cmd")),
+    }
+}
+
+fn sh_shim(gg: &GgRef, name: &str) -> String {
+    let path = match gg {
+        GgRef::Relative(p) => format!("\"$(dirname \"$0\")/{p}\""),
+        GgRef::Absolute(p) => format!("'{}'", p.display().to_string().replace('\'', r"'\''")),
+    };
+    // gg.cmd is a polyglot, not something exec can run directly
+    let runner = if is_cmd_file(gg) { "sh " } else { "" };
+    format!("#!/bin/sh\nexec {runner}{path} {name} \"$@\"\n")
+}
+
+fn cmd_shim(gg: &GgRef, name: &str) -> String {
+    let path = match gg {
+        GgRef::Relative(p) => format!("%~dp0{}", p.replace('/', "\\")),
+        GgRef::Absolute(p) => p.display().to_string(),
+    };
+    format!("@echo off\r\ncall \"{path}\" {name} %*\r\nexit /b %ERRORLEVEL%\r\n")
+}
+
+fn ps1_shim(gg: &GgRef, name: &str) -> String {
+    let path = match gg {
+        GgRef::Relative(p) => format!("\"$PSScriptRoot/{p}\""),
+        GgRef::Absolute(p) => format!("'{}'", p.display().to_string().replace('\'', "''")),
+    };
+    format!("& {path} {name} @args\nexit $LASTEXITCODE\n")
+}
+
+/// gg.cmd as seen from `dir`: relative when it sits in the project, absolute
+/// otherwise.
+fn gg_ref(gg_cmd: &Path, dir: &Path, project_root: Option<&Path>) -> GgRef {
+    if let Some(root) = project_root {
+        if let (Ok(rel), Ok(dir_rel)) = (gg_cmd.strip_prefix(root), dir.strip_prefix(root)) {
+            let up = dir_rel.components().map(|_| "..").collect::<Vec<_>>();
+            let down = rel
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect::<Vec<_>>();
+            return GgRef::Relative([up.join("/"), down.join("/")].join("/"));
+        }
+    }
+    GgRef::Absolute(gg_cmd.to_path_buf())
+}
+
+fn write_shim(path: &Path, content: &str, executable: bool) -> Result<(), String> {
+    fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    #[cfg(unix)]
+    if executable {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+            .map_err(|e| format!("Failed to make {} executable: {}", path.display(), e))?;
+    }
+    #[cfg(not(unix))]
+    let _ = executable;
+    Ok(())
+}
+
+/// `gg shims [--global]`: a launcher per tool and alias in gg.toml, so editors
+/// and scripts calling `node` or `mvn` directly get the pinned versions. The
+/// project ones go in `.gg/bin`, `--global` ones in `~/.local/bin`.
+pub fn create_shims(config: &GgConfig, global: bool) -> Result<(), String> {
+    let project_root = config
+        .root
+        .clone()
+        .ok_or("No gg.toml found. Run 'gg config init' to create one")?;
+
+    let names = shim_names(config);
+    if names.is_empty() {
+        return Err("No [dependencies] or [aliases] in gg.toml, nothing to shim".to_string());
+    }
+
+    let dir = if global {
+        let home = env::var("HOME")
+            .or_else(|_| env::var("USERPROFILE"))
+            .map_err(|_| "Could not find home directory")?;
+        PathBuf::from(home).join(".local").join("bin")
+    } else {
+        project_root.join(".gg").join("bin")
+    };
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let gg_cmd = env::var("GG_CMD_PATH")
+        .map(PathBuf::from)
+        .or_else(|_| env::current_exe())
+        .map_err(|e| format!("Could not find gg itself: {}", e))?;
+    let gg = gg_ref(
+        &gg_cmd,
+        &dir,
+        if global { None } else { Some(&project_root) },
+    );
+
+    for name in &names {
+        write_shim(&dir.join(name), &sh_shim(&gg, name), true)?;
+        write_shim(
+            &dir.join(format!("{name}.cmd")),
+            &cmd_shim(&gg, name),
+            false,
+        )?;
+        write_shim(
+            &dir.join(format!("{name}.ps1")),
+            &ps1_shim(&gg, name),
+            false,
+        )?;
+    }
+
+    println!("Created shims in {}: {}", dir.display(), names.join(", "));
+    let on_path = env::var_os("PATH")
+        .map(|p| env::split_paths(&p).any(|p| p == dir))
+        .unwrap_or(false);
+    if !on_path {
+        println!("Add {} to your PATH to use them", dir.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shim_names_include_tool_aliases_and_config_aliases() {
+        let mut config = GgConfig::default();
+        config
+            .dependencies
+            .insert("node".to_string(), "20".to_string());
+        config
+            .dependencies
+            .insert("mvn".to_string(), "3".to_string());
+        config
+            .aliases
+            .insert("build".to_string(), "gradle build".into());
+
+        assert_eq!(
+            shim_names(&config),
+            vec!["build", "maven", "mvn", "node", "npm", "npx"]
+        );
+    }
+
+    #[test]
+    fn test_shim_names_skip_deps_without_a_plain_name() {
+        let mut config = GgConfig::default();
+        for dep in ["gh/cli/cli", "npm:@biomejs/biome", "node"].iter() {
+            config
+                .dependencies
+                .insert(dep.to_string(), "latest".to_string());
+        }
+
+        assert_eq!(shim_names(&config), vec!["node", "npm", "npx"]);
+    }
+
+    #[test]
+    fn test_gg_ref_relative_inside_project() {
+        let root = Path::new("/p");
+        assert_eq!(
+            gg_ref(Path::new("/p/gg.cmd"), Path::new("/p/.gg/bin"), Some(root)),
+            GgRef::Relative("../../gg.cmd".to_string())
+        );
+        assert_eq!(
+            gg_ref(
+                Path::new("/usr/bin/gg"),
+                Path::new("/p/.gg/bin"),
+                Some(root)
+            ),
+            GgRef::Absolute(PathBuf::from("/usr/bin/gg"))
+        );
+        assert_eq!(
+            gg_ref(Path::new("/p/gg.cmd"), Path::new("/h/.local/bin"), None),
+            GgRef::Absolute(PathBuf::from("/p/gg.cmd"))
+        );
+    }
+
+    #[test]
+    fn test_shim_contents() {
+        let rel = GgRef::Relative("../../gg.cmd".to_string());
+        assert_eq!(
+            sh_shim(&rel, "node"),
+            "#!/bin/sh\nexec sh \"$(dirname \"$0\")/../../gg.cmd\" node \"$@\"\n"
+        );
+        assert!(cmd_shim(&rel, "node").contains("call \"%~dp0..\\..\\gg.cmd\" node %*"));
+        assert!(ps1_shim(&rel, "node").starts_with("& \"$PSScriptRoot/../../gg.cmd\" node @args"));
+
+        let abs = GgRef::Absolute(PathBuf::from("/opt/gg"));
+        assert_eq!(
+            sh_shim(&abs, "mvn"),
+            "#!/bin/sh\nexec '/opt/gg' mvn \"$@\"\n"
+        );
+    }
+}
@@ -81,6 +81,15 @@ impl Target {
         }
     }
 
+    /// `linux-x86_64`, `linux-x86_64-musl`, `mac-arm64`, `windows-x86_64`
+    pub fn name(&self) -> String {
+        let name = format!("{:?}-{:?}", self.os, self.arch).to_lowercase();
+        match self.variant {
+            Some(Variant::Musl) => format!("{}-musl", name),
+            _ => name,
+        }
+    }
+
     fn detect_arch_from_input(parts: &[&str], _input: &str) -> Arch {
         match parts.first().unwrap_or(&"") {
             x if x.contains("x86_64") => Arch::X86_64,
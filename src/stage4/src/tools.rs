@@ -15,6 +15,7 @@ use crate::executors::jbang::JBangExecutor;
 use crate::executors::maven::Maven;
 use crate::executors::node::{Node, NpmPackageSpec};
 use crate::executors::openapigenerator::OpenAPIGenerator;
+use crate::executors::package_manager::PackageManager;
 use crate::executors::python::Python;
 use crate::executors::rat::Rat;
 use crate::executors::ruby::Ruby;
@@ -45,14 +46,9 @@ pub static TOOL_REGISTRY: LazyLock<HashMap<&'static str, ToolInfo>> = LazyLock::
             aliases: vec!["npm", "npx"],
             description: "Node.js JavaScript runtime",
             category: ToolCategory::Language,
-            tags: vec!["+lts"],
+            tags: vec!["+lts", "+<lts codename>"],
             example: Some("gg node@14 -e 'console.log(1)'"),
-            factory: |cmd| {
-                Some(Box::new(Node {
-                    executor_cmd: cmd,
-                    npm_package: None,
-                }))
-            },
+            factory: |cmd| Some(Box::new(Node::new(cmd, None))),
         },
         ToolInfo {
             name: "python",
@@ -61,7 +57,12 @@ pub static TOOL_REGISTRY: LazyLock<HashMap<&'static str, ToolInfo>> = LazyLock::
             category: ToolCategory::Language,
             tags: vec![],
             example: Some("gg python --version"),
-            factory: |cmd| Some(Box::new(Python { executor_cmd: cmd })),
+            factory: |cmd| {
+                Some(Box::new(Python {
+                    executor_cmd: cmd,
+                    pip_package: None,
+                }))
+            },
         },
         ToolInfo {
             name: "java",
@@ -81,9 +82,10 @@ pub static TOOL_REGISTRY: LazyLock<HashMap<&'static str, ToolInfo>> = LazyLock::
                 "+fx",
                 "+normal",
                 "+hotspot",
+                "+openj9",
             ],
             example: Some("gg java@17 -version"),
-            factory: |cmd| Some(Box::new(Java { executor_cmd: cmd })),
+            factory: |cmd| Some(Box::new(Java::new(cmd))),
         },
         ToolInfo {
             name: "go",
@@ -117,7 +119,7 @@ pub static TOOL_REGISTRY: LazyLock<HashMap<&'static str, ToolInfo>> = LazyLock::
             aliases: vec![],
             description: "Gradle build automation tool",
             category: ToolCategory::BuildTool,
-            tags: vec![],
+            tags: vec!["+bin", "+all", "+rc", "+milestone", "+nightly"],
             example: Some("gg gradle@6:java@17 clean build"),
             factory: |cmd| Some(Box::new(Gradle::new(cmd))),
         },
@@ -128,7 +130,34 @@ pub static TOOL_REGISTRY: LazyLock<HashMap<&'static str, ToolInfo>> = LazyLock::
             category: ToolCategory::BuildTool,
             tags: vec![],
             example: Some("gg maven compile"),
-            factory: |cmd| Some(Box::new(Maven { executor_cmd: cmd })),
+            factory: |cmd| Some(Box::new(Maven::new(cmd))),
+        },
+        ToolInfo {
+            name: "pnpm",
+            aliases: vec!["pnpx"],
+            description: "pnpm package manager, run on gg's Node.js",
+            category: ToolCategory::BuildTool,
+            tags: vec![],
+            example: Some("gg pnpm install"),
+            factory: |cmd| Some(Box::new(PackageManager::new(cmd, "pnpm"))),
+        },
+        ToolInfo {
+            name: "yarn",
+            aliases: vec!["yarnpkg"],
+            description: "Yarn package manager, classic and berry, run on gg's Node.js",
+            category: ToolCategory::BuildTool,
+            tags: vec![],
+            example: Some("gg yarn@1 install"),
+            factory: |cmd| Some(Box::new(PackageManager::new(cmd, "yarn"))),
+        },
+        ToolInfo {
+            name: "corepack",
+            aliases: vec![],
+            description: "Node.js package manager manager, run on gg's Node.js",
+            category: ToolCategory::BuildTool,
+            tags: vec![],
+            example: Some("gg corepack pnpm --version"),
+            factory: |cmd| Some(Box::new(PackageManager::new(cmd, "corepack"))),
         },
         ToolInfo {
             name: "bld",
@@ -293,14 +322,14 @@ pub static TOOL_REGISTRY: LazyLock<HashMap<&'static str, ToolInfo>> = LazyLock::
             tags: vec![],
             example: Some("gg gemini-cli --version"),
             factory: |cmd| {
-                Some(Box::new(Node {
-                    executor_cmd: cmd,
-                    npm_package: Some(NpmPackageSpec {
+                Some(Box::new(Node::new(
+                    cmd,
+                    Some(NpmPackageSpec {
                         name: "gemini-cli".to_string(),
                         package: "@google/gemini-cli".to_string(),
                         bin: "gemini".to_string(),
                     }),
-                }))
+                )))
             },
         },
         ToolInfo {
@@ -334,14 +363,14 @@ pub static TOOL_REGISTRY: LazyLock<HashMap<&'static str, ToolInfo>> = LazyLock::
             // bundles, .zst); @openai/codex resolves the right stable native
             // binary per platform via optionalDependencies.
             factory: |cmd| {
-                Some(Box::new(Node {
-                    executor_cmd: cmd,
-                    npm_package: Some(NpmPackageSpec {
+                Some(Box::new(Node::new(
+                    cmd,
+                    Some(NpmPackageSpec {
                         name: "codex".to_string(),
                         package: "@openai/codex".to_string(),
                         bin: "codex".to_string(),
                     }),
-                }))
+                )))
             },
         },
         ToolInfo {
@@ -352,14 +381,14 @@ pub static TOOL_REGISTRY: LazyLock<HashMap<&'static str, ToolInfo>> = LazyLock::
             tags: vec![],
             example: Some("gg qwen --version"),
             factory: |cmd| {
-                Some(Box::new(Node {
-                    executor_cmd: cmd,
-                    npm_package: Some(NpmPackageSpec {
+                Some(Box::new(Node::new(
+                    cmd,
+                    Some(NpmPackageSpec {
                         name: "qwen".to_string(),
                         package: "@qwen-code/qwen-code".to_string(),
                         bin: "qwen".to_string(),
                     }),
-                }))
+                )))
             },
         },
         ToolInfo {